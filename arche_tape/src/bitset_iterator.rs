@@ -43,13 +43,19 @@ mod bitset_iterator {
         fn next(&mut self) -> Option<Self::Item> {
             loop {
                 if self.bits_remaining == 0 {
+                    if self.index >= self.bit_length as usize {
+                        return None;
+                    }
+
+                    // Bitvecs shorter than bit_length are treated as being padded with zeros so that negated bitvecs
+                    // still match the bits past their end
                     // We have to initialise filtered to a proper value so we hand write the first iteration of the loop :(
                     let mut iter = self.iters.borrow_mut().iter_mut();
                     let (first_iter, first_map) = iter.next()?;
-                    let mut filtered: usize = first_map(*first_iter.next()?);
+                    let mut filtered: usize = first_map(first_iter.next().copied().unwrap_or(0));
 
                     for (iter, map) in iter {
-                        filtered &= map(*iter.next()?);
+                        filtered &= map(iter.next().copied().unwrap_or(0));
                     }
 
                     self.bits_remaining = usize::BITS;
@@ -72,11 +78,10 @@ mod bitset_iterator {
                 self.index += zeros as usize + 1;
 
                 if self.index > self.bit_length as usize {
-                    // Hack but make sure that calling next() after None is returned continues to return None by
-                    // setting an empty iterator and making the subsequent Next calls try and call next() on it
+                    // Make sure that calling next() after None is returned continues to return None, the index is
+                    // past bit_length so the next refill of current_bits will return None
                     self.current_bits = 0;
-                    // Indexing by 0 should always be fine since an empty slice will never make it this far
-                    self.iters.borrow_mut()[0].0 = [].iter();
+                    self.bits_remaining = 0;
                     return None;
                 }
 
@@ -144,20 +149,25 @@ impl Bitvec {
 
 pub struct Bitsetsss {
     bitsets: Vec<Bitvec>,
+    /// Pairs use the index of their target as their index so they can't be stored in the vec with everything else
+    pair_bitsets: HashMap<EcsId, Bitvec, crate::utils::TypeIdHasherBuilder>,
 }
 
 use crate::EcsId;
+use std::collections::HashMap;
 impl Bitsetsss {
     #[allow(unused)]
     pub(crate) fn new() -> Self {
         Self {
             bitsets: Vec::new(),
+            pair_bitsets: HashMap::with_hasher(crate::utils::TypeIdHasherBuilder()),
         }
     }
 
     pub(crate) fn with_capacity(cap: usize) -> Self {
         Self {
             bitsets: Vec::with_capacity(cap),
+            pair_bitsets: HashMap::with_hasher(crate::utils::TypeIdHasherBuilder()),
         }
     }
 
@@ -172,10 +182,31 @@ impl Bitsetsss {
     }
 
     pub(crate) fn get_bitvec(&self, comp_id: EcsId) -> Option<&Bitvec> {
+        if comp_id.is_pair() {
+            return self.pair_bitsets.get(&comp_id);
+        }
+
         self.bitsets.get(comp_id.uindex())
     }
 
+    /// Setting a bit for a pair will also set it for the wildcard pairs that match the pair
     pub(crate) fn set_bit(&mut self, entity: EcsId, index: usize, value: bool) {
+        if let Some(wildcards) = entity.pair_wildcards() {
+            let bitsets = wildcards.iter().chain(std::iter::once(&entity));
+            for &id in bitsets {
+                // Other pairs may still set the bit in the wildcard pair so we can only ever set it to true
+                if id != entity && value == false {
+                    continue;
+                }
+
+                self.pair_bitsets
+                    .entry(id)
+                    .or_insert_with(Bitvec::new)
+                    .set_bit(index, value);
+            }
+            return;
+        }
+
         if entity.uindex() >= self.bitsets.len() {
             self.insert_bitvec(entity);
         }
@@ -184,6 +215,19 @@ impl Bitsetsss {
         bitvec.set_bit(index, value);
     }
 
+    /// Unsets every bit for this entity, used when the entity is despawned so that a later entity with the same index starts empty
+    pub(crate) fn clear_bitvec(&mut self, entity: EcsId) {
+        if entity.is_pair() {
+            self.pair_bitsets.remove(&entity);
+            return;
+        }
+
+        if let Some(bitvec) = self.bitsets.get_mut(entity.uindex()) {
+            bitvec.data.clear();
+            bitvec.len = 0;
+        }
+    }
+
     #[allow(unused)]
     pub(crate) fn push_bit(&mut self, entity: EcsId, value: bool) {
         if entity.uindex() >= self.bitsets.len() {
@@ -0,0 +1,174 @@
+use crate::world::{ArchIndex, ComponentMeta, EntityMeta, InstanceMeta};
+use crate::{Component, ComponentTicks, EcsId, World};
+use std::mem::{ManuallyDrop, MaybeUninit};
+
+/// A group of components that can be added to or removed from an entity with a single archetype move
+///
+/// # Safety
+///
+///    ``Bundle::take_components`` must pass a pointer to a valid instance of each component in the same order as the
+///    ids returned by ``Bundle::component_ids``, and must not use or drop the components afterwards
+pub unsafe trait Bundle: 'static {
+    /// The ids of every component in the bundle, creating them if they don't exist yet
+    fn component_ids(world: &mut World) -> Vec<EcsId>;
+
+    /// Calls ``func`` with a pointer to each component, ``func`` takes ownership of all the components
+    fn take_components(self, func: impl FnOnce(&[*mut u8]));
+}
+
+macro_rules! impl_bundle {
+    ($($T:ident)*) => {
+        unsafe impl<$($T: Component),*> Bundle for ($($T,)*) {
+            fn component_ids(world: &mut World) -> Vec<EcsId> {
+                vec![$(world.get_or_create_type_id_ecsid::<$T>(),)*]
+            }
+
+            #[allow(non_snake_case)]
+            fn take_components(self, func: impl FnOnce(&[*mut u8])) {
+                let ($($T,)*) = self;
+                $(
+                    let mut $T = ManuallyDrop::new($T);
+                )*
+                func(&[$(&mut *$T as *mut $T as *mut u8,)*]);
+            }
+        }
+    };
+}
+
+impl_bundle!(A B C D E F G H J K);
+impl_bundle!(A B C D E F G H J);
+impl_bundle!(A B C D E F G H);
+impl_bundle!(A B C D E F G);
+impl_bundle!(A B C D E F);
+impl_bundle!(A B C D E);
+impl_bundle!(A B C D);
+impl_bundle!(A B C);
+impl_bundle!(A B);
+impl_bundle!(A);
+
+impl World {
+    /// Adds every component in ``bundle`` to the entity, moving it between archetypes once. Components that the
+    /// entity already has are replaced
+    pub fn add_bundle<B: Bundle>(&mut self, entity: EcsId, bundle: B) {
+        assert!(self.entities.is_alive(entity));
+        let ids = B::component_ids(self);
+        // Checked before the bundle is taken apart so that a panic doesn't leave its components half moved
+        let mut sorted_ids = ids.clone();
+        sorted_ids.sort();
+        sorted_ids.dedup();
+        assert!(
+            sorted_ids.len() == ids.len(),
+            "Attempted to add the same component twice in a bundle"
+        );
+
+        let meta = self.get_entity_meta(entity).unwrap();
+        let mut components = self.archetypes[meta.instance_meta.archetype.0]
+            .comp_ids
+            .iter()
+            .filter(|id| !ids.contains(id))
+            .map(|&id| (id, None))
+            .collect::<Vec<(EcsId, Option<*mut MaybeUninit<u8>>)>>();
+
+        bundle.take_components(|ptrs| {
+            for (&id, &ptr) in ids.iter().zip(ptrs) {
+                components.push((id, Some(ptr as *mut MaybeUninit<u8>)));
+            }
+            // Safe because the ids were just created and ``take_components`` gives us ownership of the data
+            unsafe { self.move_entity_dynamic(entity, &mut components) };
+        });
+    }
+
+    /// Removes every component in ``B`` that the entity has, moving it between archetypes once
+    pub fn remove_bundle<B: Bundle>(&mut self, entity: EcsId) {
+        assert!(self.entities.is_alive(entity));
+        let ids = B::component_ids(self);
+
+        let meta = self.get_entity_meta(entity).unwrap();
+        let comp_ids = &self.archetypes[meta.instance_meta.archetype.0].comp_ids;
+        if !comp_ids.iter().any(|id| ids.contains(id)) {
+            return;
+        }
+        let mut components = comp_ids
+            .iter()
+            .filter(|id| !ids.contains(id))
+            .map(|&id| (id, None))
+            .collect::<Vec<_>>();
+        // Safe because the ids are the entity's current components so are alive and unique
+        unsafe { self.move_entity_dynamic(entity, &mut components) };
+    }
+
+    /// Spawns an entity for every bundle in ``iter`` before returning the spawned entities. The archetype is only
+    /// looked up once and its columns are reserved up front using the iterator's size hint
+    pub fn spawn_batch<B: Bundle, I: IntoIterator<Item = B>>(
+        &mut self,
+        iter: I,
+    ) -> impl Iterator<Item = EcsId> {
+        let ids = B::component_ids(self);
+        let mut sorted_ids = ids.clone();
+        sorted_ids.sort();
+        sorted_ids.dedup();
+        assert!(
+            sorted_ids.len() == ids.len(),
+            "Attempted to add the same component twice in a bundle"
+        );
+
+        let ArchIndex(archetype_idx) = self.find_or_create_archetype(&sorted_ids);
+        // The storage for each component in the order that the bundle gives them
+        let storage_idxs = ids
+            .iter()
+            .map(|id| self.archetypes[archetype_idx].comp_lookup[id])
+            .collect::<Vec<_>>();
+        let hooks = self.component_hooks(&ids, |meta| meta.on_add);
+
+        let iter = iter.into_iter();
+        let (additional, _) = iter.size_hint();
+        self.flush();
+        let change_tick = self.change_tick();
+
+        let archetype = &mut self.archetypes[archetype_idx];
+        archetype.entities.reserve(additional);
+        for (_, storage, ticks) in archetype.component_storages.iter_mut() {
+            storage.get_mut().reserve(additional);
+            ticks.get_mut().ticks.reserve(additional);
+        }
+        let required_metas = self.entities.generations.len() + additional;
+        if self.ecs_id_meta.len() < required_metas {
+            self.ecs_id_meta.resize_with(required_metas, || None);
+        }
+
+        let mut spawned = Vec::with_capacity(additional);
+        for bundle in iter {
+            let entity = self.entities.spawn();
+            let archetype = &mut self.archetypes[archetype_idx];
+            bundle.take_components(|ptrs| {
+                for (&ptr, &storage_idx) in ptrs.iter().zip(storage_idxs.iter()) {
+                    let (_, storage, ticks) = &mut archetype.component_storages[storage_idx];
+                    // Safe because the storage is for the component's id and ``take_components`` gives us ownership
+                    unsafe { storage.get_mut().push_raw(ptr as *mut MaybeUninit<u8>) };
+                    ticks.get_mut().push(ComponentTicks::new(change_tick));
+                }
+            });
+            archetype.entities.push(entity);
+
+            // The size hint may have been too small
+            if self.ecs_id_meta.len() <= entity.uindex() {
+                self.ecs_id_meta.resize_with(entity.uindex() + 1, || None);
+            }
+            self.ecs_id_meta[entity.uindex()] = Some(EntityMeta {
+                instance_meta: InstanceMeta {
+                    archetype: ArchIndex(archetype_idx),
+                    index: archetype.entities.len() - 1,
+                },
+                component_meta: ComponentMeta::unit(),
+            });
+            spawned.push(entity);
+        }
+
+        if !hooks.is_empty() {
+            for &entity in spawned.iter() {
+                self.run_hooks(entity, hooks.clone());
+            }
+        }
+        spawned.into_iter()
+    }
+}
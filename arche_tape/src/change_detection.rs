@@ -0,0 +1,126 @@
+/// How many ticks can pass before ``World::check_change_ticks`` clamps old ticks again
+pub const CHECK_TICK_THRESHOLD: u32 = 1 << 29;
+/// Ticks older than this are clamped to this age by ``World::check_change_ticks``. Every stored tick stays within
+/// ``MAX_CHANGE_AGE + CHECK_TICK_THRESHOLD`` of the world's change tick so ticks can be compared after they wrap around
+pub const MAX_CHANGE_AGE: u32 = 1 << 30;
+
+/// Returns whichever of the two stored ticks is newer, both must be within ``i32::MAX`` ticks of each other
+pub(crate) fn newest_tick(a: u32, b: u32) -> u32 {
+    match (b.wrapping_sub(a) as i32) > 0 {
+        true => b,
+        false => a,
+    }
+}
+
+/// Clamps ``tick`` so that it is at most ``MAX_CHANGE_AGE`` ticks older than ``change_tick``
+fn clamp_tick(tick: &mut u32, change_tick: u32) {
+    if change_tick.wrapping_sub(*tick) > MAX_CHANGE_AGE {
+        *tick = change_tick.wrapping_sub(MAX_CHANGE_AGE);
+    }
+}
+
+/// The ticks at which a component was added to an entity and last changed
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ComponentTicks {
+    pub added: u32,
+    pub changed: u32,
+}
+
+impl ComponentTicks {
+    pub fn new(tick: u32) -> Self {
+        Self {
+            added: tick,
+            changed: tick,
+        }
+    }
+}
+
+/// The ticks that a query compares against and marks rows changed with
+#[derive(Copy, Clone, Debug)]
+pub struct QueryTicks {
+    /// Rows with a tick newer than this are considered added/changed
+    pub last_run: u32,
+    /// The tick that rows are marked changed with
+    pub change_tick: u32,
+}
+
+impl QueryTicks {
+    /// Returns true if ``tick`` is newer than ``last_run``. Ticks are compared by how long before ``change_tick`` they
+    /// were so this still works once the world's change tick wraps around
+    pub fn is_newer(&self, tick: u32) -> bool {
+        self.change_tick.wrapping_sub(tick) < self.change_tick.wrapping_sub(self.last_run)
+    }
+}
+
+/// The ticks for every row in a component storage, kept in the same order as the storage
+pub struct ColumnTicks {
+    pub(crate) ticks: Vec<ComponentTicks>,
+    /// Upper bounds of the ticks in ``ticks`` so that archetypes with nothing newer than a query's last run can be skipped without checking every row
+    pub(crate) last_added: u32,
+    pub(crate) last_changed: u32,
+}
+
+impl ColumnTicks {
+    pub(crate) fn new() -> Self {
+        Self {
+            ticks: Vec::new(),
+            last_added: 0,
+            last_changed: 0,
+        }
+    }
+
+    pub(crate) fn push(&mut self, ticks: ComponentTicks) {
+        // The bounds of an empty column may be arbitrarily old so they can't be compared with
+        match self.ticks.is_empty() {
+            true => {
+                self.last_added = ticks.added;
+                self.last_changed = ticks.changed;
+            }
+            false => {
+                self.last_added = newest_tick(self.last_added, ticks.added);
+                self.last_changed = newest_tick(self.last_changed, ticks.changed);
+            }
+        }
+        self.ticks.push(ticks);
+    }
+
+    pub(crate) fn swap_remove(&mut self, idx: usize) -> ComponentTicks {
+        self.ticks.swap_remove(idx)
+    }
+
+    pub(crate) fn append(&mut self, other: &mut ColumnTicks) {
+        if other.ticks.is_empty() {
+            return;
+        }
+        match self.ticks.is_empty() {
+            true => {
+                self.last_added = other.last_added;
+                self.last_changed = other.last_changed;
+            }
+            false => {
+                self.last_added = newest_tick(self.last_added, other.last_added);
+                self.last_changed = newest_tick(self.last_changed, other.last_changed);
+            }
+        }
+        self.ticks.append(&mut other.ticks);
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.ticks.clear();
+    }
+
+    pub(crate) fn mark_changed(&mut self, idx: usize, tick: u32) {
+        self.last_changed = newest_tick(self.last_changed, tick);
+        self.ticks[idx].changed = tick;
+    }
+
+    /// Clamps every tick older than ``MAX_CHANGE_AGE`` so that none of them wrap around
+    pub(crate) fn clamp_ticks(&mut self, change_tick: u32) {
+        for ticks in self.ticks.iter_mut() {
+            clamp_tick(&mut ticks.added, change_tick);
+            clamp_tick(&mut ticks.changed, change_tick);
+        }
+        clamp_tick(&mut self.last_added, change_tick);
+        clamp_tick(&mut self.last_changed, change_tick);
+    }
+}
@@ -0,0 +1,324 @@
+use crate::world::ComponentMeta;
+use crate::{Component, EcsId, World};
+use std::collections::HashSet;
+use std::mem::{ManuallyDrop, MaybeUninit};
+
+#[derive(Copy, Clone)]
+enum ComponentId {
+    Id(EcsId),
+    /// Typed components may not have an ``EcsId`` yet and creating one needs ``&mut World``
+    Type(fn(&mut World) -> EcsId),
+}
+
+impl ComponentId {
+    fn resolve(self, world: &mut World) -> EcsId {
+        match self {
+            ComponentId::Id(id) => id,
+            ComponentId::Type(get_id) => get_id(world),
+        }
+    }
+}
+
+struct CommandComponent {
+    id: ComponentId,
+    /// Offset of the component's data in ``Commands::data``
+    offset: usize,
+    meta: ComponentMeta,
+}
+
+enum Command {
+    Spawn(EcsId),
+    Despawn(EcsId),
+    /// Index into ``Commands::components``
+    Add(EcsId, usize),
+    Remove(EcsId, ComponentId),
+}
+
+impl Command {
+    fn entity(&self) -> EcsId {
+        match *self {
+            Command::Spawn(entity)
+            | Command::Despawn(entity)
+            | Command::Add(entity, _)
+            | Command::Remove(entity, _) => entity,
+        }
+    }
+}
+
+/// Records structural changes to be applied later with ``World::apply_commands``, this allows spawning, despawning and
+/// adding/removing components while the world is borrowed by queries.
+///
+/// Consecutive commands for the same entity are applied with a single archetype move.
+#[derive(Default)]
+pub struct Commands {
+    commands: Vec<Command>,
+    components: Vec<CommandComponent>,
+    /// Components are moved into this arena unaligned, the same as ``EntityBuilder``
+    data: Vec<MaybeUninit<u8>>,
+}
+
+impl Drop for Commands {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl Commands {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Drops every recorded command and component, entities reserved by ``Commands::spawn`` will still be spawned without
+    /// any components the next time the world flushes reserved entities
+    pub fn clear(&mut self) {
+        for component in self.components.drain(..) {
+            let ptr = unsafe { self.data.as_mut_ptr().add(component.offset) };
+            unsafe { component.meta.drop_unaligned(ptr) };
+        }
+        self.commands.clear();
+        self.data.clear();
+    }
+
+    /// Reserves an id for the entity immediately, the entity is spawned when the commands are applied
+    #[must_use]
+    pub fn spawn(&mut self, world: &World) -> CommandsEntityBuilder<'_> {
+        let entity = world.reserve_entity();
+        self.commands.push(Command::Spawn(entity));
+        CommandsEntityBuilder {
+            commands: self,
+            entity,
+        }
+    }
+
+    pub fn despawn(&mut self, entity: EcsId) {
+        self.commands.push(Command::Despawn(entity));
+    }
+
+    /// If the entity already has the component when the commands are applied the component is replaced
+    pub fn add_component<T: Component>(&mut self, entity: EcsId, component: T) {
+        let mut component = ManuallyDrop::new(component);
+        let id = ComponentId::Type(|world| world.get_or_create_type_id_ecsid::<T>());
+        unsafe {
+            self.push_component(
+                entity,
+                id,
+                &mut component as *mut _ as *mut MaybeUninit<u8>,
+                ComponentMeta::from_generic::<T>(),
+            )
+        }
+    }
+
+    pub fn remove_component<T: Component>(&mut self, entity: EcsId) {
+        let id = ComponentId::Type(|world| world.get_or_create_type_id_ecsid::<T>());
+        self.commands.push(Command::Remove(entity, id));
+    }
+
+    /// Adds an entity as a dataless component
+    ///
+    /// The command is skipped when applied if a component with the ID of component_id expects data.
+    pub fn add_component_dynamic(&mut self, entity: EcsId, component_id: EcsId) {
+        let mut component = ();
+        unsafe {
+            self.push_component(
+                entity,
+                ComponentId::Id(component_id),
+                &mut component as *mut _ as *mut MaybeUninit<u8>,
+                ComponentMeta::unit(),
+            )
+        }
+    }
+
+    pub fn remove_component_dynamic(&mut self, entity: EcsId, component_id: EcsId) {
+        self.commands
+            .push(Command::Remove(entity, ComponentId::Id(component_id)));
+    }
+
+    /// # Safety
+    ///
+    ///    ``component`` must point to a valid instance of the type described by ``meta`` that must not be used again
+    unsafe fn push_component(
+        &mut self,
+        entity: EcsId,
+        id: ComponentId,
+        component: *mut MaybeUninit<u8>,
+        meta: ComponentMeta,
+    ) {
+        let offset = self.data.len();
+        let size = meta.layout.size();
+        self.data.reserve(size);
+        unsafe {
+            std::ptr::copy_nonoverlapping(component, self.data.as_mut_ptr().add(offset), size);
+            self.data.set_len(offset + size);
+        }
+
+        self.components.push(CommandComponent { id, offset, meta });
+        self.commands
+            .push(Command::Add(entity, self.components.len() - 1));
+    }
+}
+
+/// Created by ``Commands::spawn``, the entity's id is reserved as soon as this is created
+pub struct CommandsEntityBuilder<'a> {
+    commands: &'a mut Commands,
+    entity: EcsId,
+}
+
+impl<'a> CommandsEntityBuilder<'a> {
+    #[must_use]
+    pub fn with<C: Component>(self, component: C) -> Self {
+        self.commands.add_component(self.entity, component);
+        self
+    }
+
+    /// Adds an entity as a dataless component
+    #[must_use]
+    pub fn with_dynamic(self, component_id: EcsId) -> Self {
+        self.commands
+            .add_component_dynamic(self.entity, component_id);
+        self
+    }
+
+    pub fn build(&mut self) -> EcsId {
+        self.entity
+    }
+}
+
+/// The components that an entity will have after a run of commands for it, ``Some`` is the index of a new component in
+/// ``Commands::components`` that is added or replaces the entity's current component
+struct PendingEntity {
+    entity: EcsId,
+    components: Vec<(EcsId, Option<usize>)>,
+}
+
+impl World {
+    /// Applies every command in ``commands`` in the order that they were recorded, leaving ``commands`` empty
+    pub fn apply_commands(&mut self, commands: &mut Commands) {
+        // Taken out so that if anything panics the components get leaked instead of being dropped again by ``Commands``
+        let recorded = std::mem::take(&mut commands.commands);
+        let components = std::mem::take(&mut commands.components);
+        let data = commands.data.as_mut_ptr();
+
+        // Reserved entities that aren't spawned by these commands are spawned without components. Entities spawned by
+        // these commands are alive without being in an archetype until their ``Command::Spawn`` is applied
+        let spawned = recorded
+            .iter()
+            .filter_map(|command| match *command {
+                Command::Spawn(entity) => Some(entity),
+                _ => None,
+            })
+            .collect::<HashSet<_>>();
+        for entity in self.entities.flush_reserved() {
+            if !spawned.contains(&entity) {
+                crate::entity_builder::EntityBuilder::new(self, entity, ComponentMeta::unit())
+                    .build();
+            }
+        }
+
+        let drop_new = |idx: usize| {
+            let component: &CommandComponent = &components[idx];
+            unsafe { component.meta.drop_unaligned(data.add(component.offset)) };
+        };
+
+        let mut pending: Option<PendingEntity> = None;
+        for command in recorded.iter() {
+            if let Some(current) = &pending {
+                if current.entity != command.entity() || matches!(command, Command::Despawn(_)) {
+                    self.apply_pending(pending.take().unwrap(), &components, data);
+                }
+            }
+
+            let entity = command.entity();
+            if pending.is_none() {
+                if !self.is_alive(entity) {
+                    if let Command::Add(_, idx) = *command {
+                        drop_new(idx);
+                    }
+                    continue;
+                }
+
+                // Entities spawned by these commands aren't in an archetype yet
+                let components = match self.get_entity_meta(entity) {
+                    Some(meta) => self.archetypes[meta.instance_meta.archetype.0]
+                        .comp_ids
+                        .iter()
+                        .map(|&id| (id, None))
+                        .collect(),
+                    None => Vec::new(),
+                };
+                pending = Some(PendingEntity { entity, components });
+            }
+
+            match *command {
+                Command::Spawn(_) => (),
+                Command::Despawn(entity) => {
+                    self.apply_pending(pending.take().unwrap(), &components, data);
+                    self.despawn(entity);
+                }
+                Command::Add(_, idx) => {
+                    let comp_id = components[idx].id.resolve(self);
+                    // Panicking here would leave entities spawned by these commands without an archetype so mismatched
+                    // components are skipped the same as dead ones
+                    if !self.is_component_alive(comp_id)
+                        || self.get_component_meta(comp_id).unwrap().layout
+                            != components[idx].meta.layout
+                    {
+                        drop_new(idx);
+                        continue;
+                    }
+
+                    let pending = &mut pending.as_mut().unwrap().components;
+                    match pending.iter_mut().find(|(id, _)| *id == comp_id) {
+                        Some((_, new)) => {
+                            if let Some(replaced) = new.replace(idx) {
+                                drop_new(replaced);
+                            }
+                        }
+                        None => pending.push((comp_id, Some(idx))),
+                    }
+                }
+                Command::Remove(_, id) => {
+                    let comp_id = id.resolve(self);
+                    let pending = &mut pending.as_mut().unwrap().components;
+                    if let Some(n) = pending.iter().position(|(id, _)| *id == comp_id) {
+                        if let (_, Some(removed)) = pending.remove(n) {
+                            drop_new(removed);
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Some(pending) = pending {
+            self.apply_pending(pending, &components, data);
+        }
+
+        commands.data.clear();
+        self.check_change_ticks();
+        self.auto_compact_archetypes();
+    }
+
+    /// Moves the entity into the archetype for its pending components with a single move
+    fn apply_pending(
+        &mut self,
+        pending: PendingEntity,
+        components: &[CommandComponent],
+        data: *mut MaybeUninit<u8>,
+    ) {
+        let mut new_components = pending
+            .components
+            .iter()
+            .map(|&(id, new)| {
+                (
+                    id,
+                    new.map(|idx| unsafe { data.add(components[idx].offset) }),
+                )
+            })
+            .collect::<Vec<_>>();
+        // Safe because the ids were checked to be alive when they were added and their data is never used again
+        unsafe { self.move_entity_dynamic(pending.entity, &mut new_components) };
+    }
+}
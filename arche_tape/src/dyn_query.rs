@@ -1,53 +1,137 @@
+use crate::bitset_iterator::Bitvec;
+use crate::change_detection::{newest_tick, ComponentTicks, QueryTicks};
+use crate::static_query::try_lock;
 use crate::utils::EitherGuard;
-use crate::{world::Archetype, EcsId, World};
+use crate::{world::Archetype, BorrowError, EcsError, EcsId, World};
+use std::any::TypeId;
 use std::marker::PhantomData;
 
+/// Returns the pointer to the first element, the offset between elements and a pointer to the first element's change ticks.
+/// Returns ``None`` if nothing in the archetype can match
+type CreatePtrFn = fn(&World, &Archetype, &FetchType, QueryTicks) -> Option<CreatedPtr>;
+type CreatedPtr = (*mut u8, usize, *mut ComponentTicks);
+
+fn create_immut_ptr(
+    _: &World,
+    archetype: &Archetype,
+    fetch: &FetchType,
+    _: QueryTicks,
+) -> Option<CreatedPtr> {
+    let storage_idx = archetype.storage_index(fetch.get_id().unwrap()).unwrap();
+    let storage = unsafe { &*archetype.component_storages[storage_idx].1.get() };
+    let size = storage.get_type_info().layout.size();
+    Some((unsafe { storage.as_immut_ptr() as *mut u8 }, size, 0x0 as _))
+}
+
+fn create_mut_ptr(
+    _: &World,
+    archetype: &Archetype,
+    fetch: &FetchType,
+    query_ticks: QueryTicks,
+) -> Option<CreatedPtr> {
+    let storage_idx = archetype.storage_index(fetch.get_id().unwrap()).unwrap();
+    let (_, storage, ticks) = &archetype.component_storages[storage_idx];
+    let storage = unsafe { &mut *storage.get() };
+    let ticks = unsafe { &mut *ticks.get() };
+    // Conservatively assume that something in the column gets changed
+    ticks.last_changed = newest_tick(ticks.last_changed, query_ticks.change_tick);
+    let size = storage.get_type_info().layout.size();
+    Some((
+        unsafe { storage.as_mut_ptr() },
+        size,
+        ticks.ticks.as_mut_ptr(),
+    ))
+}
+
+/// What a fetch does with the change ticks of the rows it yields
+#[derive(Copy, Clone)]
+enum TickAccess {
+    None,
+    MarkChanged,
+    FilterAdded,
+    FilterChanged,
+}
+
 struct IntraArchetypeIter<'a, const N: usize> {
     remaining: usize,
 
     ptrs: [*mut u8; N],
     offsets: [usize; N],
+    ticks: [*mut ComponentTicks; N],
+    access: [TickAccess; N],
+    query_ticks: QueryTicks,
 
     phantom: PhantomData<&'a mut Archetype>,
 }
 
 impl<'a, const N: usize> IntraArchetypeIter<'a, N> {
     /// Empty iterator
-    fn unit() -> Self {
+    fn unit(access: [TickAccess; N], query_ticks: QueryTicks) -> Self {
         Self {
             remaining: 0,
             ptrs: [0x0 as _; N],
             offsets: [0; N],
+            ticks: [0x0 as _; N],
+            access,
+            query_ticks,
             phantom: PhantomData,
         }
     }
 
-    fn new(length: usize, ptrs: [*mut u8; N], offsets: [usize; N]) -> Self {
-        Self {
-            remaining: length,
-            ptrs,
-            offsets,
-            phantom: PhantomData,
+    fn reset(&mut self, length: usize, created: [CreatedPtr; N]) {
+        self.remaining = length;
+        for (n, (ptr, offset, ticks)) in created.iter().copied().enumerate() {
+            self.ptrs[n] = ptr;
+            self.offsets[n] = offset;
+            self.ticks[n] = ticks;
         }
     }
+
+    fn row_matches(&self) -> bool {
+        let query_ticks = self.query_ticks;
+        self.access
+            .iter()
+            .zip(self.ticks.iter())
+            .all(|(access, &ticks)| match access {
+                TickAccess::None | TickAccess::MarkChanged => true,
+                TickAccess::FilterAdded => query_ticks.is_newer(unsafe { (*ticks).added }),
+                TickAccess::FilterChanged => query_ticks.is_newer(unsafe { (*ticks).changed }),
+            })
+    }
 }
 
 impl<'a, const N: usize> Iterator for IntraArchetypeIter<'a, N> {
     type Item = [*mut u8; N];
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.remaining == 0 {
-            return None;
-        }
+        loop {
+            if self.remaining == 0 {
+                return None;
+            }
 
-        let ptrs = self.ptrs;
+            let ptrs = self.ptrs;
+            let matches = self.row_matches();
+            if matches {
+                for (access, &ticks) in self.access.iter().zip(self.ticks.iter()) {
+                    // Ticks are null for optional fetches where the archetype doesn't have the component
+                    if let (TickAccess::MarkChanged, false) = (access, ticks.is_null()) {
+                        unsafe { (*ticks).changed = self.query_ticks.change_tick };
+                    }
+                }
+            }
 
-        for (ptr, offset) in self.ptrs.iter_mut().zip(self.offsets.iter()) {
-            unsafe { *ptr = ptr.add(*offset) }
-        }
-        self.remaining -= 1;
+            for (ptr, offset) in self.ptrs.iter_mut().zip(self.offsets.iter()) {
+                unsafe { *ptr = ptr.add(*offset) }
+            }
+            for ticks in self.ticks.iter_mut().filter(|ticks| !ticks.is_null()) {
+                unsafe { *ticks = ticks.add(1) }
+            }
+            self.remaining -= 1;
 
-        Some(ptrs)
+            if matches {
+                return Some(ptrs);
+            }
+        }
     }
 }
 
@@ -55,9 +139,14 @@ impl<'a, const N: usize> Iterator for IntraArchetypeIter<'a, N> {
 #[derive(Copy, Clone)]
 pub struct PtrLen(*mut u8, usize);
 
+/// Yields whole columns so ``FetchType::Added`` and ``FetchType::Changed`` only skip archetypes where nothing matches,
+/// every row in a column fetched with ``FetchType::Mut`` is marked changed
 pub struct DynQueryColumnIter<'a, const N: usize> {
-    comp_ids: [Option<EcsId>; N],
-    create_ptr: [fn(&Archetype, Option<EcsId>) -> (*mut u8, usize); N],
+    world: &'a World,
+    fetches: &'a [FetchType; N],
+    create_ptr: [CreatePtrFn; N],
+    access: [TickAccess; N],
+    query_ticks: QueryTicks,
     archetype_iter: crate::world::ArchetypeIter<'a, N>,
 }
 
@@ -65,19 +154,41 @@ impl<'a, const N: usize> Iterator for DynQueryColumnIter<'a, N> {
     type Item = [PtrLen; N];
 
     fn next(&mut self) -> Option<Self::Item> {
-        let archetype = self.archetype_iter.next()?;
-        let mut ptrs = [PtrLen(0x0 as _, archetype.entities.len()); N];
-        for n in 0..N {
-            let (ptr, _) = self.create_ptr[n](archetype, self.comp_ids[n]);
-            ptrs[n].0 = ptr;
+        'archetypes: loop {
+            let archetype = self.archetype_iter.next()?;
+            let len = archetype.entities.len();
+            let mut created = [(0x0 as _, 0, 0x0 as _); N];
+            for n in 0..N {
+                created[n] = match self.create_ptr[n](
+                    self.world,
+                    archetype,
+                    &self.fetches[n],
+                    self.query_ticks,
+                ) {
+                    Some(created) => created,
+                    None => continue 'archetypes,
+                };
+            }
+
+            let mut ptrs = [PtrLen(0x0 as _, len); N];
+            for n in 0..N {
+                let (ptr, _, ticks) = created[n];
+                if let (TickAccess::MarkChanged, false) = (self.access[n], ticks.is_null()) {
+                    for idx in 0..len {
+                        unsafe { (*ticks.add(idx)).changed = self.query_ticks.change_tick };
+                    }
+                }
+                ptrs[n].0 = ptr;
+            }
+            return Some(ptrs);
         }
-        Some(ptrs)
     }
 }
 
 pub struct DynQueryIter<'a, const N: usize> {
-    comp_ids: [Option<EcsId>; N],
-    create_ptr: [fn(&Archetype, Option<EcsId>) -> (*mut u8, usize); N],
+    world: &'a World,
+    fetches: &'a [FetchType; N],
+    create_ptr: [CreatePtrFn; N],
     archetype_iter: crate::world::ArchetypeIter<'a, N>,
     intra_iter: IntraArchetypeIter<'a, N>,
 }
@@ -86,21 +197,26 @@ impl<'a, const N: usize> Iterator for DynQueryIter<'a, N> {
     type Item = [*mut u8; N];
 
     fn next(&mut self) -> Option<Self::Item> {
-        loop {
+        'archetypes: loop {
             match self.intra_iter.next() {
                 None => {
                     let archetype = self.archetype_iter.next()?;
 
-                    let mut ptrs = [0x0 as _; N];
-                    let mut offsets = [0; N];
+                    let query_ticks = self.intra_iter.query_ticks;
+                    let mut created = [(0x0 as _, 0, 0x0 as _); N];
                     for n in 0..N {
-                        let (ptr, offset) = self.create_ptr[n](archetype, self.comp_ids[n]);
-                        ptrs[n] = ptr;
-                        offsets[n] = offset;
+                        created[n] = match self.create_ptr[n](
+                            self.world,
+                            archetype,
+                            &self.fetches[n],
+                            query_ticks,
+                        ) {
+                            Some(created) => created,
+                            None => continue 'archetypes,
+                        };
                     }
 
-                    self.intra_iter =
-                        IntraArchetypeIter::new(archetype.entities.len(), ptrs, offsets);
+                    self.intra_iter.reset(archetype.entities.len(), created);
                 }
                 ptrs @ Some(_) => return ptrs,
             }
@@ -108,40 +224,180 @@ impl<'a, const N: usize> Iterator for DynQueryIter<'a, N> {
     }
 }
 
+/// ``Mut`` and ``Immut`` can be given a wildcard pair in which case the first component in each archetype matching it is fetched
 pub enum FetchType {
     EcsId,
+    /// Rows fetched mutably are marked as changed
     Mut(EcsId),
     Immut(EcsId),
+    /// Fetches the id of the first component in the archetype matching the (possibly wildcard) pair, the pointer will point to an ``EcsId``
+    MatchedPair(EcsId),
+    /// Same as ``Immut`` but only matches rows where the component was added since the query's last run
+    Added(EcsId),
+    /// Same as ``Immut`` but only matches rows where the component was added or changed since the query's last run
+    Changed(EcsId),
+    /// Only matches entities with the component, doesn't lock or fetch the component and the pointer will be null
+    With(EcsId),
+    /// Only matches entities without the component, the pointer will be null
+    Without(EcsId),
+    /// Matches entities that match any of the filters, the filters must be ``With``, ``Without``, ``Or`` or ``EcsId`` which matches
+    /// every entity. The pointer will be null
+    Or(Vec<FetchType>),
+    /// Matches every entity, the inner ``Mut`` or ``Immut`` is fetched for entities that have the component and the pointer is
+    /// null for entities that don't
+    Optional(Box<FetchType>),
+    /// Fetches the resource with this type id for every entity that the rest of the query matches, the query matches nothing if
+    /// the resource doesn't exist
+    Resource(TypeId),
+    /// Same as ``Resource`` but locks the resource for writing
+    ResourceMut(TypeId),
 }
 
 impl FetchType {
     pub(crate) fn get_id(&self) -> Option<EcsId> {
         Some(match self {
-            &Self::Mut(id) | &Self::Immut(id) => id,
-            Self::EcsId => return None,
+            &Self::Mut(id)
+            | &Self::Immut(id)
+            | &Self::MatchedPair(id)
+            | &Self::Added(id)
+            | &Self::Changed(id)
+            | &Self::With(id)
+            | &Self::Without(id) => id,
+            Self::Optional(inner) => return inner.get_id(),
+            Self::EcsId | Self::Or(_) | Self::Resource(_) | Self::ResourceMut(_) => return None,
         })
     }
 
-    pub(crate) fn make_create_ptr_fn(&self) -> fn(&Archetype, Option<EcsId>) -> (*mut u8, usize) {
+    /// The name used for the fetch in ``BorrowError``s
+    fn name(&self) -> &'static str {
+        match self {
+            FetchType::EcsId => "FetchType::EcsId",
+            FetchType::Mut(_) => "FetchType::Mut",
+            FetchType::Immut(_) => "FetchType::Immut",
+            FetchType::MatchedPair(_) => "FetchType::MatchedPair",
+            FetchType::Added(_) => "FetchType::Added",
+            FetchType::Changed(_) => "FetchType::Changed",
+            FetchType::With(_) => "FetchType::With",
+            FetchType::Without(_) => "FetchType::Without",
+            FetchType::Or(_) => "FetchType::Or",
+            FetchType::Optional(_) => "FetchType::Optional",
+            FetchType::Resource(_) => "FetchType::Resource",
+            FetchType::ResourceMut(_) => "FetchType::ResourceMut",
+        }
+    }
+
+    /// Whether an archetype passes a ``With``, ``Without`` or ``Or`` filter, other fetches don't filter archetypes
+    pub(crate) fn filter_archetype(&self, archetype: &Archetype) -> bool {
+        match self {
+            FetchType::With(id) => archetype.storage_index(*id).is_some(),
+            FetchType::Without(id) => archetype.storage_index(*id).is_none(),
+            FetchType::Or(filters) => filters
+                .iter()
+                .any(|filter| filter.filter_archetype(archetype)),
+            _ => true,
+        }
+    }
+
+    /// Creates the bits for every ``Or`` fetch in order, see ``World::or_archetype_bits``
+    pub(crate) fn or_archetype_bits(world: &World, fetches: &[FetchType]) -> Vec<Bitvec> {
+        fetches
+            .iter()
+            .filter_map(|fetch| match fetch {
+                FetchType::Or(filters) => Some(world.or_archetype_bits(filters)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn tick_access(&self) -> TickAccess {
+        match self {
+            FetchType::Mut(_) => TickAccess::MarkChanged,
+            FetchType::Added(_) => TickAccess::FilterAdded,
+            FetchType::Changed(_) => TickAccess::FilterChanged,
+            FetchType::Optional(inner) => inner.tick_access(),
+            FetchType::EcsId
+            | FetchType::Immut(_)
+            | FetchType::MatchedPair(_)
+            | FetchType::With(_)
+            | FetchType::Without(_)
+            | FetchType::Or(_)
+            | FetchType::Resource(_)
+            | FetchType::ResourceMut(_) => TickAccess::None,
+        }
+    }
+
+    fn make_create_ptr_fn(&self) -> CreatePtrFn {
         match self {
-            FetchType::EcsId => |archetype, _| {
-                (
+            FetchType::EcsId => |_, archetype, _, _| {
+                Some((
                     archetype.entities.as_ptr() as *mut EcsId as *mut u8,
                     core::mem::size_of::<EcsId>(),
-                )
+                    0x0 as _,
+                ))
             },
-            FetchType::Immut(_) => |archetype, id| {
-                let storage_idx = archetype.comp_lookup[&id.unwrap()];
-                let storage = unsafe { &*archetype.component_storages[storage_idx].1.get() };
+            FetchType::Immut(_) => create_immut_ptr,
+            FetchType::Mut(_) => create_mut_ptr,
+            FetchType::Optional(inner) => match **inner {
+                FetchType::Immut(_) => |world, archetype, fetch, query_ticks| match archetype
+                    .storage_index(fetch.get_id().unwrap())
+                {
+                    Some(_) => create_immut_ptr(world, archetype, fetch, query_ticks),
+                    None => Some((0x0 as _, 0, 0x0 as _)),
+                },
+                FetchType::Mut(_) => |world, archetype, fetch, query_ticks| match archetype
+                    .storage_index(fetch.get_id().unwrap())
+                {
+                    Some(_) => create_mut_ptr(world, archetype, fetch, query_ticks),
+                    None => Some((0x0 as _, 0, 0x0 as _)),
+                },
+                _ => panic!("FetchType::Optional can only contain Mut or Immut"),
+            },
+            // Offset of zero as every entity in the archetype has the same pair
+            FetchType::MatchedPair(_) => |_, archetype, fetch, _| {
+                let storage_idx = archetype.storage_index(fetch.get_id().unwrap()).unwrap();
+                Some((
+                    &archetype.comp_ids[storage_idx] as *const EcsId as *mut u8,
+                    0,
+                    0x0 as _,
+                ))
+            },
+            FetchType::Added(_) => |_, archetype, fetch, query_ticks| {
+                let storage_idx = archetype.storage_index(fetch.get_id().unwrap()).unwrap();
+                let (_, storage, ticks) = &archetype.component_storages[storage_idx];
+                // Only read through so that queries sharing the read lock don't alias a ``&mut``
+                let ticks = unsafe { &*ticks.get() };
+                if !query_ticks.is_newer(ticks.last_added) {
+                    return None;
+                }
+                let storage = unsafe { &*storage.get() };
                 let size = storage.get_type_info().layout.size();
-                (unsafe { storage.as_immut_ptr() as *mut u8 }, size)
+                let ptr = unsafe { storage.as_immut_ptr() as *mut u8 };
+                Some((ptr, size, ticks.ticks.as_ptr() as *mut ComponentTicks))
             },
-            FetchType::Mut(_) => |archetype, id| {
-                let storage_idx = archetype.comp_lookup[&id.unwrap()];
-                let storage = unsafe { &mut *archetype.component_storages[storage_idx].1.get() };
+            FetchType::Changed(_) => |_, archetype, fetch, query_ticks| {
+                let storage_idx = archetype.storage_index(fetch.get_id().unwrap()).unwrap();
+                let (_, storage, ticks) = &archetype.component_storages[storage_idx];
+                // Only read through so that queries sharing the read lock don't alias a ``&mut``
+                let ticks = unsafe { &*ticks.get() };
+                if !query_ticks.is_newer(ticks.last_changed) {
+                    return None;
+                }
+                let storage = unsafe { &*storage.get() };
                 let size = storage.get_type_info().layout.size();
-                (unsafe { storage.as_mut_ptr() }, size)
+                let ptr = unsafe { storage.as_immut_ptr() as *mut u8 };
+                Some((ptr, size, ticks.ticks.as_ptr() as *mut ComponentTicks))
+            },
+            // Offset of zero as every entity gets the same resource
+            FetchType::Resource(_) | FetchType::ResourceMut(_) => |world, _, fetch, _| {
+                let type_id = match fetch {
+                    FetchType::Resource(type_id) | FetchType::ResourceMut(type_id) => type_id,
+                    _ => unreachable!(),
+                };
+                Some((world.resources[type_id].as_ptr().unwrap(), 0, 0x0 as _))
             },
+            FetchType::With(_) | FetchType::Without(_) | FetchType::Or(_) => {
+                |_, _, _, _| Some((0x0 as _, 0, 0x0 as _))
+            }
         }
     }
 }
@@ -150,57 +406,144 @@ pub struct DynQuery<'a, const N: usize> {
     world: &'a World,
     _guards: [EitherGuard<'a>; N],
     fetches: [FetchType; N],
+    ticks: QueryTicks,
+    /// The archetypes matched by each ``FetchType::Or`` in ``fetches``
+    or_bits: Vec<Bitvec>,
 
     /// If set to true it means that some of the EcsId's used were not alive/existing
     incomplete: bool,
 }
 
 impl<'a, const N: usize> DynQuery<'a, N> {
-    pub(crate) fn new(world: &'a World, fetches: [FetchType; N]) -> Self {
-        let mut incomplete = false;
+    /// Panics if the query borrows a component mutably more than once, or if any of its borrows conflict with a query or
+    /// resource borrow that is still alive
+    pub(crate) fn new(world: &'a World, fetches: [FetchType; N], last_run: u32) -> Self {
+        Self::try_new(world, fetches, last_run).unwrap_or_else(|e| panic!("{}", e))
+    }
 
-        const NONE: EitherGuard = EitherGuard::None;
-        let mut guards = [NONE; N];
+    /// Takes every lock that the query needs without blocking
+    pub(crate) fn try_new(
+        world: &'a World,
+        fetches: [FetchType; N],
+        last_run: u32,
+    ) -> Result<Self, EcsError> {
+        let mut incomplete = false;
+        // The index into ``World::locks`` of every lock each fetch takes and whether it is written to
+        let mut locks: Vec<(usize, usize, bool)> = Vec::with_capacity(N);
+
+        for (n, fetch) in fetches.iter().enumerate() {
+            // Optional fetches still lock their component but never make the query match nothing
+            let (fetch, optional) = match fetch {
+                FetchType::Optional(inner) => (&**inner, true),
+                fetch => (fetch, false),
+            };
 
-        for (fetch, guard) in fetches.iter().zip(guards.iter_mut()) {
             let ecs_id = match fetch {
-                FetchType::EcsId => continue,
-                FetchType::Immut(id) | FetchType::Mut(id) => id,
+                FetchType::EcsId | FetchType::Without(_) | FetchType::Or(_) => continue,
+                FetchType::Resource(type_id) | FetchType::ResourceMut(type_id) => {
+                    match world.resources.get(type_id) {
+                        Some(resource) if resource.as_ptr().is_some() => {
+                            let write = matches!(fetch, FetchType::ResourceMut(_));
+                            locks.push((n, resource.lock, write));
+                        }
+                        _ => incomplete |= !optional,
+                    }
+                    continue;
+                }
+                FetchType::Optional(_) => {
+                    panic!("FetchType::Optional can only contain Mut or Immut")
+                }
+                FetchType::MatchedPair(id) | FetchType::With(id) => {
+                    if world.archetype_bitset.get_bitvec(*id).is_none() {
+                        incomplete = true;
+                    }
+                    continue;
+                }
+                FetchType::Immut(id)
+                | FetchType::Mut(id)
+                | FetchType::Added(id)
+                | FetchType::Changed(id) => id,
             };
-
-            if let Some(&idx) = world.lock_lookup.get(ecs_id) {
-                let lock = &world.locks[idx];
-                match fetch {
-                    FetchType::Mut(_) => *guard = EitherGuard::Write(lock.write().unwrap()),
-                    FetchType::Immut(_) => *guard = EitherGuard::Read(lock.read().unwrap()),
-                    _ => (),
+            let write = matches!(fetch, FetchType::Mut(_));
+
+            if ecs_id.is_wildcard() {
+                // Lock every component that the wildcard could match
+                let start = locks.len();
+                locks.extend(
+                    world
+                        .lock_lookup
+                        .iter()
+                        .filter(|(id, _)| id.matches(*ecs_id))
+                        .map(|(_, &idx)| (n, idx, write)),
+                );
+                if locks.len() == start {
+                    incomplete |= !optional;
                 }
+            } else if let Some(&idx) = world.lock_lookup.get(ecs_id) {
+                locks.push((n, idx, write));
             } else {
-                incomplete = true;
+                incomplete |= !optional;
             }
         }
 
-        Self {
+        // Taking a lock that the query already holds would always fail so check for it up front to give a better error
+        for (i, &(n, idx, write)) in locks.iter().enumerate() {
+            let aliased = locks[..i]
+                .iter()
+                .any(|&(_, other_idx, other_write)| other_idx == idx && (write || other_write));
+            if aliased {
+                return Err(BorrowError::Aliased(fetches[n].name()).into());
+            }
+        }
+
+        const NONE: EitherGuard = EitherGuard::None;
+        let mut guards = [NONE; N];
+        for (n, guard) in guards.iter_mut().enumerate() {
+            let name = fetches[n].name();
+            let mut fetch_guards = locks
+                .iter()
+                .filter(|&&(fetch_n, ..)| fetch_n == n)
+                .map(|&(_, idx, write)| {
+                    let lock = &world.locks[idx];
+                    Ok(match write {
+                        true => EitherGuard::Write(try_lock(lock.try_write(), name)?),
+                        false => EitherGuard::Read(try_lock(lock.try_read(), name)?),
+                    })
+                })
+                .collect::<Result<Vec<_>, BorrowError>>()?;
+            *guard = match fetch_guards.len() {
+                0 => continue,
+                1 => fetch_guards.pop().unwrap(),
+                _ => EitherGuard::Many(fetch_guards),
+            };
+        }
+
+        Ok(Self {
             world,
             _guards: guards,
+            or_bits: FetchType::or_archetype_bits(world, &fetches),
             fetches,
+            ticks: QueryTicks {
+                last_run,
+                change_tick: world.increment_change_tick(),
+            },
             incomplete,
-        }
+        })
     }
 
-    pub fn column_iter(&mut self) -> DynQueryColumnIter<'_, N> {
-        const NONE_ID: Option<EcsId> = None;
-        let mut ecs_ids = [NONE_ID; N];
-        for (fetch, ecs_id) in self.fetches.iter().zip(ecs_ids.iter_mut()) {
-            if let FetchType::Immut(id) | FetchType::Mut(id) = fetch {
-                *ecs_id = Some(*id);
-            }
-        }
+    /// The tick that this query marks changed components with, pass it to ``World::query_dynamic_since`` to only see
+    /// components added/changed after this query
+    pub fn change_tick(&self) -> u32 {
+        self.ticks.change_tick
+    }
 
-        const DEFAULT_FN: fn(&Archetype, Option<EcsId>) -> (*mut u8, usize) = |_, _| panic!();
+    pub fn column_iter(&mut self) -> DynQueryColumnIter<'_, N> {
+        const DEFAULT_FN: CreatePtrFn = |_, _, _, _| panic!();
         let mut create_ptr = [DEFAULT_FN; N];
-        for (fetch, func) in self.fetches.iter().zip(create_ptr.iter_mut()) {
-            *func = fetch.make_create_ptr_fn();
+        let mut access = [TickAccess::None; N];
+        for (n, fetch) in self.fetches.iter().enumerate() {
+            create_ptr[n] = fetch.make_create_ptr_fn();
+            access[n] = fetch.tick_access();
         }
 
         let archetype_iter = if self.incomplete {
@@ -216,21 +559,19 @@ impl<'a, const N: usize> DynQuery<'a, N> {
 
             self.world.query_archetypes(iters, bit_length)
         } else {
-            let identity_fn: fn(_) -> _ = |x| x;
-
             let mut bit_length = self.world.entities_bitvec.len as u32;
-            let boxed_iters = ecs_ids
+            let mut or_bits = self.or_bits.iter();
+            let boxed_iters = self
+                .fetches
                 .iter()
-                .map(|id| match id {
-                    None => (self.world.entities_bitvec.data.iter(), identity_fn),
-                    Some(id) => {
-                        let bitvec = self.world.archetype_bitset.get_bitvec(*id).unwrap();
-                        if { bitvec.len as u32 } < bit_length {
-                            bit_length = bitvec.len as u32;
-                        }
-
-                        (bitvec.data.iter(), identity_fn)
-                    }
+                .map(|fetch| {
+                    let or_bits = match fetch {
+                        FetchType::Or(_) => or_bits.next(),
+                        _ => None,
+                    };
+                    let (iter, len) = self.world.fetch_archetype_bits(fetch, or_bits).unwrap();
+                    bit_length = u32::min(bit_length, len);
+                    iter
                 })
                 .collect::<Box<[_]>>();
             use std::convert::TryInto;
@@ -241,25 +582,22 @@ impl<'a, const N: usize> DynQuery<'a, N> {
         };
 
         DynQueryColumnIter {
-            comp_ids: ecs_ids,
+            world: self.world,
+            fetches: &self.fetches,
             create_ptr,
+            access,
+            query_ticks: self.ticks,
             archetype_iter,
         }
     }
 
     pub fn iter(&mut self) -> DynQueryIter<'_, N> {
-        const NONE_ID: Option<EcsId> = None;
-        let mut ecs_ids = [NONE_ID; N];
-        for (fetch, ecs_id) in self.fetches.iter().zip(ecs_ids.iter_mut()) {
-            if let FetchType::Immut(id) | FetchType::Mut(id) = fetch {
-                *ecs_id = Some(*id);
-            }
-        }
-
-        const DEFAULT_FN: fn(&Archetype, Option<EcsId>) -> (*mut u8, usize) = |_, _| panic!();
+        const DEFAULT_FN: CreatePtrFn = |_, _, _, _| panic!();
         let mut create_ptr = [DEFAULT_FN; N];
-        for (fetch, func) in self.fetches.iter().zip(create_ptr.iter_mut()) {
-            *func = fetch.make_create_ptr_fn();
+        let mut access = [TickAccess::None; N];
+        for (n, fetch) in self.fetches.iter().enumerate() {
+            create_ptr[n] = fetch.make_create_ptr_fn();
+            access[n] = fetch.tick_access();
         }
 
         let archetype_iter = if self.incomplete {
@@ -275,21 +613,19 @@ impl<'a, const N: usize> DynQuery<'a, N> {
 
             self.world.query_archetypes(iters, bit_length)
         } else {
-            let identity_fn: fn(_) -> _ = |x| x;
-
             let mut bit_length = self.world.entities_bitvec.len as u32;
-            let boxed_iters = ecs_ids
+            let mut or_bits = self.or_bits.iter();
+            let boxed_iters = self
+                .fetches
                 .iter()
-                .map(|id| match id {
-                    None => (self.world.entities_bitvec.data.iter(), identity_fn),
-                    Some(id) => {
-                        let bitvec = self.world.archetype_bitset.get_bitvec(*id).unwrap();
-                        if { bitvec.len as u32 } < bit_length {
-                            bit_length = bitvec.len as u32;
-                        }
-
-                        (bitvec.data.iter(), identity_fn)
-                    }
+                .map(|fetch| {
+                    let or_bits = match fetch {
+                        FetchType::Or(_) => or_bits.next(),
+                        _ => None,
+                    };
+                    let (iter, len) = self.world.fetch_archetype_bits(fetch, or_bits).unwrap();
+                    bit_length = u32::min(bit_length, len);
+                    iter
                 })
                 .collect::<Box<[_]>>();
             use std::convert::TryInto;
@@ -300,10 +636,11 @@ impl<'a, const N: usize> DynQuery<'a, N> {
         };
 
         DynQueryIter {
-            comp_ids: ecs_ids,
+            world: self.world,
+            fetches: &self.fetches,
             create_ptr,
             archetype_iter,
-            intra_iter: IntraArchetypeIter::unit(),
+            intra_iter: IntraArchetypeIter::unit(access, self.ticks),
         }
     }
 }
@@ -1,3 +1,5 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
 pub struct EcsIdGen(u32);
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
@@ -32,10 +34,20 @@ pub struct EcsId(EcsIdGen, EcsIdIndex);
 
 impl std::fmt::Display for EcsId {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}, {}", self.generation(), self.index())
+        match (self.pair_relation(), self.pair_target()) {
+            (Some(relation), Some(target)) => {
+                write!(f, "Pair {:#010X}, {:#010X}", relation.0, target.0)
+            }
+            _ => write!(f, "{}, {}", self.generation(), self.index()),
+        }
     }
 }
 
+/// Set in the generation of an EcsId to mark it as a pair, the rest of the generation bits hold the index of the relation
+const PAIR_FLAG: u32 = 1 << 31;
+const WILDCARD_RELATION: u32 = !PAIR_FLAG;
+const WILDCARD_TARGET: u32 = u32::MAX;
+
 impl PartialEq for EcsId {
     fn eq(&self, other: &EcsId) -> bool {
         self.as_u64() == other.as_u64()
@@ -66,6 +78,118 @@ impl EcsId {
         Self(EcsIdGen(generation), EcsIdIndex(index))
     }
 
+    /// Used in place of the relation or target of a pair to match any entity, see ``EcsId::pair``
+    pub const WILDCARD: EcsId = EcsId(EcsIdGen(u32::MAX), EcsIdIndex(u32::MAX));
+
+    /// Creates a pair id from a relation and a target, e.g. ``(ChildOf, parent)``. Pairs can be added as components like any other
+    /// EcsId and take on the ComponentMeta of their relation.
+    ///
+    /// Either side can be ``EcsId::WILDCARD`` to create a wildcard pair that can be queried for to match
+    /// every pair with the other side, e.g. ``(ChildOf, *)``. Wildcard pairs cannot be added as components.
+    ///
+    /// Pairs only store the index of their relation and target, not the generation
+    pub fn pair(relation: EcsId, target: EcsId) -> EcsId {
+        assert!(!relation.is_pair() && !target.is_pair());
+        assert!(
+            relation != Self::WILDCARD || target != Self::WILDCARD,
+            "Pairs can only have one wildcard"
+        );
+
+        // Real indices can't be the same as the wildcard's index or the pair would be a wildcard pair
+        assert!(
+            relation == Self::WILDCARD || relation.index().0 < WILDCARD_RELATION,
+            "Entities with an index of 0x7FFFFFFF or above cannot be used as the relation of a pair"
+        );
+        assert!(
+            target == Self::WILDCARD || target.index().0 < WILDCARD_TARGET,
+            "Entities with an index of 0xFFFFFFFF cannot be used as the target of a pair"
+        );
+
+        let relation = match relation == Self::WILDCARD {
+            true => WILDCARD_RELATION,
+            false => relation.index().0,
+        };
+        let target = match target == Self::WILDCARD {
+            true => WILDCARD_TARGET,
+            false => target.index().0,
+        };
+
+        Self::new_pair(relation, target)
+    }
+
+    pub(crate) fn new_pair(relation: u32, target: u32) -> EcsId {
+        Self(EcsIdGen(relation | PAIR_FLAG), EcsIdIndex(target))
+    }
+
+    pub fn is_pair(&self) -> bool {
+        self.generation().0 & PAIR_FLAG != 0 && *self != Self::WILDCARD
+    }
+
+    pub fn is_wildcard(&self) -> bool {
+        match (self.pair_relation(), self.pair_target()) {
+            (Some(relation), Some(target)) => {
+                relation.0 == WILDCARD_RELATION || target.0 == WILDCARD_TARGET
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the index of the relation if this is a pair
+    pub fn pair_relation(&self) -> Option<EcsIdIndex> {
+        match self.is_pair() {
+            true => Some(EcsIdIndex(self.generation().0 & !PAIR_FLAG)),
+            false => None,
+        }
+    }
+
+    /// Returns the index of the target if this is a pair
+    pub fn pair_target(&self) -> Option<EcsIdIndex> {
+        match self.is_pair() {
+            true => Some(self.index()),
+            false => None,
+        }
+    }
+
+    /// Returns true if ``self`` is equal to ``id`` or ``self`` is a pair that is matched by the wildcard pair ``id``
+    pub fn matches(&self, id: EcsId) -> bool {
+        if *self == id {
+            return true;
+        }
+
+        match (
+            self.pair_relation(),
+            self.pair_target(),
+            id.pair_relation(),
+            id.pair_target(),
+        ) {
+            (Some(relation), Some(target), Some(wild_relation), Some(wild_target)) => {
+                (wild_relation.0 == WILDCARD_RELATION || wild_relation == relation)
+                    && (wild_target.0 == WILDCARD_TARGET || wild_target == target)
+                    && !self.is_wildcard()
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the wildcard pairs that match this pair, ``(relation, *)`` and ``(*, target)``
+    pub(crate) fn pair_wildcards(&self) -> Option<[EcsId; 2]> {
+        let relation = self.pair_relation()?.0;
+        let target = self.pair_target()?.0;
+        Some([
+            Self::new_pair(relation, WILDCARD_TARGET),
+            Self::new_pair(WILDCARD_RELATION, target),
+        ])
+    }
+
+    /// Returns the wildcard pairs that match every pair using this entity, ``(entity, *)`` and ``(*, entity)``
+    pub(crate) fn entity_wildcards(&self) -> [EcsId; 2] {
+        let index = self.index().0;
+        [
+            Self::new_pair(index & !PAIR_FLAG, WILDCARD_TARGET),
+            Self::new_pair(WILDCARD_RELATION, index),
+        ]
+    }
+
     pub fn as_u64(&self) -> u64 {
         let gen = self.generation().0;
         let gen = { gen as u64 } << 32;
@@ -74,6 +198,56 @@ impl EcsId {
 
         gen | idx
     }
+
+    /// The inverse of ``EcsId::as_u64``
+    pub fn from_u64(id: u64) -> EcsId {
+        Self::new(id as u32, (id >> 32) as u32)
+    }
+}
+
+/// The highest generation an index can reach, the top bit of the generation is used to mark pairs
+pub const MAX_GENERATION: u32 = !PAIR_FLAG;
+/// The number of indices that can be used by entities, index ``u32::MAX`` is reserved for wildcard pairs
+pub const MAX_INDICES: usize = u32::MAX as usize;
+
+/// What happens to an index once its generation reaches ``MAX_GENERATION``
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum GenerationPolicy {
+    /// The index is never reused, so stale ids can never become alive again
+    #[default]
+    Retire,
+    /// The generation wraps back to 0, stale ids from ``MAX_GENERATION + 1`` despawns ago will be alive again
+    Wrap,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SpawnError {
+    /// Every index has been used and none of them are free to be reused
+    OutOfIndices,
+}
+
+impl std::fmt::Display for SpawnError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SpawnError::OutOfIndices => write!(f, "ran out of entity indices"),
+        }
+    }
+}
+
+impl std::error::Error for SpawnError {}
+
+/// How close the entities are to running out of indices or wrapping a generation
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct EntityCounters {
+    pub alive: usize,
+    /// Indices that have been handed out at some point, including ones that are despawned or retired
+    pub used_indices: usize,
+    /// Indices that can still be handed out, both unused ones and despawned ones waiting to be reused
+    pub remaining_indices: usize,
+    /// Indices that will never be reused because their generation reached ``MAX_GENERATION``
+    pub retired_indices: usize,
+    /// The highest generation that any index has reached
+    pub highest_generation: u32,
 }
 
 pub struct Entities {
@@ -81,6 +255,15 @@ pub struct Entities {
     /// the u32 is the generation of the entity
     pub(crate) generations: Vec<(bool, u32)>,
     pub(crate) despawned: Vec<usize>,
+    /// The number of ids handed out by ``Entities::reserve`` that haven't been flushed yet. The first reservations take
+    /// indices from the end of ``despawned``, once those run out indices past the end of ``generations`` are used
+    pub(crate) reserved: AtomicUsize,
+    pub(crate) policy: GenerationPolicy,
+    /// The most indices that will be handed out, at most ``MAX_INDICES``
+    pub(crate) max_indices: usize,
+    /// The number of dead indices that are not in ``despawned`` because they were retired
+    pub(crate) retired: usize,
+    pub(crate) highest_generation: u32,
 }
 
 impl Entities {
@@ -88,38 +271,167 @@ impl Entities {
         Self {
             generations: Vec::with_capacity(4096),
             despawned: Vec::with_capacity(512),
+            reserved: AtomicUsize::new(0),
+            policy: GenerationPolicy::default(),
+            max_indices: MAX_INDICES,
+            retired: 0,
+            highest_generation: 0,
         }
     }
 
+    pub fn set_generation_policy(&mut self, policy: GenerationPolicy) {
+        self.policy = policy;
+    }
+
+    pub fn generation_policy(&self) -> GenerationPolicy {
+        self.policy
+    }
+
+    /// Limits how many indices will be handed out, indices that are already in use are not affected
+    pub fn set_max_indices(&mut self, max_indices: usize) {
+        assert!(
+            max_indices <= MAX_INDICES,
+            "Cannot use more than MAX_INDICES indices"
+        );
+        self.max_indices = max_indices;
+    }
+
+    pub fn counters(&self) -> EntityCounters {
+        let reserved = self.reserved.load(Ordering::Relaxed);
+        let used_indices = self.generations.len() + self.reserved_past_end(reserved);
+        let free = self.despawned.len().saturating_sub(reserved);
+        EntityCounters {
+            alive: self.generations.len() - self.despawned.len() - self.retired,
+            used_indices,
+            remaining_indices: self.max_indices.saturating_sub(used_indices) + free,
+            retired_indices: self.retired,
+            highest_generation: self.highest_generation,
+        }
+    }
+
+    /// Panics if every index has been used, see ``Entities::try_spawn``
     pub fn spawn(&mut self) -> EcsId {
+        self.try_spawn().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_spawn(&mut self) -> Result<EcsId, SpawnError> {
+        assert!(
+            *self.reserved.get_mut() == 0,
+            "Reserved entities must be flushed before spawning"
+        );
         let idx = match self.despawned.pop() {
             Some(idx) => {
                 let (alive, gen) = &mut self.generations[idx];
                 assert!(*alive == false);
-                *gen = gen.wrapping_add(1);
+                *gen = Self::next_generation(*gen);
                 *alive = true;
+                self.highest_generation = u32::max(self.highest_generation, *gen);
                 idx
             }
             None => {
+                if self.generations.len() >= self.max_indices {
+                    return Err(SpawnError::OutOfIndices);
+                }
                 self.generations.push((true, 0));
                 self.generations.len() - 1
             }
         };
 
-        if idx > u32::MAX as usize {
-            todo!("Handle running out of entity ids");
+        let &mut (_, gen) = &mut self.generations[idx];
+        Ok(EcsId::new(idx as u32, gen))
+    }
+
+    /// The generation that a despawned index gets when it's reused
+    fn next_generation(generation: u32) -> u32 {
+        // The top bit of the generation is used to mark pairs
+        generation.wrapping_add(1) & !PAIR_FLAG
+    }
+
+    /// Panics if every index has been used, see ``Entities::try_reserve``
+    pub fn reserve(&self) -> EcsId {
+        self.try_reserve().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Reserves an id without needing ``&mut self``, this is lock free so it can be called from many threads at once.
+    /// The id is not alive until ``Entities::flush_reserved`` is called
+    pub fn try_reserve(&self) -> Result<EcsId, SpawnError> {
+        let mut reserved = self.reserved.load(Ordering::Relaxed);
+        // The limit is checked before the reservation is counted so that failing doesn't use up an index
+        loop {
+            let idx = self.generations.len() + self.reserved_past_end(reserved);
+            if reserved >= self.despawned.len() && idx >= self.max_indices {
+                return Err(SpawnError::OutOfIndices);
+            }
+            match self.reserved.compare_exchange_weak(
+                reserved,
+                reserved + 1,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => break,
+                Err(current) => reserved = current,
+            }
         }
 
-        let &mut (_, gen) = &mut self.generations[idx];
-        EcsId::new(idx as u32, gen)
+        Ok(match self.despawned.len().checked_sub(reserved + 1) {
+            Some(n) => {
+                let idx = self.despawned[n];
+                let (_, gen) = self.generations[idx];
+                EcsId::new(idx as u32, Self::next_generation(gen))
+            }
+            None => {
+                let idx = self.generations.len() + self.reserved_past_end(reserved);
+                EcsId::new(idx as u32, 0)
+            }
+        })
+    }
+
+    /// The number of reserved ids that are past the end of ``generations``
+    fn reserved_past_end(&self, reserved: usize) -> usize {
+        reserved.saturating_sub(self.despawned.len())
+    }
+
+    /// Marks every reserved id as alive, returning the ids in the order they were reserved
+    pub(crate) fn flush_reserved(&mut self) -> Vec<EcsId> {
+        let reserved = std::mem::replace(self.reserved.get_mut(), 0);
+        let past_end = self.reserved_past_end(reserved);
+
+        let from_despawned = self.despawned.len() - (reserved - past_end);
+        let generations = &mut self.generations;
+        let highest_generation = &mut self.highest_generation;
+        let mut flushed = self
+            .despawned
+            .drain(from_despawned..)
+            .rev()
+            .map(|idx| {
+                let (alive, gen) = &mut generations[idx];
+                *gen = Self::next_generation(*gen);
+                *alive = true;
+                *highest_generation = u32::max(*highest_generation, *gen);
+                EcsId::new(idx as u32, *gen)
+            })
+            .collect::<Vec<_>>();
+
+        let start = self.generations.len();
+        self.generations.resize(start + past_end, (true, 0));
+        flushed.extend((start..self.generations.len()).map(|idx| EcsId::new(idx as u32, 0)));
+        flushed
     }
 
     /// Returns true if entity was despawned
     pub fn despawn(&mut self, to_despawn: EcsId) -> bool {
+        // Reserved ids are taken from the end of ``despawned`` so pushing to it would change which ids they refer to
+        assert!(
+            *self.reserved.get_mut() == 0,
+            "Reserved entities must be flushed before despawning"
+        );
         if self.is_alive(to_despawn) {
-            let (alive, _) = &mut self.generations[to_despawn.uindex()];
+            let (alive, gen) = &mut self.generations[to_despawn.uindex()];
             *alive = false;
-            self.despawned.push(to_despawn.uindex());
+            match (*gen, self.policy) {
+                (MAX_GENERATION, GenerationPolicy::Retire) => self.retired += 1,
+                _ => self.despawned.push(to_despawn.uindex()),
+            }
             true
         } else {
             false
@@ -127,11 +439,61 @@ impl Entities {
     }
 
     pub fn is_alive(&self, entity: EcsId) -> bool {
-        let &(alive, stored_generation) = self
-            .generations
-            .get(entity.uindex())
-            .unwrap_or_else(|| panic!("could not get generation for {}", entity));
+        if entity.is_pair() {
+            return false;
+        }
+
+        // Reserved indices past the end of ``generations`` aren't alive until they get flushed
+        let past_end = self.reserved_past_end(self.reserved.load(Ordering::Relaxed));
+        if (self.generations.len()..self.generations.len() + past_end).contains(&entity.uindex()) {
+            return false;
+        }
+
+        // Ids from other worlds can have indices that were never spawned in this one
         let generation = entity.generation().0;
-        alive && generation == stored_generation
+        self.generations
+            .get(entity.uindex())
+            .is_some_and(|&(alive, stored_generation)| alive && generation == stored_generation)
+    }
+
+    /// Checks that ``despawned`` matches the dead entries in ``generations`` and recounts the retired indices,
+    /// used after ``generations`` and ``despawned`` have been filled in directly
+    pub(crate) fn validate(&mut self) -> Result<(), &'static str> {
+        let mut is_despawned = vec![false; self.generations.len()];
+        for &idx in self.despawned.iter() {
+            match self.generations.get(idx) {
+                Some((false, _)) if !is_despawned[idx] => is_despawned[idx] = true,
+                _ => return Err("despawned entity is alive or out of bounds"),
+            }
+        }
+
+        self.retired = 0;
+        for (idx, &(alive, gen)) in self.generations.iter().enumerate() {
+            if !alive && !is_despawned[idx] {
+                // Only indices that reached the last generation are left out of ``despawned``
+                match gen {
+                    MAX_GENERATION => self.retired += 1,
+                    _ => return Err("despawned entities do not match generations"),
+                }
+            }
+        }
+        if self.generations.len() > MAX_INDICES {
+            return Err("too many entities");
+        }
+        self.highest_generation = self
+            .generations
+            .iter()
+            .map(|&(_, gen)| gen)
+            .max()
+            .unwrap_or(0);
+        Ok(())
+    }
+
+    /// Returns the alive entity at ``index`` if there is one
+    pub fn id_at(&self, index: EcsIdIndex) -> Option<EcsId> {
+        match self.generations.get(index.0 as usize) {
+            Some(&(true, generation)) => Some(EcsId::new(index.0, generation)),
+            _ => None,
+        }
     }
 }
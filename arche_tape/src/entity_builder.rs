@@ -6,8 +6,10 @@ use std::{
 };
 
 use crate::{
+    bundle::Bundle,
+    change_detection::{ColumnTicks, ComponentTicks},
     world::{AddRemoveCache, Archetype, ComponentMeta},
-    EcsId, World,
+    Component, EcsError, EcsId, World,
 };
 use untyped_vec::{TypeInfo, UntypedVec};
 
@@ -21,12 +23,29 @@ pub struct EntityBuilder<'a> {
     component_meta: ComponentMeta,
 
     num_components: usize,
+    /// Set once ``build`` starts moving components out of ``data``
+    built: bool,
 
     world: &'a mut World,
 }
 
 impl<'a> Drop for EntityBuilder<'a> {
     fn drop(&mut self) {
+        // The builder was abandoned so its components are dropped and its id is freed for reuse
+        if !self.built {
+            let mut data_ptr = self.data.as_ptr() as *mut MaybeUninit<u8>;
+            for &comp_id in &self.comp_ids {
+                let component_meta = self.world.get_component_meta(comp_id).unwrap();
+                unsafe {
+                    // Safe because components are only moved out of ``data`` by ``build``
+                    component_meta.drop_unaligned(data_ptr);
+                    data_ptr = data_ptr.add(component_meta.layout.size());
+                }
+            }
+            self.world.entities.despawn(self.entity);
+            self.built = true;
+        }
+
         // If it never allocated, don't drop
         if self.cap != 0 {
             if let None = self.world.entity_builder_reuse {
@@ -75,6 +94,7 @@ impl<'a> EntityBuilder<'a> {
             entity,
             world,
             num_components: 0,
+            built: false,
         }
     }
 
@@ -94,6 +114,7 @@ impl<'a> EntityBuilder<'a> {
             component_meta,
 
             num_components: 0,
+            built: false,
 
             world,
         }
@@ -129,6 +150,7 @@ impl<'a> EntityBuilder<'a> {
             component_meta,
 
             num_components: 0,
+            built: false,
 
             world,
         }
@@ -167,9 +189,8 @@ impl<'a> EntityBuilder<'a> {
     pub fn with_dynamic(mut self, component_id: EcsId) -> Self {
         assert!(
             self.world
-                .get_entity_meta(component_id)
+                .get_component_meta(component_id)
                 .unwrap()
-                .component_meta
                 .layout
                 .size()
                 == 0
@@ -181,6 +202,25 @@ impl<'a> EntityBuilder<'a> {
         self
     }
 
+    /// Same as ``EntityBuilder::with_dynamic`` except returns an error instead of panicking if ``component_id`` is dead,
+    /// expects data or was already added. The builder is dropped on error
+    pub fn try_with_dynamic(self, component_id: EcsId) -> Result<Self, EcsError> {
+        let component_meta = self
+            .world
+            .get_component_meta(component_id)
+            .ok_or(EcsError::DeadEntity(component_id))?;
+        if component_meta.layout.size() != 0 {
+            return Err(EcsError::LayoutMismatch(component_id));
+        }
+        if self.comp_ids.contains(&component_id) {
+            return Err(EcsError::DuplicateComponent {
+                entity: self.entity,
+                component: component_id,
+            });
+        }
+        Ok(self.with_dynamic(component_id))
+    }
+
     /// # Safety
     ///
     ///    data behind ``component`` must not be used again.
@@ -191,12 +231,19 @@ impl<'a> EntityBuilder<'a> {
         component: *mut u8,
         component_id: EcsId,
     ) -> Self {
+        unsafe { self.push_component(component, component_id) };
+        self
+    }
+
+    /// # Safety
+    ///
+    ///    Same as ``EntityBuilder::with_dynamic_with_data``
+    unsafe fn push_component(&mut self, component: *mut u8, component_id: EcsId) {
         self.comp_ids.push(component_id);
         let component_size = self
             .world
-            .get_entity_meta(component_id)
+            .get_component_meta(component_id)
             .expect("Dead entity may not be used as a component")
-            .component_meta
             .layout
             .size();
 
@@ -215,19 +262,58 @@ impl<'a> EntityBuilder<'a> {
         }
         self.len += component_size;
         self.num_components += 1;
+    }
 
+    /// Adds every component in ``bundle``
+    #[must_use]
+    pub fn with_bundle<B: Bundle>(mut self, bundle: B) -> Self {
+        let ids = B::component_ids(self.world);
+        bundle.take_components(|ptrs| {
+            for (&id, &ptr) in ids.iter().zip(ptrs) {
+                // Safe because ``take_components`` gives us ownership of the data and it matches the ids
+                unsafe { self.push_component(ptr, id) };
+            }
+        });
         self
     }
 
     #[must_use]
-    pub fn with<C: 'static>(self, component: C) -> Self {
+    pub fn with<C: Component>(self, component: C) -> Self {
         let mut component = ManuallyDrop::new(component);
         let component_id = self.world.get_or_create_type_id_ecsid::<C>();
         unsafe { self.with_dynamic_with_data(&mut component as *mut _ as *mut _, component_id) }
     }
 
+    /// Drops every component added to the builder and frees the entity's id, the same as dropping the builder
+    pub fn cancel(self) {}
+
+    /// Same as ``EntityBuilder::build`` except returns an error instead of panicking if a component was added more than
+    /// once, nothing is built and dropping the builder drops its components
+    pub fn try_build(&mut self) -> Result<EcsId, EcsError> {
+        if let Some(component) = self.duplicate_component() {
+            return Err(EcsError::DuplicateComponent {
+                entity: self.entity,
+                component,
+            });
+        }
+        Ok(self.build())
+    }
+
     pub fn build(&mut self) -> EcsId {
-        use crate::world::{ArchIndex, EntityMeta, InstanceMeta};
+        use crate::world::{EntityMeta, InstanceMeta};
+        assert!(!self.built, "EntityBuilder::build was called twice");
+        // Checked before ``built`` is set so that panicking here still drops the components and frees the id
+        assert!(
+            self.duplicate_component().is_none(),
+            "Attempted to add the same component twice in EntityBuilder"
+        );
+        // If anything panics from here on the components are leaked instead of being dropped twice
+        self.built = true;
+
+        let change_tick = self.world.change_tick();
+        let hooks = self
+            .world
+            .component_hooks(&self.comp_ids, |meta| meta.on_add);
         if let Some(arch_index) = self.world.find_archetype_dynamic(&self.comp_ids) {
             self.world.archetypes[arch_index.0]
                 .entities
@@ -235,22 +321,16 @@ impl<'a> EntityBuilder<'a> {
 
             let mut data_ptr = self.data.as_ptr();
             for &comp_id in &self.comp_ids {
-                let component_meta = self
-                    .world
-                    .get_entity_meta(comp_id)
-                    .unwrap()
-                    .component_meta
-                    .clone();
+                let component_meta = self.world.get_component_meta(comp_id).unwrap().clone();
 
                 let archetype = &mut self.world.archetypes[arch_index.0];
                 let comp_storage_index = archetype.comp_lookup[&comp_id];
+                let (_, storage, ticks) = &mut archetype.component_storages[comp_storage_index];
                 unsafe {
-                    archetype.component_storages[comp_storage_index]
-                        .1
-                        .get_mut()
-                        .push_raw(data_ptr.cast());
+                    storage.get_mut().push_raw(data_ptr.cast());
                     data_ptr = data_ptr.offset(component_meta.layout.size() as isize);
                 }
+                ticks.get_mut().push(ComponentTicks::new(change_tick));
 
                 assert!(
                     archetype.component_storages[comp_storage_index]
@@ -270,26 +350,8 @@ impl<'a> EntityBuilder<'a> {
             };
             self.world.set_entity_meta(self.entity, entity_meta);
         } else {
-            for id in &self.comp_ids {
-                use std::collections::hash_map::Entry;
-                let entry = self.world.lock_lookup.entry(*id);
-                if let Entry::Vacant(entry) = entry {
-                    entry.insert(self.world.locks.len());
-                    self.world.locks.push(std::sync::RwLock::new(()));
-                }
-            }
-
-            let archetype = self.create_archetype();
-
-            for id in archetype.comp_ids.iter() {
-                self.world
-                    .archetype_bitset
-                    .set_bit(*id, self.world.archetypes.len(), true);
-            }
-            self.world.entities_bitvec.push_bit(true);
-
-            self.world.archetypes.push(archetype);
-            let (archetype_idx, entity_idx) = (ArchIndex(self.world.archetypes.len() - 1), 0);
+            let archetype = self.create_archetype(change_tick);
+            let (archetype_idx, entity_idx) = (self.world.push_archetype(archetype), 0);
 
             let entity_meta = EntityMeta {
                 instance_meta: InstanceMeta {
@@ -301,16 +363,26 @@ impl<'a> EntityBuilder<'a> {
             self.world.set_entity_meta(self.entity, entity_meta);
         }
 
+        self.world.run_hooks(self.entity, hooks);
         self.entity
     }
 
+    /// Returns a component that was added to the builder more than once
+    fn duplicate_component(&self) -> Option<EcsId> {
+        self.comp_ids
+            .iter()
+            .enumerate()
+            .find(|&(n, id)| self.comp_ids[..n].contains(id))
+            .map(|(_, &id)| id)
+    }
+
     /// Creates an archetype and moves the built entity into it
-    fn create_archetype(&mut self) -> Archetype {
+    fn create_archetype(&mut self, change_tick: u32) -> Archetype {
         let mut component_storages = Vec::with_capacity(self.num_components);
 
         let mut data_ptr = self.data.as_ptr();
         for &comp_id in &self.comp_ids {
-            let component_meta = &self.world.get_entity_meta(comp_id).unwrap().component_meta;
+            let component_meta = self.world.get_component_meta(comp_id).unwrap();
             let mut untyped_vec = unsafe {
                 UntypedVec::new_from_raw(TypeInfo::new(
                     component_meta.layout,
@@ -318,30 +390,32 @@ impl<'a> EntityBuilder<'a> {
                 ))
             };
             unsafe { untyped_vec.push_raw(data_ptr.cast()) };
-            component_storages.push((comp_id, std::cell::UnsafeCell::new(untyped_vec)));
+            let mut ticks = ColumnTicks::new();
+            ticks.push(ComponentTicks::new(change_tick));
+            component_storages.push((
+                comp_id,
+                std::cell::UnsafeCell::new(untyped_vec),
+                std::cell::UnsafeCell::new(ticks),
+            ));
 
             data_ptr = unsafe { data_ptr.offset(component_meta.layout.size() as isize) };
         }
 
         self.comp_ids.sort();
-        component_storages.sort_by(|(id1, _), (id2, _)| Ord::cmp(&id1, &id2));
+        component_storages.sort_by(|(id1, ..), (id2, ..)| Ord::cmp(&id1, &id2));
 
         let mut lookup = HashMap::with_capacity_and_hasher(
             self.num_components,
             crate::utils::TypeIdHasherBuilder(),
         );
         for (n, &id) in self.comp_ids.iter().enumerate() {
-            // Dont add the same component twice
-            assert!(
-                lookup.insert(id, n).is_none(),
-                "Attempted to add the same component twice in EntityBuilder"
-            );
+            lookup.insert(id, n);
         }
 
         assert!(
             self.comp_ids
                 .iter()
-                .zip(component_storages.iter().map(|(id, _)| id))
+                .zip(component_storages.iter().map(|(id, ..)| id))
                 .all(|(id1, id2)| id1 == id2)
         );
 
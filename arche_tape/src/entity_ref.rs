@@ -0,0 +1,167 @@
+use crate::world::InstanceMeta;
+use crate::{Component, EcsId, World};
+use std::any::TypeId;
+
+/// A read only view of an entity's components, created by ``World::entity``. The world is borrowed mutably while this
+/// exists so no query can be borrowing the entity's components at the same time
+#[derive(Clone)]
+pub struct EntityRef<'a> {
+    world: &'a World,
+    entity: EcsId,
+    meta: InstanceMeta,
+}
+
+impl<'a> EntityRef<'a> {
+    pub fn id(&self) -> EcsId {
+        self.entity
+    }
+
+    /// The ids of every component the entity has, sorted
+    pub fn component_ids(&self) -> &'a [EcsId] {
+        &self.world.archetypes[self.meta.archetype.0].comp_ids
+    }
+
+    pub fn has<T: Component>(&self) -> bool {
+        has(self.world, &self.meta, TypeId::of::<T>())
+    }
+
+    /// Returns true if the entity has the component, ``component_id`` can be a wildcard pair
+    pub fn has_dynamic(&self, component_id: EcsId) -> bool {
+        self.world.archetypes[self.meta.archetype.0]
+            .storage_index(component_id)
+            .is_some()
+    }
+
+    pub fn get<T: Component>(&self) -> Option<&'a T> {
+        get(self.world, &self.meta)
+    }
+}
+
+/// A view of an entity that can change its components, created by ``World::entity_mut``
+pub struct EntityMut<'a> {
+    world: &'a mut World,
+    entity: EcsId,
+    /// Updated whenever the entity moves archetype
+    meta: InstanceMeta,
+}
+
+impl<'a> EntityMut<'a> {
+    pub fn id(&self) -> EcsId {
+        self.entity
+    }
+
+    /// The ids of every component the entity has, sorted
+    pub fn component_ids(&self) -> &[EcsId] {
+        &self.world.archetypes[self.meta.archetype.0].comp_ids
+    }
+
+    pub fn has<T: Component>(&self) -> bool {
+        has(self.world, &self.meta, TypeId::of::<T>())
+    }
+
+    /// Returns true if the entity has the component, ``component_id`` can be a wildcard pair
+    pub fn has_dynamic(&self, component_id: EcsId) -> bool {
+        self.world.archetypes[self.meta.archetype.0]
+            .storage_index(component_id)
+            .is_some()
+    }
+
+    pub fn get<T: Component>(&self) -> Option<&T> {
+        get(self.world, &self.meta)
+    }
+
+    /// Marks the component as changed
+    pub fn get_mut<T: Component>(&mut self) -> Option<&mut T> {
+        get_mut(self.world, &self.meta)
+    }
+
+    /// Adds the component to the entity or replaces it if the entity already has it, see ``World::insert_component``
+    pub fn insert<T: Component>(&mut self, component: T) -> &mut Self {
+        self.world.insert_component(self.entity, component);
+        self.update_meta();
+        self
+    }
+
+    /// Removes the component from the entity, returning it if the entity had it
+    pub fn remove<T: Component>(&mut self) -> Option<T> {
+        let component = self.world.take_component::<T>(self.entity);
+        self.update_meta();
+        component
+    }
+
+    fn update_meta(&mut self) {
+        self.meta = self
+            .world
+            .get_entity_meta(self.entity)
+            .expect("Entity was despawned by a component hook")
+            .instance_meta
+            .clone();
+    }
+}
+
+fn has(world: &World, meta: &InstanceMeta, type_id: TypeId) -> bool {
+    match world.type_id_to_ecs_id.get(&type_id) {
+        Some(comp_id) => world.archetypes[meta.archetype.0]
+            .comp_lookup
+            .contains_key(comp_id),
+        None => false,
+    }
+}
+
+fn get<'a, T: Component>(world: &'a World, meta: &InstanceMeta) -> Option<&'a T> {
+    let comp_id = world.type_id_to_ecs_id.get(&TypeId::of::<T>())?;
+    let archetype = &world.archetypes[meta.archetype.0];
+    let &storage_idx = archetype.comp_lookup.get(comp_id)?;
+    let (_, storage, _) = &archetype.component_storages[storage_idx];
+    // Safe because handles are only created from ``&mut World`` so nothing can be mutating the storage
+    Some(unsafe { &(*storage.get()).as_slice::<T>()[meta.index] })
+}
+
+fn get_mut<'a, T: Component>(world: &'a mut World, meta: &InstanceMeta) -> Option<&'a mut T> {
+    let comp_id = *world.type_id_to_ecs_id.get(&TypeId::of::<T>())?;
+    let change_tick = world.change_tick();
+    let archetype = &mut world.archetypes[meta.archetype.0];
+    let &storage_idx = archetype.comp_lookup.get(&comp_id)?;
+    let (_, storage, ticks) = &mut archetype.component_storages[storage_idx];
+    ticks.get_mut().mark_changed(meta.index, change_tick);
+    // Safe because the id for ``T`` always stores ``T``
+    Some(unsafe { &mut storage.get_mut().as_slice_mut::<T>()[meta.index] })
+}
+
+impl World {
+    /// Returns ``None`` if the entity isn't alive
+    ///
+    /// Takes ``&mut self`` because the returned ``EntityRef`` reads components without taking their locks, the
+    /// exclusive borrow is what guarantees no query is mutating them. Use ``World::query`` with ``StaticQuery::get``
+    /// to read components through ``&World``
+    pub fn entity(&mut self, entity: EcsId) -> Option<EntityRef<'_>> {
+        let meta = self.get_entity_meta(entity)?.instance_meta.clone();
+        Some(EntityRef {
+            world: self,
+            entity,
+            meta,
+        })
+    }
+
+    /// Returns ``None`` if the entity isn't alive
+    pub fn entity_mut(&mut self, entity: EcsId) -> Option<EntityMut<'_>> {
+        let meta = self.get_entity_meta(entity)?.instance_meta.clone();
+        Some(EntityMut {
+            world: self,
+            entity,
+            meta,
+        })
+    }
+
+    /// Takes ``&mut self`` for the same reason as ``World::entity``, the component is read without taking its lock
+    pub fn get<T: Component>(&mut self, entity: EcsId) -> Option<&T> {
+        let meta = &self.get_entity_meta(entity)?.instance_meta;
+        get(self, meta)
+    }
+
+    /// Marks the component as changed
+    pub fn get_mut<T: Component>(&mut self, entity: EcsId) -> Option<&mut T> {
+        let meta = self.get_entity_meta(entity)?.instance_meta.clone();
+        get_mut(self, &meta)
+    }
+}
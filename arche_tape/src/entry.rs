@@ -0,0 +1,73 @@
+use crate::{Component, EcsId, World};
+use std::marker::PhantomData;
+
+/// Created by ``World::entry``, a view into a single component of an entity that may or may not exist
+pub struct Entry<'a, T: Component> {
+    world: &'a mut World,
+    entity: EcsId,
+    _p: PhantomData<fn() -> T>,
+}
+
+impl<'a, T: Component> Entry<'a, T> {
+    pub fn entity(&self) -> EcsId {
+        self.entity
+    }
+
+    /// Returns true if the entity has the component
+    pub fn is_occupied(&self) -> bool {
+        self.world.has_component::<T>(self.entity)
+    }
+
+    /// Calls ``func`` with the component if the entity has it
+    pub fn and_modify(mut self, func: impl FnOnce(&mut T)) -> Self {
+        if let Some(component) = self.get_mut() {
+            func(component);
+        }
+        self
+    }
+
+    /// Returns the component, adding ``default`` to the entity first if it doesn't have the component
+    pub fn or_insert(self, default: T) -> &'a mut T {
+        self.or_insert_with(|| default)
+    }
+
+    /// Returns the component, adding the result of ``func`` to the entity first if it doesn't have the component
+    pub fn or_insert_with(self, func: impl FnOnce() -> T) -> &'a mut T {
+        if !self.is_occupied() {
+            self.world.add_component(self.entity, func());
+        }
+        let (world, entity) = (self.world, self.entity);
+        let comp_id = world.get_or_create_type_id_ecsid::<T>();
+        let ptr = world
+            .get_component_mut_dynamic(entity, comp_id)
+            .expect("Component was removed by its on_add hook");
+        // Safe because ``comp_id`` is the id for ``T`` and the world is borrowed mutably for ``'a``
+        unsafe { &mut *(ptr as *mut T) }
+    }
+
+    fn get_mut(&mut self) -> Option<&mut T> {
+        let comp_id = self.world.get_or_create_type_id_ecsid::<T>();
+        let ptr = self.world.get_component_mut_dynamic(self.entity, comp_id)?;
+        // Safe because ``comp_id`` is the id for ``T`` and the world is borrowed mutably by ``self``
+        Some(unsafe { &mut *(ptr as *mut T) })
+    }
+}
+
+impl<'a, T: Component + Default> Entry<'a, T> {
+    /// Returns the component, adding ``T::default()`` to the entity first if it doesn't have the component
+    pub fn or_default(self) -> &'a mut T {
+        self.or_insert_with(T::default)
+    }
+}
+
+impl World {
+    /// Gets the entity's ``T`` component for in place manipulation, see ``Entry``
+    pub fn entry<T: Component>(&mut self, entity: EcsId) -> Entry<'_, T> {
+        assert!(self.is_alive(entity));
+        Entry {
+            world: self,
+            entity,
+            _p: PhantomData,
+        }
+    }
+}
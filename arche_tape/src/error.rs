@@ -0,0 +1,94 @@
+use crate::{EcsId, SpawnError};
+
+/// Returned by the ``try_*`` methods on ``World`` and ``StaticQuery`` instead of panicking
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum EcsError {
+    /// The entity, or the entity used as a component, is not alive
+    DeadEntity(EcsId),
+    MissingComponent {
+        entity: EcsId,
+        component: EcsId,
+    },
+    DuplicateComponent {
+        entity: EcsId,
+        component: EcsId,
+    },
+    /// The component expects data of a different layout than what was given
+    LayoutMismatch(EcsId),
+    /// The entity is alive but doesn't match the query
+    QueryMismatch(EcsId),
+    AlreadyBorrowed(BorrowError),
+    /// The resource with this type name doesn't exist
+    MissingResource(&'static str),
+    /// A new entity couldn't be created
+    Spawn(SpawnError),
+}
+
+impl std::fmt::Display for EcsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EcsError::DeadEntity(entity) => write!(f, "entity {} is not alive", entity),
+            EcsError::MissingComponent { entity, component } => {
+                write!(f, "entity {} does not have component {}", entity, component)
+            }
+            EcsError::DuplicateComponent { entity, component } => {
+                write!(f, "entity {} already has component {}", entity, component)
+            }
+            EcsError::LayoutMismatch(component) => {
+                write!(f, "wrong data layout for component {}", component)
+            }
+            EcsError::QueryMismatch(entity) => {
+                write!(f, "entity {} does not match the query", entity)
+            }
+            EcsError::AlreadyBorrowed(e) => write!(f, "{}", e),
+            EcsError::MissingResource(name) => write!(f, "resource {} does not exist", name),
+            EcsError::Spawn(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for EcsError {}
+
+impl From<BorrowError> for EcsError {
+    fn from(e: BorrowError) -> Self {
+        EcsError::AlreadyBorrowed(e)
+    }
+}
+
+impl From<SpawnError> for EcsError {
+    fn from(e: SpawnError) -> Self {
+        EcsError::Spawn(e)
+    }
+}
+
+/// Why a query couldn't take the locks it needs, each variant holds the name of the query parameter that conflicts
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BorrowError {
+    /// The query borrows the same component or resource mutably more than once, or both mutably and immutably
+    Aliased(&'static str),
+    /// The component or resource is already borrowed somewhere else in a way that conflicts with the query
+    AlreadyBorrowed(&'static str),
+}
+
+impl std::fmt::Display for BorrowError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BorrowError::Aliased(name) => {
+                write!(
+                    f,
+                    "query borrows {} while also borrowing it elsewhere in the query",
+                    name
+                )
+            }
+            BorrowError::AlreadyBorrowed(name) => {
+                write!(
+                    f,
+                    "{} is already borrowed by a conflicting query or resource",
+                    name
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for BorrowError {}
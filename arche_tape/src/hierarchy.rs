@@ -0,0 +1,131 @@
+use crate::{bitset_iterator::BitsetIterator, Component, EcsId, World};
+use std::any::TypeId;
+use std::collections::VecDeque;
+
+/// The relation used for parent/child links, a child has the pair ``(ChildOf, parent)``.
+///
+/// Because it's a normal relation it can be queried for like any other pair, e.g. ``Relation<ChildOf>`` in a StaticQuery
+#[derive(Copy, Clone, Component)]
+#[component(name = "ChildOf", raw)]
+pub struct ChildOf;
+
+pub struct DepthFirstIter<'a> {
+    world: &'a World,
+    stack: Vec<EcsId>,
+}
+
+impl<'a> Iterator for DepthFirstIter<'a> {
+    type Item = EcsId;
+
+    fn next(&mut self) -> Option<EcsId> {
+        let entity = self.stack.pop()?;
+        // Reversed so that children get visited in the same order children() returns them
+        let start = self.stack.len();
+        self.stack.extend(self.world.children(entity));
+        self.stack[start..].reverse();
+        Some(entity)
+    }
+}
+
+pub struct BreadthFirstIter<'a> {
+    world: &'a World,
+    queue: VecDeque<EcsId>,
+}
+
+impl<'a> Iterator for BreadthFirstIter<'a> {
+    type Item = EcsId;
+
+    fn next(&mut self) -> Option<EcsId> {
+        let entity = self.queue.pop_front()?;
+        self.queue.extend(self.world.children(entity));
+        Some(entity)
+    }
+}
+
+impl World {
+    fn child_of_pair(&self, parent: EcsId) -> Option<EcsId> {
+        let &child_of = self.type_id_to_ecs_id.get(&TypeId::of::<ChildOf>())?;
+        Some(EcsId::pair(child_of, parent))
+    }
+
+    /// Makes ``child`` a child of ``parent``, replacing its old parent if it had one
+    ///
+    /// This method will panic if ``parent`` is ``child`` or a descendant of ``child``
+    pub fn set_parent(&mut self, child: EcsId, parent: EcsId) {
+        assert!(self.is_alive(child));
+        assert!(self.is_alive(parent));
+        assert!(
+            child != parent
+                && self
+                    .iter_descendants_depth_first(child)
+                    .all(|e| e != parent),
+            "Attempted to parent an entity to one of its descendants"
+        );
+
+        self.remove_parent(child);
+        self.add_relation(child, parent, ChildOf);
+    }
+
+    /// Returns true if the child had a parent to remove
+    pub fn remove_parent(&mut self, child: EcsId) -> bool {
+        match self.parent(child) {
+            Some(parent) => {
+                self.remove_relation::<ChildOf>(child, parent);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn parent(&self, child: EcsId) -> Option<EcsId> {
+        let archetype = &self.archetypes[self.get_entity_meta(child)?.instance_meta.archetype.0];
+        let wildcard = self.child_of_pair(EcsId::WILDCARD)?;
+        let storage_idx = archetype.storage_index(wildcard)?;
+        let (_, parent) = self.pair_parts(archetype.comp_ids[storage_idx])?;
+        Some(parent)
+    }
+
+    /// Iterates the direct children of ``parent``
+    pub fn children(&self, parent: EcsId) -> impl Iterator<Item = EcsId> + '_ {
+        let bitvec = match self.is_alive(parent) {
+            true => self
+                .child_of_pair(parent)
+                .and_then(|pair| self.archetype_bitset.get_bitvec(pair)),
+            false => None,
+        };
+
+        bitvec
+            .into_iter()
+            .flat_map(|bitvec| {
+                let identity: fn(_) -> _ = |x: usize| x;
+                BitsetIterator::new([(bitvec.data.iter(), identity)], bitvec.len as u32)
+            })
+            .flat_map(move |idx| self.archetypes[idx].entities.iter().copied())
+    }
+
+    /// Iterates every descendant of ``root`` depth first, ``root`` is not included
+    pub fn iter_descendants_depth_first(&self, root: EcsId) -> DepthFirstIter<'_> {
+        let mut stack = self.children(root).collect::<Vec<_>>();
+        stack.reverse();
+        DepthFirstIter { world: self, stack }
+    }
+
+    /// Iterates every descendant of ``root`` breadth first, ``root`` is not included
+    pub fn iter_descendants_breadth_first(&self, root: EcsId) -> BreadthFirstIter<'_> {
+        BreadthFirstIter {
+            world: self,
+            queue: self.children(root).collect(),
+        }
+    }
+
+    /// Despawns every descendant of ``parent``, called by ``World::despawn``
+    pub(crate) fn despawn_children(&mut self, parent: EcsId) {
+        let descendants = self
+            .iter_descendants_depth_first(parent)
+            .collect::<Vec<_>>();
+        // Despawn the deepest entities first so that each despawn has no children left to look for
+        for &entity in descendants.iter().rev() {
+            self.despawn(entity);
+        }
+    }
+}
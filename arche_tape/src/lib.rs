@@ -2,6 +2,9 @@
 #![feature(unsafe_block_in_unsafe_fn, int_bits_const)]
 #![deny(unsafe_op_in_unsafe_fn)]
 
+// Lets the derive macros refer to ``::arche_tape`` from inside this crate
+extern crate self as arche_tape;
+
 #[macro_export]
 macro_rules! spawn {
     (&mut $world:ident, $($c:expr),* $(,)?) => {
@@ -16,28 +19,87 @@ macro_rules! spawn {
 
 mod bitset_iterator;
 
+pub mod bundle;
+pub mod change_detection;
+pub mod commands;
 pub mod entities;
 pub mod entity_builder;
+pub mod entity_ref;
+pub mod entry;
+pub mod error;
+pub mod hierarchy;
+pub mod registry;
+pub mod resource;
+pub mod schedule;
+pub mod snapshot;
+pub mod text;
 pub mod world;
 
 pub(crate) mod array_vec;
 pub(crate) mod dyn_query;
 pub(crate) mod static_query;
+pub(crate) mod thread_pool;
 
+pub use arche_tape_derive::{Bundle, Component};
+pub use bundle::Bundle;
+pub use change_detection::ComponentTicks;
+pub use commands::Commands;
 pub use dyn_query::DynQuery;
 pub use dyn_query::FetchType;
 pub use entities::EcsId;
+pub use entities::EntityCounters;
+pub use entities::GenerationPolicy;
+pub use entities::SpawnError;
+pub use entity_ref::EntityMut;
+pub use entity_ref::EntityRef;
+pub use entry::Entry;
+pub use error::BorrowError;
+pub use error::EcsError;
+pub use hierarchy::ChildOf;
+pub use registry::ComponentRegistry;
+pub use registry::SnapshotComponent;
+pub use resource::ResourceMut;
+pub use resource::ResourceRef;
+pub use schedule::IntoSystem;
+pub use schedule::Schedule;
+pub use schedule::System;
+pub use snapshot::SnapshotError;
+pub use static_query::Added;
+pub use static_query::Changed;
 pub use static_query::EcsIds;
+pub use static_query::Or;
+pub use static_query::Relation;
+pub use static_query::Res;
 pub use static_query::StaticQuery;
+pub use static_query::With;
+pub use static_query::Without;
+pub use text::TextComponent;
+pub use text::TextError;
+pub use text::TextValue;
 pub use world::World;
 
 #[cfg(test)]
 mod tests {
     mod bitset_iterator;
     mod bitsetsss;
+    mod bundle;
+    mod change_detection;
+    mod commands;
+    mod derive;
     mod dyn_query;
     mod entities;
+    mod entity_ref;
+    mod entry;
+    mod error;
+    mod filters;
+    mod hierarchy;
+    mod optional;
+    mod pairs;
     mod query;
+    mod resource;
+    mod schedule;
+    mod snapshot;
+    mod text;
     mod world;
 }
 
@@ -49,6 +111,8 @@ pub(crate) mod utils {
     pub enum EitherGuard<'a> {
         Read(RwLockReadGuard<'a, ()>),
         Write(RwLockWriteGuard<'a, ()>),
+        /// Used when a fetch for a wildcard pair has to lock every component it could match
+        Many(Vec<EitherGuard<'a>>),
         None,
     }
 
@@ -95,5 +159,40 @@ pub(crate) mod utils {
     }
 }
 
-pub trait Component: 'static {}
-impl<T: 'static> Component for T {}
+/// Runs when a component is added to or removed from an entity
+pub type ComponentHook = fn(&mut World, EcsId);
+
+/// Data that can be added to entities, usually implemented with ``#[derive(Component)]``
+///
+/// Types from other crates can't implement ``Component`` outside of the crate that defines them, wrap them in a newtype instead
+pub trait Component: 'static {
+    /// Runs after the component is added to an entity, including when it replaces a component the entity already had
+    const ON_ADD: Option<ComponentHook> = None;
+    /// Runs after the component is removed from an entity, the entity is no longer alive if it was despawned
+    const ON_REMOVE: Option<ComponentHook> = None;
+
+    /// The name that ``ComponentRegistry::register_component`` registers the component with
+    fn name() -> &'static str {
+        std::any::type_name::<Self>()
+    }
+
+    /// Registers the functions used to save the component, see ``ComponentRegistry::register_component``
+    fn register(_registry: &mut ComponentRegistry) {}
+}
+
+macro_rules! impl_component {
+    ($($T:ty)*) => {
+        $(
+            impl Component for $T {}
+        )*
+    };
+}
+
+// ``()`` goes first as ``char ()`` would parse as ``Fn`` sugar
+impl_component!(() u8 u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize f32 f64 bool char String &'static str EcsId);
+
+impl<T: 'static> Component for Option<T> {}
+impl<T: 'static> Component for Vec<T> {}
+impl<T: 'static + ?Sized> Component for Box<T> {}
+impl<T: 'static + ?Sized> Component for std::sync::Arc<T> {}
+impl<T: 'static, const N: usize> Component for [T; N] {}
@@ -0,0 +1,271 @@
+use crate::text::{TextComponent, TextFns};
+use crate::{snapshot::SnapshotError, world::ComponentMeta, Component, EcsId};
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::mem::ManuallyDrop;
+use untyped_vec::UntypedVec;
+
+/// A component that can be written to and read from a binary snapshot
+pub trait SnapshotComponent: Component + Sized {
+    fn serialize(&self, out: &mut Vec<u8>);
+    fn deserialize(input: &mut &[u8]) -> Result<Self, SnapshotError>;
+}
+
+pub(crate) fn take_bytes<'a>(input: &mut &'a [u8], len: usize) -> Result<&'a [u8], SnapshotError> {
+    if input.len() < len {
+        return Err(SnapshotError::UnexpectedEof);
+    }
+
+    let (bytes, rest) = input.split_at(len);
+    *input = rest;
+    Ok(bytes)
+}
+
+macro_rules! impl_snapshot_component_num {
+    ($($T:ty)*) => {
+        $(
+            impl SnapshotComponent for $T {
+                fn serialize(&self, out: &mut Vec<u8>) {
+                    out.extend_from_slice(&self.to_le_bytes());
+                }
+
+                fn deserialize(input: &mut &[u8]) -> Result<Self, SnapshotError> {
+                    use std::convert::TryInto;
+                    let bytes = take_bytes(input, core::mem::size_of::<$T>())?;
+                    Ok(<$T>::from_le_bytes(bytes.try_into().unwrap()))
+                }
+            }
+        )*
+    };
+}
+
+impl_snapshot_component_num!(u8 u16 u32 u64 u128 i8 i16 i32 i64 i128 f32 f64);
+
+impl SnapshotComponent for usize {
+    fn serialize(&self, out: &mut Vec<u8>) {
+        (*self as u64).serialize(out);
+    }
+
+    fn deserialize(input: &mut &[u8]) -> Result<Self, SnapshotError> {
+        Ok(u64::deserialize(input)? as usize)
+    }
+}
+
+impl SnapshotComponent for bool {
+    fn serialize(&self, out: &mut Vec<u8>) {
+        (*self as u8).serialize(out);
+    }
+
+    fn deserialize(input: &mut &[u8]) -> Result<Self, SnapshotError> {
+        match u8::deserialize(input)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(SnapshotError::InvalidData("bool was not 0 or 1")),
+        }
+    }
+}
+
+impl SnapshotComponent for String {
+    fn serialize(&self, out: &mut Vec<u8>) {
+        self.len().serialize(out);
+        out.extend_from_slice(self.as_bytes());
+    }
+
+    fn deserialize(input: &mut &[u8]) -> Result<Self, SnapshotError> {
+        let len = usize::deserialize(input)?;
+        let bytes = take_bytes(input, len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| SnapshotError::InvalidData("invalid utf8"))
+    }
+}
+
+impl SnapshotComponent for EcsId {
+    fn serialize(&self, out: &mut Vec<u8>) {
+        self.as_u64().serialize(out);
+    }
+
+    fn deserialize(input: &mut &[u8]) -> Result<Self, SnapshotError> {
+        Ok(EcsId::from_u64(u64::deserialize(input)?))
+    }
+}
+
+impl<T: SnapshotComponent> SnapshotComponent for Option<T> {
+    fn serialize(&self, out: &mut Vec<u8>) {
+        self.is_some().serialize(out);
+        if let Some(data) = self {
+            data.serialize(out);
+        }
+    }
+
+    fn deserialize(input: &mut &[u8]) -> Result<Self, SnapshotError> {
+        Ok(match bool::deserialize(input)? {
+            true => Some(T::deserialize(input)?),
+            false => None,
+        })
+    }
+}
+
+impl<T: SnapshotComponent> SnapshotComponent for Vec<T> {
+    fn serialize(&self, out: &mut Vec<u8>) {
+        self.len().serialize(out);
+        for data in self {
+            data.serialize(out);
+        }
+    }
+
+    fn deserialize(input: &mut &[u8]) -> Result<Self, SnapshotError> {
+        let len = usize::deserialize(input)?;
+        // Don't trust the length for the capacity as it could be garbage
+        let mut vec = Vec::new();
+        for _ in 0..len {
+            vec.push(T::deserialize(input)?);
+        }
+        Ok(vec)
+    }
+}
+
+fn serialize_erased<T: SnapshotComponent>(ptr: *const u8, out: &mut Vec<u8>) {
+    let data = unsafe { &*(ptr as *const T) };
+    data.serialize(out);
+}
+
+fn deserialize_erased<T: SnapshotComponent>(
+    input: &mut &[u8],
+    storage: &mut UntypedVec,
+) -> Result<(), SnapshotError> {
+    let mut data = ManuallyDrop::new(T::deserialize(input)?);
+    // Safe because the storage is always created from the ComponentMeta of T
+    unsafe { storage.push_raw(&mut data as *mut _ as *mut _) };
+    Ok(())
+}
+
+fn serialize_raw<T: Copy + Component>(ptr: *const u8, out: &mut Vec<u8>) {
+    let bytes = unsafe { std::slice::from_raw_parts(ptr, core::mem::size_of::<T>()) };
+    out.extend_from_slice(bytes);
+}
+
+fn deserialize_raw<T: Copy + Component>(
+    input: &mut &[u8],
+    storage: &mut UntypedVec,
+) -> Result<(), SnapshotError> {
+    let bytes = take_bytes(input, core::mem::size_of::<T>())?;
+    // push_raw copies bytewise so the bytes don't need to be aligned
+    unsafe { storage.push_raw(bytes.as_ptr() as *mut _) };
+    Ok(())
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct BinaryFns {
+    pub(crate) serialize: fn(*const u8, &mut Vec<u8>),
+    pub(crate) deserialize: fn(&mut &[u8], &mut UntypedVec) -> Result<(), SnapshotError>,
+}
+
+#[derive(Clone)]
+pub(crate) struct RegistryEntry {
+    pub(crate) name: String,
+    pub(crate) type_id: TypeId,
+    pub(crate) component_meta: ComponentMeta,
+    pub(crate) binary: Option<BinaryFns>,
+    pub(crate) text: Option<TextFns>,
+}
+
+/// Maps rust types to stable names and the functions used to save and load them, components that are saved
+/// have to be registered with the same name when loading.
+///
+/// Entities used as components that were created with ``ComponentMeta::from_size_align`` or ``ComponentMeta::unit``
+/// have no drop_fn and are saved as raw bytes so they don't need registering
+pub struct ComponentRegistry {
+    entries: Vec<RegistryEntry>,
+    type_lookup: HashMap<TypeId, usize, crate::utils::TypeIdHasherBuilder>,
+    name_lookup: HashMap<String, usize>,
+}
+
+impl Default for ComponentRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ComponentRegistry {
+    /// Creates a registry with ``ChildOf`` already registered
+    pub fn new() -> Self {
+        let mut registry = Self {
+            entries: Vec::new(),
+            type_lookup: HashMap::with_hasher(crate::utils::TypeIdHasherBuilder()),
+            name_lookup: HashMap::new(),
+        };
+        registry.register_component::<crate::ChildOf>();
+        registry
+    }
+
+    /// Gets the entry for ``T`` creating it if it doesn't exist, a type must always be registered with the same name
+    fn entry<T: Component>(&mut self, name: &str) -> &mut RegistryEntry {
+        if let Some(&idx) = self.type_lookup.get(&TypeId::of::<T>()) {
+            assert!(
+                self.entries[idx].name == name,
+                "Attempted to register a component with two different names"
+            );
+            return &mut self.entries[idx];
+        }
+
+        assert!(
+            !self.name_lookup.contains_key(name),
+            "Attempted to register two components with the same name"
+        );
+
+        let idx = self.entries.len();
+        self.type_lookup.insert(TypeId::of::<T>(), idx);
+        self.name_lookup.insert(name.to_owned(), idx);
+        self.entries.push(RegistryEntry {
+            name: name.to_owned(),
+            type_id: TypeId::of::<T>(),
+            component_meta: ComponentMeta::from_generic::<T>(),
+            binary: None,
+            text: None,
+        });
+        &mut self.entries[idx]
+    }
+
+    /// Registers the functions used to save ``T`` in binary snapshots
+    pub fn register<T: SnapshotComponent>(&mut self, name: &str) {
+        self.entry::<T>(name).binary = Some(BinaryFns {
+            serialize: serialize_erased::<T>,
+            deserialize: deserialize_erased::<T>,
+        });
+    }
+
+    /// Registers the functions used to save ``T`` in the text format, see ``World::save_text``
+    pub fn register_text<T: TextComponent>(&mut self, name: &str) {
+        self.entry::<T>(name).text = Some(TextFns::new::<T>());
+    }
+
+    /// Registers a plain old data component that is saved by copying its bytes, in the text format it is written as a list of bytes
+    ///
+    /// # Safety
+    ///
+    ///    Every bit pattern of ``T``'s size must be a valid ``T`` and ``T`` must not contain references or pointers, as
+    ///    loading a snapshot or text creates a ``T`` from whatever bytes it contains
+    pub unsafe fn register_raw<T: Copy + Component>(&mut self, name: &str) {
+        let entry = self.entry::<T>(name);
+        entry.binary = Some(BinaryFns {
+            serialize: serialize_raw::<T>,
+            deserialize: deserialize_raw::<T>,
+        });
+        entry.text = Some(unsafe { TextFns::new_raw::<T>() });
+    }
+
+    /// Registers ``T`` with the name and functions given by its ``Component`` impl, types using
+    /// ``#[derive(Component)]`` choose them with ``#[component(name = "...", snapshot, text, raw)]``
+    pub fn register_component<T: Component>(&mut self) {
+        T::register(self);
+    }
+
+    pub(crate) fn get_by_type(&self, type_id: TypeId) -> Option<&RegistryEntry> {
+        self.type_lookup
+            .get(&type_id)
+            .map(|&idx| &self.entries[idx])
+    }
+
+    pub(crate) fn get_by_name(&self, name: &str) -> Option<&RegistryEntry> {
+        self.name_lookup.get(name).map(|&idx| &self.entries[idx])
+    }
+}
@@ -0,0 +1,131 @@
+use crate::static_query::try_lock;
+use crate::{Component, EcsError, World};
+use std::any::{type_name, Any, TypeId};
+use std::cell::UnsafeCell;
+use std::ops::{Deref, DerefMut};
+use std::sync::{RwLockReadGuard, RwLockWriteGuard};
+
+pub(crate) struct Resource {
+    /// ``None`` if the resource was removed, the lock is kept so that it gets reused if the resource is inserted again
+    data: Option<UnsafeCell<Box<dyn Any>>>,
+    /// Index into ``World::locks``
+    pub(crate) lock: usize,
+}
+
+impl Resource {
+    /// Returns a pointer to the resource, it must only be dereferenced while the resource's lock is held
+    pub(crate) fn as_ptr(&self) -> Option<*mut u8> {
+        let data = self.data.as_ref()?;
+        // Go through raw pointers only so that no reference to the resource exists outside of the lock
+        Some(unsafe { std::ptr::addr_of_mut!(**data.get()) } as *mut u8)
+    }
+}
+
+pub struct ResourceRef<'a, T> {
+    _guard: RwLockReadGuard<'a, ()>,
+    resource: &'a T,
+}
+
+impl<'a, T> Deref for ResourceRef<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.resource
+    }
+}
+
+pub struct ResourceMut<'a, T> {
+    _guard: RwLockWriteGuard<'a, ()>,
+    resource: &'a mut T,
+}
+
+impl<'a, T> Deref for ResourceMut<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.resource
+    }
+}
+
+impl<'a, T> DerefMut for ResourceMut<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.resource
+    }
+}
+
+impl World {
+    /// Inserts a resource, returning the previous resource of the same type if there was one
+    pub fn insert_resource<T: Component>(&mut self, resource: T) -> Option<T> {
+        if !self.resources.contains_key(&TypeId::of::<T>()) {
+            let lock = self.new_lock();
+            self.resources
+                .insert(TypeId::of::<T>(), Resource { data: None, lock });
+        }
+        let entry = self.resources.get_mut(&TypeId::of::<T>()).unwrap();
+
+        let old = entry.data.replace(UnsafeCell::new(Box::new(resource)))?;
+        Some(*old.into_inner().downcast::<T>().unwrap())
+    }
+
+    /// Removes the resource, returning it if it existed
+    pub fn remove_resource<T: Component>(&mut self) -> Option<T> {
+        let data = self.resources.get_mut(&TypeId::of::<T>())?.data.take()?;
+        Some(*data.into_inner().downcast::<T>().unwrap())
+    }
+
+    pub fn has_resource<T: Component>(&self) -> bool {
+        self.resources
+            .get(&TypeId::of::<T>())
+            .is_some_and(|resource| resource.data.is_some())
+    }
+
+    /// Borrows the resource, returning ``None`` if it doesn't exist. Panics if the resource is borrowed mutably, see
+    /// ``World::try_resource``
+    pub fn resource<T: Component>(&self) -> Option<ResourceRef<'_, T>> {
+        match self.try_resource() {
+            Ok(resource) => Some(resource),
+            Err(EcsError::MissingResource(_)) => None,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Mutably borrows the resource, returning ``None`` if it doesn't exist. Panics if the resource is already
+    /// borrowed, see ``World::try_resource_mut``
+    pub fn resource_mut<T: Component>(&self) -> Option<ResourceMut<'_, T>> {
+        match self.try_resource_mut() {
+            Ok(resource) => Some(resource),
+            Err(EcsError::MissingResource(_)) => None,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Same as ``World::resource`` except returns an error instead of ``None`` or panicking
+    pub fn try_resource<T: Component>(&self) -> Result<ResourceRef<'_, T>, EcsError> {
+        let resource = self.get_resource::<T>()?;
+        let name = type_name::<T>();
+        let guard = try_lock(self.locks[resource.lock].try_read(), name)?;
+        let ptr = resource.as_ptr().ok_or(EcsError::MissingResource(name))?;
+        Ok(ResourceRef {
+            _guard: guard,
+            resource: unsafe { &*(ptr as *mut T) },
+        })
+    }
+
+    /// Same as ``World::resource_mut`` except returns an error instead of ``None`` or panicking
+    pub fn try_resource_mut<T: Component>(&self) -> Result<ResourceMut<'_, T>, EcsError> {
+        let resource = self.get_resource::<T>()?;
+        let name = type_name::<T>();
+        let guard = try_lock(self.locks[resource.lock].try_write(), name)?;
+        let ptr = resource.as_ptr().ok_or(EcsError::MissingResource(name))?;
+        Ok(ResourceMut {
+            _guard: guard,
+            resource: unsafe { &mut *(ptr as *mut T) },
+        })
+    }
+
+    fn get_resource<T: Component>(&self) -> Result<&Resource, EcsError> {
+        self.resources
+            .get(&TypeId::of::<T>())
+            .ok_or(EcsError::MissingResource(type_name::<T>()))
+    }
+}
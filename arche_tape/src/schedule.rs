@@ -0,0 +1,325 @@
+use crate::change_detection::MAX_CHANGE_AGE;
+use crate::resource::{ResourceMut, ResourceRef};
+use crate::static_query::{StaticQuery, SyncQueryTuple};
+use crate::thread_pool::ThreadPool;
+use crate::{Component, FetchType, World};
+use std::any::{type_name, TypeId};
+use std::marker::PhantomData;
+
+/// The locks in ``World::locks`` that a system takes when it runs
+#[derive(Clone, Debug, Default)]
+pub struct Access {
+    reads: Vec<usize>,
+    writes: Vec<usize>,
+}
+
+impl Access {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the locks that a query taking ``fetch`` would take
+    pub fn add_fetch(&mut self, world: &World, fetch: &FetchType) {
+        let (id, write) = match fetch {
+            FetchType::Mut(id) => (id, true),
+            FetchType::Immut(id) | FetchType::Added(id) | FetchType::Changed(id) => (id, false),
+            FetchType::Optional(inner) => return self.add_fetch(world, inner),
+            FetchType::Resource(type_id) | FetchType::ResourceMut(type_id) => {
+                if let Some(resource) = world.resources.get(type_id) {
+                    self.add_lock(resource.lock, matches!(fetch, FetchType::ResourceMut(_)));
+                }
+                return;
+            }
+            FetchType::EcsId
+            | FetchType::MatchedPair(_)
+            | FetchType::With(_)
+            | FetchType::Without(_)
+            | FetchType::Or(_) => return,
+        };
+
+        // Components that aren't in any archetype yet have no lock and can't be fetched by the query
+        match id.is_wildcard() {
+            true => world
+                .lock_lookup
+                .iter()
+                .filter(|(comp_id, _)| comp_id.matches(*id))
+                .for_each(|(_, &lock)| self.add_lock(lock, write)),
+            false => {
+                if let Some(&lock) = world.lock_lookup.get(id) {
+                    self.add_lock(lock, write);
+                }
+            }
+        }
+    }
+
+    fn add_lock(&mut self, lock: usize, write: bool) {
+        // Taking a write lock while already holding the same lock would deadlock the system
+        assert!(
+            !self.writes.contains(&lock) && !(write && self.reads.contains(&lock)),
+            "A system cannot borrow a component or resource mutably while also borrowing it elsewhere"
+        );
+        match write {
+            true => self.writes.push(lock),
+            false => self.reads.push(lock),
+        }
+    }
+
+    /// Returns true if the two accesses can't be held at the same time
+    pub fn conflicts(&self, other: &Access) -> bool {
+        self.writes
+            .iter()
+            .any(|lock| other.reads.contains(lock) || other.writes.contains(lock))
+            || other.writes.iter().any(|lock| self.reads.contains(lock))
+    }
+}
+
+pub trait System: Send {
+    /// Adds every lock that the system takes when it runs to ``access``
+    fn access(&self, world: &World, access: &mut Access);
+    fn run(&mut self, world: &World);
+
+    /// Called after ``World::check_change_ticks`` clamps old ticks, systems should clamp any ticks they store the same way
+    fn check_change_tick(&mut self, _change_tick: u32) {}
+}
+
+pub trait SystemParamGATs<'a> {
+    type Item;
+
+    /// ``last_run`` is the world's change tick when the system last started running
+    fn get(world: &'a World, last_run: u32) -> Self::Item;
+}
+
+/// A function argument that a system can take, implemented for ``StaticQuery``, ``ResourceRef`` and ``ResourceMut``
+pub trait SystemParam: for<'a> SystemParamGATs<'a> {
+    fn access(world: &World, access: &mut Access);
+}
+
+impl<'b, 'a, Q: SyncQueryTuple> SystemParamGATs<'a> for StaticQuery<'b, Q> {
+    type Item = StaticQuery<'a, Q>;
+
+    fn get(world: &'a World, last_run: u32) -> Self::Item {
+        Q::new(world, last_run)
+    }
+}
+
+impl<'b, Q: SyncQueryTuple> SystemParam for StaticQuery<'b, Q> {
+    fn access(world: &World, access: &mut Access) {
+        for fetch in Q::fetch_types(world)
+            .iter()
+            .flat_map(|fetches| fetches.as_ref())
+        {
+            access.add_fetch(world, fetch);
+        }
+    }
+}
+
+impl<'b, 'a, T: Component + Send + Sync> SystemParamGATs<'a> for ResourceRef<'b, T> {
+    type Item = ResourceRef<'a, T>;
+
+    fn get(world: &'a World, _: u32) -> Self::Item {
+        world
+            .resource::<T>()
+            .unwrap_or_else(|| panic!("Resource {} does not exist", type_name::<T>()))
+    }
+}
+
+impl<'b, T: Component + Send + Sync> SystemParam for ResourceRef<'b, T> {
+    fn access(world: &World, access: &mut Access) {
+        access.add_fetch(world, &FetchType::Resource(TypeId::of::<T>()));
+    }
+}
+
+impl<'b, 'a, T: Component + Send + Sync> SystemParamGATs<'a> for ResourceMut<'b, T> {
+    type Item = ResourceMut<'a, T>;
+
+    fn get(world: &'a World, _: u32) -> Self::Item {
+        world
+            .resource_mut::<T>()
+            .unwrap_or_else(|| panic!("Resource {} does not exist", type_name::<T>()))
+    }
+}
+
+impl<'b, T: Component + Send + Sync> SystemParam for ResourceMut<'b, T> {
+    fn access(world: &World, access: &mut Access) {
+        access.add_fetch(world, &FetchType::ResourceMut(TypeId::of::<T>()));
+    }
+}
+
+pub trait IntoSystem<Params> {
+    type System: System + 'static;
+
+    fn system(self) -> Self::System;
+}
+
+/// A system created from a function or closure whose arguments are all ``SystemParam``s
+pub struct FunctionSystem<F, Params> {
+    func: F,
+    last_run: u32,
+    _p: PhantomData<fn() -> Params>,
+}
+
+macro_rules! impl_function_system {
+    ($($P:ident)*) => {
+        impl<Func, $($P: SystemParam + 'static),*> System for FunctionSystem<Func, ($($P,)*)>
+        where
+            Func: Send + FnMut($($P),*) + for<'a> FnMut($(<$P as SystemParamGATs<'a>>::Item),*),
+        {
+            #[allow(unused_variables)]
+            fn access(&self, world: &World, access: &mut Access) {
+                $($P::access(world, access);)*
+            }
+
+            #[allow(non_snake_case, unused_variables)]
+            fn run(&mut self, world: &World) {
+                // Calling through a function with a single ``FnMut`` bound picks which of our two bounds to use
+                #[allow(clippy::too_many_arguments)]
+                fn call<$($P),*>(mut func: impl FnMut($($P),*), $($P: $P),*) {
+                    func($($P),*)
+                }
+
+                let last_run = std::mem::replace(&mut self.last_run, world.increment_change_tick());
+                $(
+                    let $P = <$P as SystemParamGATs<'_>>::get(world, last_run);
+                )*
+                call(&mut self.func, $($P),*);
+            }
+
+            fn check_change_tick(&mut self, change_tick: u32) {
+                if change_tick.wrapping_sub(self.last_run) > MAX_CHANGE_AGE {
+                    self.last_run = change_tick.wrapping_sub(MAX_CHANGE_AGE);
+                }
+            }
+        }
+
+        impl<Func, $($P: SystemParam + 'static),*> IntoSystem<($($P,)*)> for Func
+        where
+            Func: Send + 'static + FnMut($($P),*) + for<'a> FnMut($(<$P as SystemParamGATs<'a>>::Item),*),
+        {
+            type System = FunctionSystem<Func, ($($P,)*)>;
+
+            fn system(self) -> Self::System {
+                FunctionSystem {
+                    func: self,
+                    last_run: 0,
+                    _p: PhantomData,
+                }
+            }
+        }
+    };
+}
+
+impl_function_system!(A B C D E F G H);
+impl_function_system!(A B C D E F G);
+impl_function_system!(A B C D E F);
+impl_function_system!(A B C D E);
+impl_function_system!(A B C D);
+impl_function_system!(A B C);
+impl_function_system!(A B);
+impl_function_system!(A);
+impl_function_system!();
+
+/// Lets batches of systems share the world across the thread pool's threads
+struct SharedWorld<'a>(&'a World);
+// Safety: systems in the same batch never take conflicting locks so they never access the same data
+unsafe impl Send for SharedWorld<'_> {}
+
+/// A list of systems that are run in batches. Systems in the same batch have no conflicting ``Access`` and run in
+/// parallel. Systems that do conflict run in the order that they were added.
+///
+/// Components and resources accessed by systems may be accessed from any of the schedule's threads so they must
+/// be ``Send`` and ``Sync``.
+pub struct Schedule {
+    systems: Vec<Box<dyn System>>,
+    pool: ThreadPool,
+}
+
+impl Default for Schedule {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Schedule {
+    /// Creates a schedule with one thread per available core
+    pub fn new() -> Self {
+        let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+        Self::with_threads(threads)
+    }
+
+    pub fn with_threads(threads: usize) -> Self {
+        Self {
+            systems: Vec::new(),
+            pool: ThreadPool::new(threads),
+        }
+    }
+
+    pub fn thread_count(&self) -> usize {
+        self.pool.thread_count()
+    }
+
+    pub fn add_system<Params>(&mut self, system: impl IntoSystem<Params>) -> &mut Self {
+        self.systems.push(Box::new(system.system()));
+        self
+    }
+
+    /// Groups the systems into batches that can each run in parallel, a system is placed in the first batch after
+    /// every batch containing a system that it conflicts with
+    pub(crate) fn batches(&self, world: &World) -> Vec<Vec<usize>> {
+        let mut batches: Vec<(Vec<usize>, Access)> = Vec::new();
+
+        for (n, system) in self.systems.iter().enumerate() {
+            let mut access = Access::new();
+            system.access(world, &mut access);
+
+            let first_batch = batches
+                .iter()
+                .rposition(|(_, batch_access)| batch_access.conflicts(&access))
+                .map_or(0, |conflict| conflict + 1);
+
+            match batches.get_mut(first_batch) {
+                Some((systems, batch_access)) => {
+                    systems.push(n);
+                    batch_access.reads.extend_from_slice(&access.reads);
+                    batch_access.writes.extend_from_slice(&access.writes);
+                }
+                None => batches.push((vec![n], access)),
+            }
+        }
+
+        batches.into_iter().map(|(systems, _)| systems).collect()
+    }
+
+    /// Runs every system once, the batches are planned before any system runs so systems never block on each other
+    pub fn run(&mut self, world: &mut World) {
+        self.run_systems(world);
+        if world.check_change_ticks() {
+            let change_tick = world.change_tick();
+            for system in self.systems.iter_mut() {
+                system.check_change_tick(change_tick);
+            }
+        }
+        world.auto_compact_archetypes();
+    }
+
+    fn run_systems(&mut self, world: &World) {
+        let batches = self.batches(world);
+
+        let mut systems = self.systems.iter_mut().map(Some).collect::<Vec<_>>();
+        for batch in batches {
+            if let [n] = batch[..] {
+                systems[n].take().unwrap().run(world);
+                continue;
+            }
+
+            let jobs = batch
+                .into_iter()
+                .map(|n| {
+                    let system = systems[n].take().unwrap();
+                    let world = SharedWorld(world);
+                    Box::new(move || system.run(world.0)) as Box<dyn FnOnce() + Send + '_>
+                })
+                .collect();
+            self.pool.scope(jobs);
+        }
+    }
+}
@@ -0,0 +1,371 @@
+use crate::change_detection::{ColumnTicks, ComponentTicks};
+use crate::entities::Entities;
+use crate::registry::{take_bytes, ComponentRegistry, RegistryEntry, SnapshotComponent};
+use crate::world::{AddRemoveCache, ArchIndex, Archetype, ComponentMeta, EntityMeta, InstanceMeta};
+use crate::{EcsId, World};
+use std::any::TypeId;
+use std::cell::UnsafeCell;
+use std::collections::HashMap;
+use untyped_vec::{TypeInfo, UntypedVec};
+
+const MAGIC: &[u8; 4] = b"ATWS";
+const VERSION: u32 = 1;
+
+const META_RAW: u8 = 0;
+const META_REGISTERED: u8 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnapshotError {
+    /// The component has a drop_fn or a rust type but was not registered in the ComponentRegistry
+    UnregisteredComponent(EcsId),
+    /// The snapshot refers to a component name that is not in the ComponentRegistry
+    UnknownComponentName(String),
+    UnexpectedEof,
+    InvalidData(&'static str),
+}
+
+impl std::fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SnapshotError::UnregisteredComponent(id) => {
+                write!(f, "component {} is not registered", id)
+            }
+            SnapshotError::UnknownComponentName(name) => {
+                write!(f, "no component registered with the name {}", name)
+            }
+            SnapshotError::UnexpectedEof => write!(f, "snapshot ended unexpectedly"),
+            SnapshotError::InvalidData(reason) => write!(f, "invalid snapshot: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+/// How the data of a component is saved, registered components go through their registered functions
+/// and everything else has no drop_fn so is copied as raw bytes
+#[derive(Clone)]
+struct LoadedMeta<'a> {
+    component_meta: ComponentMeta,
+    entry: Option<&'a RegistryEntry>,
+}
+
+impl World {
+    /// Looks up how the data for ``comp_id`` should be saved
+    fn snapshot_entry<'a>(
+        &self,
+        registry: &'a ComponentRegistry,
+        type_ids: &HashMap<EcsId, TypeId>,
+        comp_id: EcsId,
+    ) -> Result<Option<&'a RegistryEntry>, SnapshotError> {
+        let entity = match comp_id.is_pair() {
+            true => self.pair_parts(comp_id).unwrap().0,
+            false => comp_id,
+        };
+
+        if let Some(type_id) = type_ids.get(&entity) {
+            return match registry.get_by_type(*type_id) {
+                Some(entry) if entry.binary.is_some() => Ok(Some(entry)),
+                _ => Err(SnapshotError::UnregisteredComponent(entity)),
+            };
+        }
+
+        match self.get_component_meta(entity).unwrap().drop_fn {
+            Some(_) => Err(SnapshotError::UnregisteredComponent(entity)),
+            None => Ok(None),
+        }
+    }
+
+    /// Serializes every entity and component in the world into a binary snapshot that can be loaded with ``World::load_snapshot``.
+    ///
+    /// Every rust type that has been used as a component must be registered in ``registry``,
+    /// EcsIds are saved as is so generations and reused indices are kept exactly
+    pub fn save_snapshot(
+        &mut self,
+        registry: &ComponentRegistry,
+    ) -> Result<Vec<u8>, SnapshotError> {
+        let mut out = Vec::new();
+        out.extend_from_slice(MAGIC);
+        VERSION.serialize(&mut out);
+
+        self.entities.generations.len().serialize(&mut out);
+        for &(alive, generation) in self.entities.generations.iter() {
+            alive.serialize(&mut out);
+            generation.serialize(&mut out);
+        }
+        self.entities.despawned.serialize(&mut out);
+
+        let type_ids = self
+            .type_id_to_ecs_id
+            .iter()
+            .map(|(&type_id, &entity)| (entity, type_id))
+            .collect::<HashMap<_, _>>();
+
+        let alive_metas = self
+            .entities
+            .generations
+            .iter()
+            .enumerate()
+            .filter(|(_, &(alive, _))| alive)
+            .filter_map(|(idx, &(_, generation))| {
+                let meta = self.ecs_id_meta.get(idx)?.as_ref()?;
+                Some((EcsId::new(idx as u32, generation), meta))
+            })
+            .collect::<Vec<_>>();
+        alive_metas.len().serialize(&mut out);
+        for (entity, meta) in alive_metas {
+            entity.serialize(&mut out);
+            match self.snapshot_entry(registry, &type_ids, entity)? {
+                Some(entry) => {
+                    META_REGISTERED.serialize(&mut out);
+                    entry.name.serialize(&mut out);
+                }
+                None => {
+                    META_RAW.serialize(&mut out);
+                    meta.component_meta.layout.size().serialize(&mut out);
+                    meta.component_meta.layout.align().serialize(&mut out);
+                }
+            }
+        }
+
+        // Archetypes that had a component despawned are left empty in the vec, they can't be loaded so are skipped
+        let archetypes = self
+            .archetypes
+            .iter()
+            .filter(|archetype| {
+                archetype
+                    .comp_ids
+                    .iter()
+                    .all(|&id| self.is_component_alive(id))
+            })
+            .collect::<Vec<_>>();
+        archetypes.len().serialize(&mut out);
+        for archetype in archetypes {
+            archetype.comp_ids.serialize(&mut out);
+            archetype.entities.serialize(&mut out);
+
+            for (comp_id, storage, _) in archetype.component_storages.iter() {
+                let entry = self.snapshot_entry(registry, &type_ids, *comp_id)?;
+                // Safe because we have a mutable borrow of the world so nothing else can be accessing the storage
+                let storage = unsafe { &*storage.get() };
+
+                let mut column = Vec::new();
+                for idx in 0..storage.len() {
+                    let ptr = storage.get_raw(idx).unwrap();
+                    match entry {
+                        Some(entry) => (entry.binary.unwrap().serialize)(ptr, &mut column),
+                        None => {
+                            let size = storage.get_type_info().layout.size();
+                            column.extend_from_slice(unsafe {
+                                std::slice::from_raw_parts(ptr, size)
+                            });
+                        }
+                    }
+                }
+
+                column.len().serialize(&mut out);
+                out.extend_from_slice(&column);
+            }
+        }
+
+        Ok(out)
+    }
+
+    /// Creates a world from a snapshot made by ``World::save_snapshot``, every registered component in the snapshot
+    /// must be registered with the same name in ``registry``
+    pub fn load_snapshot(
+        data: &[u8],
+        registry: &ComponentRegistry,
+    ) -> Result<World, SnapshotError> {
+        let input = &mut &data[..];
+
+        if take_bytes(input, MAGIC.len())? != MAGIC {
+            return Err(SnapshotError::InvalidData("not a world snapshot"));
+        }
+        if u32::deserialize(input)? != VERSION {
+            return Err(SnapshotError::InvalidData("unsupported snapshot version"));
+        }
+
+        let mut world = World::new();
+
+        let generations_len = usize::deserialize(input)?;
+        let mut entities = Entities::new();
+        for _ in 0..generations_len {
+            let alive = bool::deserialize(input)?;
+            let generation = u32::deserialize(input)?;
+            entities.generations.push((alive, generation));
+        }
+        entities.despawned = Vec::deserialize(input)?;
+        entities.validate().map_err(SnapshotError::InvalidData)?;
+        world.entities = entities;
+
+        let mut metas: Vec<Option<LoadedMeta>> = vec![None; generations_len];
+        let metas_len = usize::deserialize(input)?;
+        for _ in 0..metas_len {
+            let entity = EcsId::deserialize(input)?;
+            if !world.entities.is_alive(entity) {
+                return Err(SnapshotError::InvalidData(
+                    "component meta for a dead entity",
+                ));
+            }
+
+            let meta = match u8::deserialize(input)? {
+                META_RAW => {
+                    let size = usize::deserialize(input)?;
+                    let align = usize::deserialize(input)?;
+                    let layout = core::alloc::Layout::from_size_align(size, align)
+                        .map_err(|_| SnapshotError::InvalidData("invalid component layout"))?;
+                    LoadedMeta {
+                        component_meta: ComponentMeta {
+                            drop_fn: None,
+                            layout,
+                            on_add: None,
+                            on_remove: None,
+                        },
+                        entry: None,
+                    }
+                }
+                META_REGISTERED => {
+                    let name = String::deserialize(input)?;
+                    let entry = match registry.get_by_name(&name) {
+                        Some(entry) if entry.binary.is_some() => entry,
+                        _ => return Err(SnapshotError::UnknownComponentName(name)),
+                    };
+                    if world
+                        .type_id_to_ecs_id
+                        .insert(entry.type_id, entity)
+                        .is_some()
+                    {
+                        return Err(SnapshotError::InvalidData(
+                            "component registered for two entities",
+                        ));
+                    }
+                    LoadedMeta {
+                        component_meta: entry.component_meta.clone(),
+                        entry: Some(entry),
+                    }
+                }
+                _ => return Err(SnapshotError::InvalidData("unknown component meta kind")),
+            };
+            metas[entity.uindex()] = Some(meta);
+        }
+
+        world.ecs_id_meta.resize_with(generations_len, || None);
+
+        let archetypes_len = usize::deserialize(input)?;
+        for arch_idx in 0..archetypes_len {
+            let comp_ids = Vec::<EcsId>::deserialize(input)?;
+            if comp_ids.windows(2).any(|ids| ids[0] >= ids[1]) {
+                return Err(SnapshotError::InvalidData(
+                    "archetype components are not sorted",
+                ));
+            }
+            if world.archetype_lookup.contains_key(&comp_ids) {
+                return Err(SnapshotError::InvalidData(
+                    "two archetypes have the same components",
+                ));
+            }
+            if arch_idx == 0 && !comp_ids.is_empty() {
+                return Err(SnapshotError::InvalidData(
+                    "first archetype must have no components",
+                ));
+            }
+
+            let entities = Vec::<EcsId>::deserialize(input)?;
+            for (idx, &entity) in entities.iter().enumerate() {
+                let meta = match world.entities.is_alive(entity) {
+                    true => metas[entity.uindex()].as_ref(),
+                    false => None,
+                }
+                .ok_or(SnapshotError::InvalidData(
+                    "archetype contains a dead entity",
+                ))?;
+                if world.ecs_id_meta[entity.uindex()].is_some() {
+                    return Err(SnapshotError::InvalidData(
+                        "entity is in more than one archetype",
+                    ));
+                }
+
+                world.ecs_id_meta[entity.uindex()] = Some(EntityMeta {
+                    instance_meta: InstanceMeta {
+                        archetype: ArchIndex(arch_idx),
+                        index: idx,
+                    },
+                    component_meta: meta.component_meta.clone(),
+                });
+            }
+
+            let mut component_storages = Vec::with_capacity(comp_ids.len());
+            for &comp_id in comp_ids.iter() {
+                let meta = match comp_id.is_pair() {
+                    true => world.pair_parts(comp_id).map(|(relation, _)| relation),
+                    false => Some(comp_id).filter(|&id| world.entities.is_alive(id)),
+                }
+                .and_then(|entity| metas[entity.uindex()].as_ref())
+                .ok_or(SnapshotError::InvalidData(
+                    "archetype contains a dead component",
+                ))?;
+
+                let column_len = usize::deserialize(input)?;
+                let column = &mut take_bytes(input, column_len)?;
+
+                let component_meta = &meta.component_meta;
+                let type_info = TypeInfo::new(component_meta.layout, component_meta.drop_fn);
+                // Safe because the type info comes from the ComponentMeta used for this component
+                let mut storage = unsafe { UntypedVec::new_from_raw(type_info) };
+                for _ in 0..entities.len() {
+                    match meta.entry {
+                        Some(entry) => (entry.binary.unwrap().deserialize)(column, &mut storage)?,
+                        None => {
+                            let bytes = take_bytes(column, component_meta.layout.size())?;
+                            // push_raw copies bytewise so the bytes don't need to be aligned
+                            unsafe { storage.push_raw(bytes.as_ptr() as *mut _) };
+                        }
+                    }
+                }
+                if !column.is_empty() {
+                    return Err(SnapshotError::InvalidData(
+                        "component data has trailing bytes",
+                    ));
+                }
+
+                let mut ticks = ColumnTicks::new();
+                for _ in 0..entities.len() {
+                    ticks.push(ComponentTicks::new(world.change_tick()));
+                }
+                component_storages.push((
+                    comp_id,
+                    UnsafeCell::new(storage),
+                    UnsafeCell::new(ticks),
+                ));
+            }
+
+            world.push_archetype(Archetype {
+                comp_lookup: {
+                    let mut lookup = HashMap::with_hasher(crate::utils::TypeIdHasherBuilder());
+                    for (idx, &id) in comp_ids.iter().enumerate() {
+                        lookup.insert(id, idx);
+                    }
+                    lookup
+                },
+                entities,
+                component_storages,
+                comp_ids,
+                add_remove_cache: AddRemoveCache::new(),
+            });
+        }
+
+        if !input.is_empty() {
+            return Err(SnapshotError::InvalidData("snapshot has trailing bytes"));
+        }
+
+        for (idx, &(alive, _)) in world.entities.generations.iter().enumerate() {
+            if alive && world.ecs_id_meta[idx].is_none() {
+                return Err(SnapshotError::InvalidData(
+                    "alive entity is not in an archetype",
+                ));
+            }
+        }
+
+        Ok(world)
+    }
+}
@@ -1,4 +1,9 @@
-use crate::{utils::EitherGuard, world::Archetype, Component, EcsId, FetchType, World};
+use crate::bitset_iterator::Bitvec;
+use crate::change_detection::{newest_tick, ComponentTicks, QueryTicks};
+use crate::{
+    utils::EitherGuard, world::Archetype, BorrowError, Component, EcsError, EcsId, FetchType, World,
+};
+use std::sync::{TryLockError, TryLockResult};
 use std::{any::TypeId, marker::PhantomData};
 
 // If we remove the 'static bound here we are required to manually annotate 'static lifetimes for StaticQuery's in
@@ -7,11 +12,16 @@ pub struct StaticQuery<'a, Q: QueryTuple + 'static> {
     world: &'a World,
     _guards: <Q as QueryTupleGATs<'a>>::Guards,
     fetches: Option<Q::Fetches>,
+    ticks: QueryTicks,
+    /// The archetypes matched by each ``Or`` filter in the query
+    or_bits: Vec<Bitvec>,
     _p: PhantomData<Q>,
 }
 
 pub struct StaticQueryIter<'a, Q: QueryTuple + 'static> {
+    world: &'a World,
     fetches: Option<&'a Q::Fetches>,
+    ticks: QueryTicks,
     archetypes: <Q as QueryTupleGATs<'a>>::ArchetypeIter,
     intra_iter: IntraArchetypeIter<'a, Q>,
 }
@@ -28,59 +38,156 @@ pub trait QueryTupleGATs<'a>: 'static {
 }
 pub trait QueryTuple: Sized + for<'a> QueryTupleGATs<'a> + 'static {
     type Ptrs: Copy;
-    type Fetches;
+    type Fetches: AsRef<[FetchType]>;
+
+    fn new(world: &World, last_run: u32) -> StaticQuery<Self>;
+    fn try_new(world: &World, last_run: u32) -> Result<StaticQuery<'_, Self>, EcsError>;
+    /// Returns ``None`` if the query can't match anything in which case it doesn't lock anything either
+    fn fetch_types(world: &World) -> Option<Self::Fetches>;
+}
 
-    fn new(world: &World) -> StaticQuery<Self>;
+/// Queries made only of ``SyncQueryParam``s, these are the queries that systems can take
+pub trait SyncQueryTuple: QueryTuple {}
+
+/// The index into ``World::locks`` that ``fetch`` needs and whether it is written to
+fn fetch_lock(world: &World, fetch: &FetchType) -> Option<(usize, bool)> {
+    match fetch {
+        FetchType::Mut(id) => Some((world.lock_lookup[id], true)),
+        FetchType::Immut(id) | FetchType::Added(id) | FetchType::Changed(id) => {
+            Some((world.lock_lookup[id], false))
+        }
+        FetchType::EcsId
+        | FetchType::MatchedPair(_)
+        | FetchType::With(_)
+        | FetchType::Without(_)
+        | FetchType::Or(_) => None,
+        FetchType::Resource(type_id) => Some((world.resources[type_id].lock, false)),
+        FetchType::ResourceMut(type_id) => Some((world.resources[type_id].lock, true)),
+        // The component may not be in any archetype yet in which case it has no lock
+        FetchType::Optional(inner) => {
+            let &idx = world.lock_lookup.get(&inner.get_id().unwrap())?;
+            Some((idx, matches!(**inner, FetchType::Mut(_))))
+        }
+    }
+}
+
+/// Takes every lock that ``fetches`` need without blocking, ``names`` are the query params that each fetch came from
+fn lock_fetches<'a, const N: usize>(
+    world: &'a World,
+    fetches: &[FetchType; N],
+    names: [&'static str; N],
+) -> Result<[EitherGuard<'a>; N], BorrowError> {
+    let locks = fetches.each_ref().map(|fetch| fetch_lock(world, fetch));
+
+    // Taking a lock that the query already holds would always fail so check for it up front to give a better error
+    for (n, &(idx, write)) in locks
+        .iter()
+        .enumerate()
+        .filter_map(|(n, lock)| Some((n, lock.as_ref()?)))
+    {
+        let aliased = locks[..n]
+            .iter()
+            .flatten()
+            .any(|&(other_idx, other_write)| other_idx == idx && (write || other_write));
+        if aliased {
+            return Err(BorrowError::Aliased(names[n]));
+        }
+    }
+
+    const NONE_GUARD: EitherGuard = EitherGuard::None;
+    let mut guards = [NONE_GUARD; N];
+    for ((lock, guard), name) in locks.iter().zip(guards.iter_mut()).zip(names) {
+        let &(idx, write) = match lock {
+            Some(lock) => lock,
+            None => continue,
+        };
+        let lock = &world.locks[idx];
+        *guard = match write {
+            true => EitherGuard::Write(try_lock(lock.try_write(), name)?),
+            false => EitherGuard::Read(try_lock(lock.try_read(), name)?),
+        };
+    }
+    Ok(guards)
+}
+
+pub(crate) fn try_lock<G>(result: TryLockResult<G>, name: &'static str) -> Result<G, BorrowError> {
+    match result {
+        Ok(guard) => Ok(guard),
+        Err(TryLockError::WouldBlock) => Err(BorrowError::AlreadyBorrowed(name)),
+        Err(TryLockError::Poisoned(e)) => panic!("{}", e),
+    }
 }
 
 macro_rules! impl_query_tuple {
     ($($T:ident)* $N:literal) => {
         impl<$($T: for<'a> QueryParam<'a>),*> QueryTuple for ($($T,)*) {
-            type Ptrs = [*mut u8; $N];
+            type Ptrs = [FetchPtr; $N];
             type Fetches = [crate::FetchType; $N];
 
-            fn new(world: &World) -> StaticQuery<Self> {
-                StaticQuery::<($($T,)*)>::new(world)
+            fn new(world: &World, last_run: u32) -> StaticQuery<Self> {
+                StaticQuery::<($($T,)*)>::new(world, last_run)
+            }
+
+            fn try_new(world: &World, last_run: u32) -> Result<StaticQuery<'_, Self>, EcsError> {
+                StaticQuery::<($($T,)*)>::try_new(world, last_run)
+            }
+
+            fn fetch_types(world: &World) -> Option<Self::Fetches> {
+                Some([$(
+                    $T::fetch_type(world)?,
+                )*])
             }
         }
 
+        impl<$($T: SyncQueryParam),*> SyncQueryTuple for ($($T,)*) {}
+
         impl<'a, $($T: for<'b> QueryParam<'b>),*> QueryTupleGATs<'a> for ($($T,)*) {
             type Guards = [EitherGuard<'a>; $N];
             type ArchetypeIter = crate::world::ArchetypeIter<'a, $N>;
         }
 
         impl<'a, $($T: for<'b> QueryParam<'b>,)*> StaticQuery<'a, ($($T,)*)> {
+            /// Panics if the query borrows a component mutably more than once, or if any of its borrows conflict with
+            /// a query or resource borrow that is still alive
+            pub(crate) fn new(world: &'a World, last_run: u32) -> Self {
+                Self::try_new(world, last_run).unwrap_or_else(|e| panic!("{}", e))
+            }
+
             #[allow(non_snake_case)]
-            pub(crate) fn new(world: &'a World) -> Self {
-                let fetches = (|| {
-                    Some([$(
-                        $T::fetch_type(world)?,
-                    )*])
-                })();
+            pub(crate) fn try_new(world: &'a World, last_run: u32) -> Result<Self, EcsError> {
+                let fetches = <($($T,)*) as QueryTuple>::fetch_types(world);
 
                 let guards = match &fetches {
-                    Some([$($T,)*]) => {
-                        [$(
-                            match $T {
-                                FetchType::Mut(id) => EitherGuard::Write(world.locks[world.lock_lookup[id]].write().unwrap()),
-                                FetchType::Immut(id) => EitherGuard::Read(world.locks[world.lock_lookup[id]].read().unwrap()),
-                                FetchType::EcsId => EitherGuard::None,
-                            },
-                        )*]
-                    }
+                    Some(fetches) => lock_fetches(world, fetches, [$(std::any::type_name::<$T>(),)*])?,
                     None => {
                         const NONE_GUARD: EitherGuard = EitherGuard::None;
                         [NONE_GUARD; $N]
                     }
                 };
 
-                Self {
+                let or_bits = match &fetches {
+                    Some(fetches) => FetchType::or_archetype_bits(world, fetches),
+                    None => Vec::new(),
+                };
+
+                Ok(Self {
                     fetches,
                     world,
+                    or_bits,
+                    ticks: QueryTicks {
+                        last_run,
+                        change_tick: world.increment_change_tick(),
+                    },
 
                     _guards: guards,
                     _p: PhantomData,
-                }
+                })
+            }
+
+            /// The tick that this query marks changed components with, pass it to ``World::query_since`` to only see
+            /// components added/changed after this query
+            pub fn change_tick(&self) -> u32 {
+                self.ticks.change_tick
             }
 
             #[allow(non_snake_case)]
@@ -94,11 +201,23 @@ macro_rules! impl_query_tuple {
                 assert!(meta.index < archetype.entities.len());
 
                 let [$($T,)*] = self.fetches.as_ref()?;
-                let [$(mut $T,)*] = [$($T::create_ptr(archetype, $T)?,)*];
+                let ticks = self.ticks;
+                let [$(mut $T,)*] = [$($T::create_ptr(self.world, archetype, $T, ticks)?,)*];
                 $(
                     $T::offset_ptr(&mut $T, meta.index);
                 )*
-                Some(($($T::cast_ptr($T),)*))
+                if !($($T::matches_row(&$T, ticks) &&)* true) {
+                    return None;
+                }
+                Some(($($T::cast_ptr($T, ticks),)*))
+            }
+
+            /// Same as ``StaticQuery::get`` except returns an error saying why the entity wasn't returned
+            pub fn try_get(&mut self, entity: EcsId) -> Result<($(<$T as QueryParam<'_>>::Returns,)*), EcsError> {
+                if !self.world.is_alive(entity) {
+                    return Err(EcsError::DeadEntity(entity));
+                }
+                self.get(entity).ok_or(EcsError::QueryMismatch(entity))
             }
 
             #[allow(unused_variables, non_snake_case)]
@@ -107,18 +226,16 @@ macro_rules! impl_query_tuple {
                 let archetype_iter: crate::world::ArchetypeIter<$N> = match &self.fetches {
                     Some([$($T,)*]) => {
                         let mut bitlength = self.world.entities_bitvec.len as u32;
-                        let iters = [$(
-                            match $T {
-                                FetchType::EcsId => {
-                                    (self.world.entities_bitvec.data.iter(), identity)
-                                }
-                                FetchType::Immut(id) | FetchType::Mut(id) => {
-                                    let bitvec = self.world.archetype_bitset.get_bitvec(*id).unwrap();
-                                    bitlength = u32::min(bitlength, bitvec.len as u32);
-                                    (bitvec.data.iter(), identity)
-                                }
-                            },
-                        )*];
+                        let mut or_bits = self.or_bits.iter();
+                        let iters = [$({
+                            let or_bits = match $T {
+                                FetchType::Or(_) => or_bits.next(),
+                                _ => None,
+                            };
+                            let (iter, len) = self.world.fetch_archetype_bits($T, or_bits).unwrap();
+                            bitlength = u32::min(bitlength, len);
+                            iter
+                        },)*];
                         self.world.query_archetypes(iters, bitlength)
                     }
                     None => {
@@ -134,7 +251,9 @@ macro_rules! impl_query_tuple {
                 };
 
                 StaticQueryIter {
+                    world: self.world,
                     fetches: self.fetches.as_ref(),
+                    ticks: self.ticks,
                     archetypes: archetype_iter,
                     intra_iter: IntraArchetypeIter::<($($T,)*)>::unit(),
                 }
@@ -147,22 +266,29 @@ macro_rules! impl_query_tuple {
                 #[allow(non_snake_case, unused_assignments)]
                 #[inline(always)]
                 fn next(&mut self) -> Option<Self::Item> {
+                    let ticks = self.ticks;
                     loop {
                         match self.intra_iter.next() {
                             Some([$($T,)*]) => {
-                                return Some((
-                                    $($T::cast_ptr($T),)*
-                                ));
+                                if $($T::matches_row(&$T, ticks) &&)* true {
+                                    return Some((
+                                        $($T::cast_ptr($T, ticks),)*
+                                    ));
+                                }
                             }
                             None => {
                                 let archetype = self.archetypes.next()?;
-                                let mut ptrs = [0x0 as *mut u8; $N];
+                                let mut ptrs = [FetchPtr::null(); $N];
 
                                 let fetches = self.fetches.as_ref().unwrap();
                                 let mut n = 0;
                                 $({
                                     let fetch = &fetches[n];
-                                    let ptr = $T::create_ptr(archetype, fetch).unwrap();
+                                    // Archetypes where nothing can match (i.e. no changes since the last run) are skipped
+                                    let ptr = match $T::create_ptr(self.world, archetype, fetch, ticks) {
+                                        Some(ptr) => ptr,
+                                        None => continue,
+                                    };
                                     ptrs[n] = ptr;
                                     n += 1;
                                 })*
@@ -182,14 +308,14 @@ macro_rules! impl_query_tuple {
             fn unit() -> Self {
                 Self {
                     remaining: 0,
-                    ptrs: [0x0 as _; $N],
+                    ptrs: [FetchPtr::null(); $N],
                     _p: PhantomData,
                 }
             }
         }
 
         impl<'a, $($T: for<'b> QueryParam<'b>,)*> Iterator for IntraArchetypeIter<'a, ($($T,)*)> {
-            type Item = [*mut u8; $N];
+            type Item = [FetchPtr; $N];
 
             #[allow(unused_assignments)]
             fn next(&mut self) -> Option<Self::Item> {
@@ -223,15 +349,64 @@ impl_query_tuple!(A B C 3);
 impl_query_tuple!(A B 2);
 impl_query_tuple!(A 1);
 
+/// Pointers to the current element of a ``QueryParam`` and its change ticks
+#[derive(Copy, Clone)]
+pub struct FetchPtr {
+    pub(crate) data: *mut u8,
+    /// Null if the ``QueryParam`` doesn't use change ticks
+    pub(crate) ticks: *mut ComponentTicks,
+}
+
+impl FetchPtr {
+    pub(crate) fn null() -> Self {
+        Self {
+            data: 0x0 as _,
+            ticks: 0x0 as _,
+        }
+    }
+
+    fn offset<T>(&mut self, elements: usize) {
+        self.data = unsafe { (self.data as *mut T).add(elements) as *mut u8 };
+        if !self.ticks.is_null() {
+            self.ticks = unsafe { self.ticks.add(elements) };
+        }
+    }
+}
+
 pub trait QueryParam<'a>: 'static {
     type Returns: 'a;
 
     fn fetch_type(world: &World) -> Option<FetchType>;
-    fn create_ptr(archetype: &Archetype, fetch: &FetchType) -> Option<*mut u8>;
-    fn offset_ptr(ptr: &mut *mut u8, elements: usize);
-    fn cast_ptr(ptr: *mut u8) -> Self::Returns;
+    /// Returns ``None`` if no entity in the archetype can match
+    fn create_ptr(
+        world: &World,
+        archetype: &Archetype,
+        fetch: &FetchType,
+        ticks: QueryTicks,
+    ) -> Option<FetchPtr>;
+    fn offset_ptr(ptr: &mut FetchPtr, elements: usize);
+    /// Returns false if the element should be skipped
+    fn matches_row(_ptr: &FetchPtr, _ticks: QueryTicks) -> bool {
+        true
+    }
+    fn cast_ptr(ptr: FetchPtr, ticks: QueryTicks) -> Self::Returns;
 }
 
+/// Query params that can be used from any of a ``Schedule``'s threads, the data they fetch has to be ``Send`` and ``Sync``
+pub trait SyncQueryParam: for<'a> QueryParam<'a> {}
+impl<T: Component + Send + Sync> SyncQueryParam for &'static mut T {}
+impl<T: Component + Send + Sync> SyncQueryParam for &'static T {}
+impl<T: Component + Send + Sync> SyncQueryParam for Option<&'static mut T> {}
+impl<T: Component + Send + Sync> SyncQueryParam for Option<&'static T> {}
+impl<T: Component + Send + Sync> SyncQueryParam for Added<T> {}
+impl<T: Component + Send + Sync> SyncQueryParam for Changed<T> {}
+impl<T: Component + Send + Sync> SyncQueryParam for Res<T> {}
+// These never access a component or resource
+impl<T: Component> SyncQueryParam for With<T> {}
+impl<T: Component> SyncQueryParam for Without<T> {}
+impl SyncQueryParam for EcsIds {}
+impl<R: Component> SyncQueryParam for Relation<R> {}
+
 impl<'a, T: Component> QueryParam<'a> for &'static mut T {
     type Returns = &'a mut T;
 
@@ -240,18 +415,33 @@ impl<'a, T: Component> QueryParam<'a> for &'static mut T {
         Some(FetchType::Mut(id))
     }
 
-    fn create_ptr(archetype: &Archetype, fetch: &FetchType) -> Option<*mut u8> {
+    fn create_ptr(
+        _: &World,
+        archetype: &Archetype,
+        fetch: &FetchType,
+        ticks: QueryTicks,
+    ) -> Option<FetchPtr> {
         let &storage_idx = archetype.comp_lookup.get(&fetch.get_id().unwrap())?;
-        let storage = unsafe { &mut *archetype.component_storages[storage_idx].1.get() };
-        unsafe { Some(storage.as_mut_ptr()) }
+        let (_, storage, column_ticks) = &archetype.component_storages[storage_idx];
+        let storage = unsafe { &mut *storage.get() };
+        let column_ticks = unsafe { &mut *column_ticks.get() };
+        // Conservatively assume that something in the column gets changed
+        column_ticks.last_changed = newest_tick(column_ticks.last_changed, ticks.change_tick);
+        Some(FetchPtr {
+            data: unsafe { storage.as_mut_ptr() },
+            ticks: column_ticks.ticks.as_mut_ptr(),
+        })
     }
 
-    fn offset_ptr(ptr: &mut *mut u8, elements: usize) {
-        *ptr = unsafe { ((*ptr) as *mut T).add(elements) as *mut u8 };
+    fn offset_ptr(ptr: &mut FetchPtr, elements: usize) {
+        ptr.offset::<T>(elements);
     }
 
-    fn cast_ptr(ptr: *mut u8) -> Self::Returns {
-        unsafe { &mut *(ptr as *mut T) }
+    fn cast_ptr(ptr: FetchPtr, ticks: QueryTicks) -> Self::Returns {
+        unsafe {
+            (*ptr.ticks).changed = ticks.change_tick;
+            &mut *(ptr.data as *mut T)
+        }
     }
 }
 impl<'a, T: Component> QueryParam<'a> for &'static T {
@@ -262,18 +452,321 @@ impl<'a, T: Component> QueryParam<'a> for &'static T {
         Some(FetchType::Immut(id))
     }
 
-    fn create_ptr(archetype: &Archetype, fetch: &FetchType) -> Option<*mut u8> {
+    fn create_ptr(
+        _: &World,
+        archetype: &Archetype,
+        fetch: &FetchType,
+        _: QueryTicks,
+    ) -> Option<FetchPtr> {
         let &storage_idx = archetype.comp_lookup.get(&fetch.get_id().unwrap())?;
         let storage = unsafe { &*archetype.component_storages[storage_idx].1.get() };
-        unsafe { Some(storage.as_immut_ptr() as *mut u8) }
+        Some(FetchPtr {
+            data: unsafe { storage.as_immut_ptr() as *mut u8 },
+            ticks: 0x0 as _,
+        })
+    }
+
+    fn offset_ptr(ptr: &mut FetchPtr, elements: usize) {
+        ptr.offset::<T>(elements);
+    }
+
+    fn cast_ptr(ptr: FetchPtr, _: QueryTicks) -> Self::Returns {
+        unsafe { &*(ptr.data as *mut T) }
+    }
+}
+
+/// Creates the pointer for ``Option<&T>`` and ``Option<&mut T>`` using the pointer of ``Q`` if the archetype has the component
+fn create_optional_ptr<Q: for<'a> QueryParam<'a>>(
+    world: &World,
+    archetype: &Archetype,
+    fetch: &FetchType,
+    ticks: QueryTicks,
+) -> Option<FetchPtr> {
+    match fetch {
+        FetchType::Optional(inner) => {
+            Some(Q::create_ptr(world, archetype, inner, ticks).unwrap_or_else(FetchPtr::null))
+        }
+        // The component was never created so no entity can have it
+        _ => Some(FetchPtr::null()),
+    }
+}
+
+impl<'a, T: Component> QueryParam<'a> for Option<&'static mut T> {
+    type Returns = Option<&'a mut T>;
+
+    fn fetch_type(world: &World) -> Option<FetchType> {
+        Some(match world.type_id_to_ecs_id.get(&TypeId::of::<T>()) {
+            Some(&id) => FetchType::Optional(Box::new(FetchType::Mut(id))),
+            None => FetchType::EcsId,
+        })
+    }
+
+    fn create_ptr(
+        world: &World,
+        archetype: &Archetype,
+        fetch: &FetchType,
+        ticks: QueryTicks,
+    ) -> Option<FetchPtr> {
+        create_optional_ptr::<&mut T>(world, archetype, fetch, ticks)
+    }
+
+    fn offset_ptr(ptr: &mut FetchPtr, elements: usize) {
+        if !ptr.data.is_null() {
+            ptr.offset::<T>(elements);
+        }
+    }
+
+    fn cast_ptr(ptr: FetchPtr, ticks: QueryTicks) -> Self::Returns {
+        match ptr.data.is_null() {
+            true => None,
+            false => Some(<&mut T as QueryParam<'a>>::cast_ptr(ptr, ticks)),
+        }
+    }
+}
+impl<'a, T: Component> QueryParam<'a> for Option<&'static T> {
+    type Returns = Option<&'a T>;
+
+    fn fetch_type(world: &World) -> Option<FetchType> {
+        Some(match world.type_id_to_ecs_id.get(&TypeId::of::<T>()) {
+            Some(&id) => FetchType::Optional(Box::new(FetchType::Immut(id))),
+            None => FetchType::EcsId,
+        })
+    }
+
+    fn create_ptr(
+        world: &World,
+        archetype: &Archetype,
+        fetch: &FetchType,
+        ticks: QueryTicks,
+    ) -> Option<FetchPtr> {
+        create_optional_ptr::<&T>(world, archetype, fetch, ticks)
+    }
+
+    fn offset_ptr(ptr: &mut FetchPtr, elements: usize) {
+        if !ptr.data.is_null() {
+            ptr.offset::<T>(elements);
+        }
+    }
+
+    fn cast_ptr(ptr: FetchPtr, ticks: QueryTicks) -> Self::Returns {
+        match ptr.data.is_null() {
+            true => None,
+            false => Some(<&T as QueryParam<'a>>::cast_ptr(ptr, ticks)),
+        }
+    }
+}
+
+/// Creates the pointer for ``Added<T>`` and ``Changed<T>``, ``column_tick`` gets the newest tick in the column that the filter cares about
+fn create_filtered_ptr(
+    archetype: &Archetype,
+    fetch: &FetchType,
+    ticks: QueryTicks,
+    column_tick: fn(&crate::change_detection::ColumnTicks) -> u32,
+) -> Option<FetchPtr> {
+    let &storage_idx = archetype.comp_lookup.get(&fetch.get_id().unwrap())?;
+    let (_, storage, column_ticks) = &archetype.component_storages[storage_idx];
+    // Only a read lock is held so other queries may be reading the ticks too, they are never written through this pointer
+    let column_ticks = unsafe { &*column_ticks.get() };
+    if !ticks.is_newer(column_tick(column_ticks)) {
+        return None;
+    }
+    let storage = unsafe { &*storage.get() };
+    Some(FetchPtr {
+        data: unsafe { storage.as_immut_ptr() as *mut u8 },
+        ticks: column_ticks.ticks.as_ptr() as *mut ComponentTicks,
+    })
+}
+
+/// Fetches ``&T`` for entities whose ``T`` was added since the query's last run, see ``World::query_since``
+pub struct Added<T: Component>(PhantomData<T>);
+impl<'a, T: Component> QueryParam<'a> for Added<T> {
+    type Returns = &'a T;
+
+    fn fetch_type(world: &World) -> Option<FetchType> {
+        let id = *world.type_id_to_ecs_id.get(&TypeId::of::<T>())?;
+        Some(FetchType::Added(id))
+    }
+
+    fn create_ptr(
+        _: &World,
+        archetype: &Archetype,
+        fetch: &FetchType,
+        ticks: QueryTicks,
+    ) -> Option<FetchPtr> {
+        create_filtered_ptr(archetype, fetch, ticks, |column| column.last_added)
+    }
+
+    fn offset_ptr(ptr: &mut FetchPtr, elements: usize) {
+        ptr.offset::<T>(elements);
+    }
+
+    fn matches_row(ptr: &FetchPtr, ticks: QueryTicks) -> bool {
+        ticks.is_newer(unsafe { (*ptr.ticks).added })
+    }
+
+    fn cast_ptr(ptr: FetchPtr, _: QueryTicks) -> Self::Returns {
+        unsafe { &*(ptr.data as *mut T) }
+    }
+}
+
+/// Fetches ``&T`` for entities whose ``T`` was added or changed since the query's last run, see ``World::query_since``
+pub struct Changed<T: Component>(PhantomData<T>);
+impl<'a, T: Component> QueryParam<'a> for Changed<T> {
+    type Returns = &'a T;
+
+    fn fetch_type(world: &World) -> Option<FetchType> {
+        let id = *world.type_id_to_ecs_id.get(&TypeId::of::<T>())?;
+        Some(FetchType::Changed(id))
+    }
+
+    fn create_ptr(
+        _: &World,
+        archetype: &Archetype,
+        fetch: &FetchType,
+        ticks: QueryTicks,
+    ) -> Option<FetchPtr> {
+        create_filtered_ptr(archetype, fetch, ticks, |column| column.last_changed)
+    }
+
+    fn offset_ptr(ptr: &mut FetchPtr, elements: usize) {
+        ptr.offset::<T>(elements);
+    }
+
+    fn matches_row(ptr: &FetchPtr, ticks: QueryTicks) -> bool {
+        ticks.is_newer(unsafe { (*ptr.ticks).changed })
+    }
+
+    fn cast_ptr(ptr: FetchPtr, _: QueryTicks) -> Self::Returns {
+        unsafe { &*(ptr.data as *mut T) }
+    }
+}
+
+/// Creates the pointer for ``With<T>``, ``Without<T>`` and ``Or<(A, B)>`` which never point to anything
+fn create_filter_ptr(archetype: &Archetype, fetch: &FetchType) -> Option<FetchPtr> {
+    match fetch.filter_archetype(archetype) {
+        true => Some(FetchPtr::null()),
+        false => None,
+    }
+}
+
+/// Filters that only depend on the components an entity has, only these can be used in ``Or``
+pub trait QueryFilter: for<'a> QueryParam<'a, Returns = ()> {}
+
+/// Only matches entities that have a ``T`` component without fetching or locking it
+pub struct With<T: Component>(PhantomData<T>);
+impl<'a, T: Component> QueryParam<'a> for With<T> {
+    type Returns = ();
+
+    fn fetch_type(world: &World) -> Option<FetchType> {
+        let id = *world.type_id_to_ecs_id.get(&TypeId::of::<T>())?;
+        Some(FetchType::With(id))
+    }
+
+    fn create_ptr(
+        _: &World,
+        archetype: &Archetype,
+        fetch: &FetchType,
+        _: QueryTicks,
+    ) -> Option<FetchPtr> {
+        create_filter_ptr(archetype, fetch)
+    }
+
+    fn offset_ptr(_: &mut FetchPtr, _: usize) {}
+
+    fn cast_ptr(_: FetchPtr, _: QueryTicks) -> Self::Returns {}
+}
+impl<T: Component> QueryFilter for With<T> {}
+
+/// Only matches entities that don't have a ``T`` component
+pub struct Without<T: Component>(PhantomData<T>);
+impl<'a, T: Component> QueryParam<'a> for Without<T> {
+    type Returns = ();
+
+    fn fetch_type(world: &World) -> Option<FetchType> {
+        match world.type_id_to_ecs_id.get(&TypeId::of::<T>()) {
+            Some(&id) => Some(FetchType::Without(id)),
+            // Nothing can have a component that doesn't exist so match every entity
+            None => Some(FetchType::Or(vec![FetchType::EcsId])),
+        }
     }
 
-    fn offset_ptr(ptr: &mut *mut u8, elements: usize) {
-        *ptr = unsafe { ((*ptr) as *mut T).add(elements) as *mut u8 };
+    fn create_ptr(
+        _: &World,
+        archetype: &Archetype,
+        fetch: &FetchType,
+        _: QueryTicks,
+    ) -> Option<FetchPtr> {
+        create_filter_ptr(archetype, fetch)
     }
 
-    fn cast_ptr(ptr: *mut u8) -> Self::Returns {
-        unsafe { &*(ptr as *mut T) }
+    fn offset_ptr(_: &mut FetchPtr, _: usize) {}
+
+    fn cast_ptr(_: FetchPtr, _: QueryTicks) -> Self::Returns {}
+}
+impl<T: Component> QueryFilter for Without<T> {}
+
+/// Matches entities that match any of the filters in the tuple
+pub struct Or<T>(PhantomData<T>);
+
+macro_rules! impl_or_filter {
+    ($($T:ident)*) => {
+        impl<'a, $($T: QueryFilter),*> QueryParam<'a> for Or<($($T,)*)> {
+            type Returns = ();
+
+            fn fetch_type(world: &World) -> Option<FetchType> {
+                // Filters on components that don't exist can't match anything so they can be left out
+                let filters = [$($T::fetch_type(world),)*];
+                Some(FetchType::Or(IntoIterator::into_iter(filters).flatten().collect()))
+            }
+
+            fn create_ptr(_: &World, archetype: &Archetype, fetch: &FetchType, _: QueryTicks) -> Option<FetchPtr> {
+                create_filter_ptr(archetype, fetch)
+            }
+
+            fn offset_ptr(_: &mut FetchPtr, _: usize) {}
+
+            fn cast_ptr(_: FetchPtr, _: QueryTicks) -> Self::Returns {}
+        }
+        impl<$($T: QueryFilter),*> QueryFilter for Or<($($T,)*)> {}
+        impl<$($T: QueryFilter),*> SyncQueryParam for Or<($($T,)*)> {}
+    };
+}
+
+impl_or_filter!(A B C D);
+impl_or_filter!(A B C);
+impl_or_filter!(A B);
+
+fn create_resource_ptr<T: Component>(world: &World) -> Option<FetchPtr> {
+    Some(FetchPtr {
+        data: world.resources[&TypeId::of::<T>()].as_ptr()?,
+        ticks: 0x0 as _,
+    })
+}
+
+/// Fetches the ``T`` resource for every entity that the rest of the query matches, the query matches nothing if the resource doesn't exist
+///
+/// There is no mutable version because every row would alias the same ``&mut T``, use ``World::resource_mut`` or a ``ResourceMut`` system param instead
+pub struct Res<T: Component>(PhantomData<T>);
+impl<'a, T: Component> QueryParam<'a> for Res<T> {
+    type Returns = &'a T;
+
+    fn fetch_type(world: &World) -> Option<FetchType> {
+        match world.has_resource::<T>() {
+            true => Some(FetchType::Resource(TypeId::of::<T>())),
+            false => None,
+        }
+    }
+
+    fn create_ptr(world: &World, _: &Archetype, _: &FetchType, _: QueryTicks) -> Option<FetchPtr> {
+        create_resource_ptr::<T>(world)
+    }
+
+    fn offset_ptr(_: &mut FetchPtr, _: usize) {
+        // Every entity gets the same resource
+    }
+
+    fn cast_ptr(ptr: FetchPtr, _: QueryTicks) -> Self::Returns {
+        unsafe { &*(ptr.data as *mut T) }
     }
 }
 
@@ -285,15 +778,57 @@ impl<'a> QueryParam<'a> for EcsIds {
         Some(FetchType::EcsId)
     }
 
-    fn create_ptr(archetype: &Archetype, _: &FetchType) -> Option<*mut u8> {
-        Some(archetype.entities.as_ptr() as *mut EcsId as *mut u8)
+    fn create_ptr(
+        _: &World,
+        archetype: &Archetype,
+        _: &FetchType,
+        _: QueryTicks,
+    ) -> Option<FetchPtr> {
+        Some(FetchPtr {
+            data: archetype.entities.as_ptr() as *mut EcsId as *mut u8,
+            ticks: 0x0 as _,
+        })
+    }
+
+    fn offset_ptr(ptr: &mut FetchPtr, elements: usize) {
+        ptr.offset::<EcsId>(elements);
+    }
+
+    fn cast_ptr(ptr: FetchPtr, _: QueryTicks) -> Self::Returns {
+        unsafe { *(ptr.data as *mut EcsId) }
+    }
+}
+
+/// Matches entities with any ``(R, target)`` pair and returns the pair, use ``World::pair_parts`` to get the target from it
+pub struct Relation<R: Component>(PhantomData<R>);
+impl<'a, R: Component> QueryParam<'a> for Relation<R> {
+    type Returns = EcsId;
+
+    fn fetch_type(world: &World) -> Option<FetchType> {
+        let relation = *world.type_id_to_ecs_id.get(&TypeId::of::<R>())?;
+        let wildcard = EcsId::pair(relation, EcsId::WILDCARD);
+        world.archetype_bitset.get_bitvec(wildcard)?;
+        Some(FetchType::MatchedPair(wildcard))
+    }
+
+    fn create_ptr(
+        _: &World,
+        archetype: &Archetype,
+        fetch: &FetchType,
+        _: QueryTicks,
+    ) -> Option<FetchPtr> {
+        let storage_idx = archetype.storage_index(fetch.get_id().unwrap())?;
+        Some(FetchPtr {
+            data: &archetype.comp_ids[storage_idx] as *const EcsId as *mut u8,
+            ticks: 0x0 as _,
+        })
     }
 
-    fn offset_ptr(ptr: &mut *mut u8, elements: usize) {
-        *ptr = unsafe { ((*ptr) as *mut EcsId).add(elements) as *mut u8 };
+    fn offset_ptr(_: &mut FetchPtr, _: usize) {
+        // Every entity in an archetype has the same pair
     }
 
-    fn cast_ptr(ptr: *mut u8) -> Self::Returns {
-        unsafe { *(ptr as *mut EcsId) }
+    fn cast_ptr(ptr: FetchPtr, _: QueryTicks) -> Self::Returns {
+        unsafe { *(ptr.data as *mut EcsId) }
     }
 }
@@ -177,3 +177,22 @@ fn returns_none_continuously() {
     assert!(matches!(bitset_iter.next(), None));
     assert!(matches!(bitset_iter.next(), None));
 }
+
+#[test]
+fn short_bitset() {
+    let invert_map: fn(usize) -> _ = |x| !x;
+    let map: fn(_) -> _ = |x| x;
+
+    let data1 = [0b1, 0b1];
+    let data2 = [0b1];
+
+    // Bitvecs that run out before bit_length are treated as zeros
+    let mut bitset_iter = BitsetIterator::new(
+        [(data1.iter(), map), (data2.iter(), invert_map)],
+        usize::BITS * 2,
+    );
+
+    assert_eq!(bitset_iter.next(), Some(64));
+    assert_eq!(bitset_iter.next(), None);
+    assert_eq!(bitset_iter.next(), None);
+}
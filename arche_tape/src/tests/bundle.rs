@@ -0,0 +1,153 @@
+use crate::{EcsIds, World};
+use std::sync::Arc;
+
+#[test]
+fn add_bundle() {
+    let mut world = World::new();
+    let e1 = spawn!(&mut world, 1_u32);
+    let archetypes = world.archetypes.len();
+
+    world.add_bundle(e1, (2_u64, 3_u8, 4_u16));
+    // Only the archetype with every component gets created, not the ones in between
+    assert!(world.archetypes.len() == archetypes + 1);
+
+    let mut query = world.query::<(&u32, &u64, &u8, &u16)>();
+    let (&a, &b, &c, &d) = query.get(e1).unwrap();
+    assert!((a, b, c, d) == (1, 2, 3, 4));
+}
+
+#[test]
+fn add_bundle_replaces() {
+    let mut world = World::new();
+    let arc = Arc::new(());
+    let e1 = spawn!(&mut world, 1_u32, arc.clone());
+    let e2 = spawn!(&mut world, 2_u32, arc.clone());
+
+    world.add_bundle(e1, (10_u32, 1_u64));
+    world.add_bundle(e2, (20_u32, arc.clone()));
+    assert!(Arc::strong_count(&arc) == 3);
+
+    let mut query = world.query::<(EcsIds, &u32)>();
+    let mut entities = query.iter().map(|(e, &n)| (e, n)).collect::<Vec<_>>();
+    entities.sort();
+    assert!(entities == [(e1, 10), (e2, 20)]);
+    drop(query);
+    assert!(world.has_component::<u64>(e1));
+    assert!(!world.has_component::<u64>(e2));
+}
+
+#[test]
+fn remove_bundle() {
+    let mut world = World::new();
+    let arc = Arc::new(());
+    let e1 = spawn!(&mut world, 1_u32, 2_u64, arc.clone());
+    let e2 = spawn!(&mut world, 3_u32, 4_u64, arc.clone());
+
+    world.remove_bundle::<(u64, Arc<()>, u8)>(e1);
+    assert!(Arc::strong_count(&arc) == 2);
+    assert!(world.has_component::<u32>(e1));
+    assert!(!world.has_component::<u64>(e1));
+
+    // The other entity in the archetype gets swapped into ``e1``'s place
+    let mut query = world.query::<(&u32, &u64)>();
+    assert!(query.get(e2).map(|(&a, &b)| (a, b)) == Some((3, 4)));
+    drop(query);
+
+    world.remove_bundle::<(u8, u16)>(e2);
+    assert!(world.has_component::<u64>(e2));
+}
+
+#[test]
+fn with_bundle() {
+    let mut world = World::new();
+    let e1 = world.spawn().with(1_u32).with_bundle((2_u64, 3_u8)).build();
+
+    let mut query = world.query::<(&u32, &u64, &u8)>();
+    let (&a, &b, &c) = query.get(e1).unwrap();
+    assert!((a, b, c) == (1, 2, 3));
+}
+
+#[test]
+#[should_panic(expected = "Attempted to add the same component twice in a bundle")]
+fn duplicate_component() {
+    let mut world = World::new();
+    let e1 = spawn!(&mut world, 1_u32);
+    world.add_bundle(e1, (1_u64, 2_u64));
+}
+
+#[test]
+fn duplicate_component_drops_bundle() {
+    let mut world = World::new();
+    let arc = Arc::new(());
+    let e1 = spawn!(&mut world, 1_u32);
+    let archetype = world.get_entity_meta(e1).unwrap().instance_meta.archetype.0;
+
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        world.add_bundle(e1, (arc.clone(), 1_u64, 2_u64));
+    }));
+    assert!(result.is_err());
+    // The bundle is dropped whole and the entity is left untouched
+    assert!(Arc::strong_count(&arc) == 1);
+    assert!(world.get_entity_meta(e1).unwrap().instance_meta.archetype.0 == archetype);
+}
+
+#[test]
+fn spawn_batch() {
+    let mut world = World::new();
+    let arc = Arc::new(());
+    let e1 = spawn!(&mut world, 1_u32, 1_u64);
+    let archetypes = world.archetypes.len();
+
+    let spawned = world
+        .spawn_batch((2..5).map(|n| (n as u64, n as u32)))
+        .collect::<Vec<_>>();
+    // The entities go into the same archetype as e1 even though the bundle's components are in a different order
+    assert!(world.archetypes.len() == archetypes);
+
+    let mut query = world.query::<(EcsIds, &u32, &u64)>();
+    let entities = query
+        .iter()
+        .map(|(e, &a, &b)| (e, a, b))
+        .collect::<Vec<_>>();
+    assert!(entities[0] == (e1, 1, 1));
+    assert!(entities[1..] == [(spawned[0], 2, 2), (spawned[1], 3, 3), (spawned[2], 4, 4)]);
+    drop(query);
+
+    // The size hint of a filter is too small to reserve anything
+    let spawned = world
+        .spawn_batch((0..4).filter(|n| n % 2 == 0).map(|_| (arc.clone(),)))
+        .collect::<Vec<_>>();
+    assert!(spawned.len() == 2 && Arc::strong_count(&arc) == 3);
+    world.despawn(spawned[0]);
+    assert!(Arc::strong_count(&arc) == 2);
+    assert!(world.has_component::<Arc<()>>(spawned[1]));
+}
+
+#[test]
+fn spawn_batch_reuses_ids() {
+    let mut world = World::new();
+    let e1 = spawn!(&mut world, 1_u32);
+    let e2 = spawn!(&mut world, 2_u32);
+    world.despawn(e1);
+    world.despawn(e2);
+
+    let spawned = world
+        .spawn_batch(vec![(3_u32,), (4_u32,), (5_u32,)])
+        .collect::<Vec<_>>();
+    assert!(spawned.iter().all(|&e| world.is_alive(e)));
+    let mut query = world.query::<(&u32,)>();
+    assert!(
+        spawned
+            .iter()
+            .map(|&e| *query.get(e).unwrap().0)
+            .collect::<Vec<_>>()
+            == [3, 4, 5]
+    );
+}
+
+#[test]
+#[should_panic(expected = "Attempted to add the same component twice in a bundle")]
+fn spawn_batch_duplicate_component() {
+    let mut world = World::new();
+    let _ = world.spawn_batch(vec![(1_u32, 2_u32)]);
+}
@@ -0,0 +1,179 @@
+use crate::change_detection::{CHECK_TICK_THRESHOLD, MAX_CHANGE_AGE};
+use crate::{Added, Changed, EcsId, EcsIds, FetchType, World};
+use std::sync::atomic::Ordering;
+
+#[test]
+fn added() {
+    let mut world = World::new();
+    let e1 = spawn!(&mut world, 10_u32);
+
+    let mut query = world.query_since::<(EcsIds, Added<u32>)>(0);
+    assert!(query.iter().map(|(e, _)| e).collect::<Vec<_>>() == [e1]);
+    let last_run = query.change_tick();
+    drop(query);
+
+    let mut query = world.query_since::<(EcsIds, Added<u32>)>(last_run);
+    assert!(query.iter().count() == 0);
+    drop(query);
+
+    let e2 = spawn!(&mut world, 12_u32, 1_u64);
+    let e3 = spawn!(&mut world, 1_u64);
+    world.add_component(e3, 14_u32);
+
+    let mut query = world.query_since::<(EcsIds, Added<u32>)>(last_run);
+    assert!(query.iter().map(|(e, _)| e).collect::<Vec<_>>() == [e2, e3]);
+    assert!(query.get(e1).is_none());
+    assert!(*query.get(e3).unwrap().1 == 14);
+}
+
+#[test]
+fn changed() {
+    let mut world = World::new();
+    let e1 = spawn!(&mut world, 10_u32);
+    let e2 = spawn!(&mut world, 12_u32);
+    let e3 = spawn!(&mut world, 14_u32, 1_u64);
+
+    let last_run = world.query::<(&u32,)>().change_tick();
+
+    // Only the rows that are fetched mutably are marked changed
+    let mut query = world.query::<(&mut u32,)>();
+    *query.get(e2).unwrap().0 += 1;
+    *query.get(e3).unwrap().0 += 1;
+    drop(query);
+    assert!(world.is_alive(e1));
+
+    let mut query = world.query_since::<(EcsIds, Changed<u32>)>(last_run);
+    let changed = query.iter().map(|(e, &num)| (e, num)).collect::<Vec<_>>();
+    assert!(changed == [(e2, 13), (e3, 15)]);
+    let last_run = query.change_tick();
+    drop(query);
+
+    // Components being moved between archetypes keep their ticks
+    world.remove_component::<u64>(e3);
+    let mut query = world.query_since::<(Changed<u32>,)>(last_run);
+    assert!(query.iter().count() == 0);
+    drop(query);
+
+    // Adding a component counts as changing it
+    let e4 = spawn!(&mut world, 16_u32);
+    let mut query = world.query_since::<(EcsIds, Changed<u32>)>(last_run);
+    assert!(query.iter().map(|(e, _)| e).collect::<Vec<_>>() == [e4]);
+}
+
+#[test]
+fn get_mut_marks_changed() {
+    let mut world = World::new();
+    let e1 = spawn!(&mut world, 10_u32);
+    let e2 = spawn!(&mut world, 12_u32);
+    let u32_id = world.get_or_create_type_id_ecsid::<u32>();
+
+    let last_run = world.query::<(&u32,)>().change_tick();
+    world.query::<(&mut u32,)>().get(e2).unwrap();
+    unsafe { *(world.get_component_mut_dynamic(e1, u32_id).unwrap() as *mut u32) = 11 };
+
+    let mut query = world.query_since::<(EcsIds, Changed<u32>)>(last_run);
+    assert!(query.iter().map(|(e, _)| e).collect::<Vec<_>>() == [e1, e2]);
+}
+
+#[test]
+fn dynamic() {
+    let mut world = World::new();
+    let e1 = spawn!(&mut world, 10_u32);
+    let e2 = spawn!(&mut world, 12_u32, 1_u64);
+    let u32_id = world.get_or_create_type_id_ecsid::<u32>();
+    let u64_id = world.get_or_create_type_id_ecsid::<u64>();
+
+    let last_run = world
+        .query_dynamic([FetchType::Immut(u32_id)])
+        .change_tick();
+    let mut query = world.query_dynamic([
+        FetchType::EcsId,
+        FetchType::Mut(u32_id),
+        FetchType::Immut(u64_id),
+    ]);
+    for [_, num, _] in query.iter() {
+        unsafe { *(num as *mut u32) += 1 };
+    }
+    drop(query);
+
+    let mut query =
+        world.query_dynamic_since([FetchType::EcsId, FetchType::Changed(u32_id)], last_run);
+    let changed = query
+        .iter()
+        .map(|[entity, _]| unsafe { *(entity as *mut EcsId) })
+        .collect::<Vec<_>>();
+    assert!(changed == [e2]);
+    let last_run = query.change_tick();
+    drop(query);
+
+    let e3 = spawn!(&mut world, 14_u32);
+    let mut query =
+        world.query_dynamic_since([FetchType::EcsId, FetchType::Added(u32_id)], last_run);
+    let added = query
+        .iter()
+        .map(|[entity, _]| unsafe { *(entity as *mut EcsId) })
+        .collect::<Vec<_>>();
+    assert!(added == [e3]);
+    drop(query);
+
+    // Column iteration marks every row in a mutably fetched column changed
+    let last_run = world
+        .query_dynamic([FetchType::Immut(u32_id)])
+        .change_tick();
+    world
+        .query_dynamic([FetchType::Mut(u32_id)])
+        .column_iter()
+        .count();
+    let mut query =
+        world.query_dynamic_since([FetchType::EcsId, FetchType::Changed(u32_id)], last_run);
+    assert!(query.iter().count() == 3);
+    assert!(world.is_alive(e1));
+}
+
+#[test]
+fn change_tick_wraps_around() {
+    let mut world = World::new();
+    world.change_tick.store(u32::MAX - 1, Ordering::Relaxed);
+    let e1 = spawn!(&mut world, 10_u32);
+
+    let mut query = world.query_since::<(EcsIds, Added<u32>)>(u32::MAX - 2);
+    assert!(query.iter().map(|(e, _)| e).collect::<Vec<_>>() == [e1]);
+    let last_run = query.change_tick();
+    drop(query);
+
+    // The tick is now past u32::MAX so the new component has a smaller tick than ``last_run``
+    let e2 = spawn!(&mut world, 12_u32);
+    world.query::<(&mut u32,)>().get(e1);
+    assert!(world.change_tick() < last_run);
+
+    let mut query = world.query_since::<(EcsIds, Added<u32>)>(last_run);
+    assert!(query.iter().map(|(e, _)| e).collect::<Vec<_>>() == [e2]);
+    drop(query);
+    let mut query = world.query_since::<(EcsIds, Changed<u32>)>(last_run);
+    let mut changed = query.iter().map(|(e, _)| e).collect::<Vec<_>>();
+    changed.sort();
+    assert!(changed == [e1, e2]);
+}
+
+#[test]
+fn old_ticks_are_clamped() {
+    let mut world = World::new();
+    let e1 = spawn!(&mut world, 10_u32);
+    assert!(world.check_change_ticks() == false);
+
+    // Without clamping the component's tick would look new again once the change tick has wrapped around
+    let start = world.change_tick();
+    world.change_tick.store(
+        start.wrapping_add(MAX_CHANGE_AGE + CHECK_TICK_THRESHOLD),
+        Ordering::Relaxed,
+    );
+    assert!(world.check_change_ticks());
+    let meta = world.get_entity_meta(e1).unwrap().instance_meta.clone();
+    let (_, _, ticks) = &mut world.archetypes[meta.archetype.0].component_storages[0];
+    let ticks = ticks.get_mut().ticks[meta.index];
+    assert!(world.change_tick().wrapping_sub(ticks.added) == MAX_CHANGE_AGE);
+
+    let last_run = world.change_tick().wrapping_sub(10);
+    assert!(world.query_since::<(Added<u32>,)>(last_run).iter().count() == 0);
+    assert!(world.query_since::<(Added<u32>,)>(0).iter().count() == 1);
+}
@@ -0,0 +1,159 @@
+use crate::{Commands, EcsIds, World};
+use std::sync::Arc;
+
+#[test]
+fn spawn_during_query() {
+    let mut world = World::new();
+    spawn!(&mut world, 1_u32);
+    spawn!(&mut world, 2_u32);
+
+    let mut commands = Commands::new();
+    let mut query = world.query::<(&u32,)>();
+    let spawned = query
+        .iter()
+        .map(|(&n,)| commands.spawn(&world).with(n * 10).with(n as u64).build())
+        .collect::<Vec<_>>();
+    drop(query);
+
+    // Reserved ids aren't alive until the commands are applied
+    assert!(spawned.iter().all(|&e| !world.is_alive(e)));
+    world.apply_commands(&mut commands);
+    assert!(commands.is_empty());
+
+    let mut query = world.query::<(EcsIds, &u32, &u64)>();
+    let entities = query
+        .iter()
+        .map(|(e, &a, &b)| (e, a, b))
+        .collect::<Vec<_>>();
+    assert!(entities == [(spawned[0], 10, 1), (spawned[1], 20, 2)]);
+}
+
+#[test]
+fn despawn_and_add_remove() {
+    let mut world = World::new();
+    let e1 = spawn!(&mut world, 1_u32, 1_u64);
+    let e2 = spawn!(&mut world, 2_u32);
+    let e3 = spawn!(&mut world, 3_u32);
+
+    let mut commands = Commands::new();
+    commands.despawn(e1);
+    commands.add_component(e2, 20_u64);
+    commands.remove_component::<u32>(e2);
+    commands.add_component(e3, 30_u8);
+    // Adding a component the entity already has replaces it
+    commands.add_component(e3, 4_u32);
+    world.apply_commands(&mut commands);
+
+    assert!(!world.is_alive(e1));
+    assert!(!world.has_component::<u32>(e2));
+    assert!(*world.query::<(&u64,)>().get(e2).unwrap().0 == 20);
+    let mut query = world.query::<(&u32, &u8)>();
+    let (&a, &b) = query.get(e3).unwrap();
+    assert!((a, b) == (4, 30));
+}
+
+#[test]
+fn batched_moves() {
+    let mut world = World::new();
+    let e1 = spawn!(&mut world, 1_u32);
+    let archetypes = world.archetypes.len();
+
+    let mut commands = Commands::new();
+    commands.add_component(e1, 1_u64);
+    commands.add_component(e1, 1_u8);
+    commands.remove_component::<u32>(e1);
+    world.apply_commands(&mut commands);
+
+    // Only the archetype with (u64, u8) gets created, not the ones in between
+    assert!(world.archetypes.len() == archetypes + 1);
+    assert!(!world.has_component::<u32>(e1));
+    let mut query = world.query::<(&u64, &u8)>();
+    assert!(query.get(e1).is_some());
+}
+
+#[test]
+fn spawn_then_despawn() {
+    let mut world = World::new();
+    let mut commands = Commands::new();
+    let e1 = commands.spawn(&world).with(1_u32).build();
+    commands.despawn(e1);
+    let e2 = commands.spawn(&world).build();
+    world.apply_commands(&mut commands);
+
+    assert!(!world.is_alive(e1));
+    assert!(world.is_alive(e2));
+    assert!(world.query::<(&u32,)>().iter().count() == 0);
+}
+
+#[test]
+fn unapplied_commands_are_dropped() {
+    let mut world = World::new();
+    let arc = Arc::new(());
+    let e1 = spawn!(&mut world, 1_u32);
+
+    let mut commands = Commands::new();
+    let reserved = commands.spawn(&world).with(arc.clone()).build();
+    commands.add_component(e1, arc.clone());
+    assert!(Arc::strong_count(&arc) == 3);
+    drop(commands);
+    assert!(Arc::strong_count(&arc) == 1);
+
+    // Reserved entities get spawned without components even if their commands are never applied
+    let e2 = world.spawn().build();
+    assert!(world.is_alive(reserved));
+    assert!(e2 != reserved);
+}
+
+#[test]
+fn dead_entities_are_skipped() {
+    let mut world = World::new();
+    let arc = Arc::new(());
+    let e1 = spawn!(&mut world, 1_u32);
+    world.despawn(e1);
+
+    let mut commands = Commands::new();
+    commands.add_component(e1, arc.clone());
+    commands.remove_component::<u32>(e1);
+    commands.despawn(e1);
+    world.apply_commands(&mut commands);
+    assert!(Arc::strong_count(&arc) == 1);
+}
+
+#[test]
+fn mismatched_layouts_are_skipped() {
+    let mut world = World::new();
+    let tag = world.spawn().build();
+    let u32_id = world.get_or_create_type_id_ecsid::<u32>();
+
+    let mut commands = Commands::new();
+    let e1 = commands
+        .spawn(&world)
+        .with_dynamic(u32_id)
+        .with_dynamic(tag)
+        .build();
+    world.apply_commands(&mut commands);
+
+    assert!(world.has_component_dynamic(e1, tag));
+    assert!(!world.has_component::<u32>(e1));
+    assert!(world.despawn(e1));
+}
+
+#[test]
+fn multiple_buffers() {
+    let mut world = World::new();
+    let mut commands_1 = Commands::new();
+    let mut commands_2 = Commands::new();
+    let e1 = commands_1.spawn(&world).with(1_u32).build();
+    let e2 = commands_2.spawn(&world).with(2_u32).build();
+    assert!(e1 != e2);
+
+    world.apply_commands(&mut commands_2);
+    // e1 was reserved so it gets spawned when e2's commands get applied
+    assert!(world.is_alive(e1));
+    world.apply_commands(&mut commands_1);
+
+    let mut query = world.query::<(EcsIds, &u32)>();
+    let entities = query.iter().map(|(e, &n)| (e, n)).collect::<Vec<_>>();
+    assert!(entities.len() == 2);
+    assert!(entities.contains(&(e1, 1)) && entities.contains(&(e2, 2)));
+}
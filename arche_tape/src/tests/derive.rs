@@ -0,0 +1,174 @@
+use crate::{Bundle, Commands, Component, ComponentRegistry, EcsId, World};
+
+#[derive(Component)]
+struct Position(f32, f32);
+#[derive(Component)]
+struct Velocity(f32, f32);
+
+#[derive(Bundle)]
+struct Movement {
+    position: Position,
+    velocity: Velocity,
+}
+
+#[derive(Bundle)]
+struct Pair(u32, u64);
+
+/// Every hook that has run, resources can be reached from hooks through the world
+#[derive(Component, Default)]
+struct HookLog(Vec<(&'static str, EcsId)>);
+
+fn log_add(world: &mut World, entity: EcsId) {
+    world
+        .resource_mut::<HookLog>()
+        .unwrap()
+        .0
+        .push(("add", entity));
+}
+
+fn log_remove(world: &mut World, entity: EcsId) {
+    world
+        .resource_mut::<HookLog>()
+        .unwrap()
+        .0
+        .push(("remove", entity));
+}
+
+#[derive(Component, Clone)]
+#[component(on_add = log_add, on_remove = log_remove)]
+struct Hooked;
+
+fn take_log(world: &mut World) -> Vec<(&'static str, EcsId)> {
+    std::mem::take(&mut world.resource_mut::<HookLog>().unwrap().0)
+}
+
+#[test]
+fn derive_bundle() {
+    let mut world = World::new();
+    let e1 = world
+        .spawn()
+        .with_bundle(Movement {
+            position: Position(1.0, 2.0),
+            velocity: Velocity(3.0, 4.0),
+        })
+        .build();
+    world.add_bundle(e1, Pair(5, 6));
+
+    let mut query = world.query::<(&Position, &Velocity, &u32, &u64)>();
+    let (position, velocity, &a, &b) = query.get(e1).unwrap();
+    assert!((position.0, position.1, velocity.0, velocity.1) == (1.0, 2.0, 3.0, 4.0));
+    assert!((a, b) == (5, 6));
+    drop(query);
+
+    world.remove_bundle::<Movement>(e1);
+    assert!(!world.has_component::<Position>(e1));
+    assert!(!world.has_component::<Velocity>(e1));
+    assert!(world.has_component::<u32>(e1));
+}
+
+#[test]
+fn hooks() {
+    let mut world = World::new();
+    world.insert_resource(HookLog::default());
+
+    let e1 = world.spawn().with(Hooked).build();
+    let e2 = world.spawn().with(1_u32).build();
+    world.add_component(e2, Hooked);
+    assert!(take_log(&mut world) == [("add", e1), ("add", e2)]);
+
+    world.remove_component::<Hooked>(e1);
+    world.despawn(e2);
+    assert!(take_log(&mut world) == [("remove", e1), ("remove", e2)]);
+
+    let mut commands = Commands::new();
+    let e3 = commands.spawn(&world).with(Hooked).build();
+    commands.remove_component::<Hooked>(e1);
+    world.apply_commands(&mut commands);
+    world.add_bundle(e3, (Hooked, 1_u64));
+    world.remove_bundle::<(Hooked,)>(e3);
+    assert!(take_log(&mut world) == [("add", e3), ("add", e3), ("remove", e3)]);
+}
+
+#[test]
+fn insert_replace_hooks() {
+    let mut world = World::new();
+    world.insert_resource(HookLog::default());
+
+    let e1 = world.spawn().with(1_u32).build();
+    world.insert_component(e1, Hooked);
+    // Replacing the component runs its on_add hook again without running on_remove
+    world.insert_component(e1, Hooked);
+    world.entity_mut(e1).unwrap().insert(Hooked);
+    assert!(take_log(&mut world) == [("add", e1), ("add", e1), ("add", e1)]);
+}
+
+#[test]
+fn spawn_batch_hooks() {
+    let mut world = World::new();
+    world.insert_resource(HookLog::default());
+
+    let spawned = world
+        .spawn_batch((0..2).map(|n| (Hooked, n as u32)))
+        .collect::<Vec<_>>();
+    assert!(take_log(&mut world) == [("add", spawned[0]), ("add", spawned[1])]);
+}
+
+#[test]
+fn query_bulk_hooks() {
+    let mut world = World::new();
+    world.insert_resource(HookLog::default());
+    let e1 = world.spawn().with(1_u32).build();
+    let e2 = world.spawn().with(2_u32).with(Hooked).build();
+    take_log(&mut world);
+
+    // Entities that already have the component get their hook run for the replacement
+    world.add_component_to_query::<(&u32,), _>(Hooked);
+    let mut log = take_log(&mut world);
+    log.sort();
+    let mut expected = vec![("add", e1), ("add", e2)];
+    expected.sort();
+    assert!(log == expected);
+
+    world.remove_component_from_query::<(&u32,), Hooked>();
+    let mut log = take_log(&mut world);
+    log.sort();
+    let mut expected = vec![("remove", e1), ("remove", e2)];
+    expected.sort();
+    assert!(log == expected);
+}
+
+#[test]
+fn despawn_component_hooks() {
+    let mut world = World::new();
+    world.insert_resource(HookLog::default());
+    let e1 = world.spawn().with(Hooked).build();
+    let e2 = world.spawn().with(1_u32).with(Hooked).build();
+    take_log(&mut world);
+
+    // Despawning the component's entity removes it from everything that had it
+    let hooked_id = world.get_or_create_type_id_ecsid::<Hooked>();
+    world.despawn(hooked_id);
+    let mut log = take_log(&mut world);
+    log.sort();
+    let mut expected = vec![("remove", e1), ("remove", e2)];
+    expected.sort();
+    assert!(log == expected);
+}
+
+#[derive(Component, Copy, Clone)]
+#[component(name = "Health", raw)]
+struct Health(u32);
+
+#[test]
+fn register_component() {
+    let mut registry = ComponentRegistry::new();
+    registry.register_component::<Health>();
+    assert!(registry.get_by_name("Health").is_some());
+    assert!(Health::name() == "Health");
+
+    let mut world = World::new();
+    let e1 = world.spawn().with(Health(10)).build();
+    let data = world.save_snapshot(&registry).unwrap();
+    let loaded = World::load_snapshot(&data, &registry).unwrap();
+    assert!(loaded.query::<(&Health,)>().get(e1).unwrap().0 .0 == 10);
+}
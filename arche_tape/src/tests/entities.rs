@@ -1,4 +1,4 @@
-use crate::{entities::*, spawn, EcsId, World};
+use crate::{entities::*, spawn, Component, EcsError, EcsId, World};
 
 #[test]
 pub fn spawn_one() {
@@ -47,11 +47,12 @@ pub fn spawn_one_despawn_one() {
 }
 
 #[test]
-#[should_panic(expected = "could not get generation for Generation 0xFFFFFFFF, Index 0xFFFFFFFF")]
 pub fn despawn_invalid() {
-    let entities = Entities::new();
+    let mut entities = Entities::new();
     let invalid_id = EcsId::new(u32::MAX, u32::MAX);
-    let _ = entities.is_alive(invalid_id);
+    assert!(!entities.is_alive(invalid_id));
+    assert!(!entities.is_alive(EcsId::new(10, 0)));
+    assert!(!entities.despawn(EcsId::new(10, 0)));
 }
 
 #[test]
@@ -151,8 +152,207 @@ pub fn generation_wraps() {
     assert!(entities.despawned.len() == 0);
 }
 
+#[test]
+pub fn generation_retires() {
+    let mut entities = Entities::new();
+    entities.generations.push((true, MAX_GENERATION));
+
+    let stale = EcsId::new(0, MAX_GENERATION);
+    assert!(entities.despawn(stale));
+    assert!(entities.despawned.is_empty());
+    assert!(entities.counters().retired_indices == 1);
+
+    // The retired index is skipped so the stale id can never be alive again
+    assert!(entities.spawn() == EcsId::new(1, 0));
+    assert!(!entities.is_alive(stale));
+    assert!(!entities.is_alive(EcsId::new(0, 0)));
+}
+
+#[test]
+pub fn generation_wrap_policy() {
+    let mut entities = Entities::new();
+    entities.set_generation_policy(GenerationPolicy::Wrap);
+    entities.generations.push((true, MAX_GENERATION));
+
+    assert!(entities.despawn(EcsId::new(0, MAX_GENERATION)));
+    assert!(entities.spawn() == EcsId::new(0, 0));
+    assert!(entities.counters().retired_indices == 0);
+}
+
+#[test]
+pub fn try_spawn_out_of_indices() {
+    let mut entities = Entities::new();
+    entities.set_max_indices(2);
+    let e1 = entities.try_spawn().unwrap();
+    entities.try_spawn().unwrap();
+    assert!(entities.try_spawn() == Err(SpawnError::OutOfIndices));
+
+    // Despawned indices can still be reused once the limit is reached
+    entities.despawn(e1);
+    assert!(entities.try_spawn() == Ok(EcsId::new(0, 1)));
+    assert!(entities.try_spawn() == Err(SpawnError::OutOfIndices));
+}
+
+#[test]
+#[should_panic(expected = "ran out of entity indices")]
+pub fn spawn_out_of_indices() {
+    let mut world = World::new();
+    world.set_max_entity_indices(1);
+    world.try_spawn().unwrap().build();
+    assert!(matches!(
+        world.try_spawn().err(),
+        Some(EcsError::Spawn(SpawnError::OutOfIndices))
+    ));
+    world.spawn().build();
+}
+
+#[test]
+pub fn counters() {
+    let mut entities = Entities::new();
+    entities.set_max_indices(10);
+    let e1 = entities.spawn();
+    let e2 = entities.spawn();
+    entities.despawn(e1);
+    entities.despawn(e2);
+    entities.spawn();
+    entities.reserve();
+    entities.reserve();
+
+    let counters = entities.counters();
+    assert!(counters.alive == 1);
+    assert!(counters.used_indices == 3);
+    assert!(counters.remaining_indices == 7);
+    assert!(counters.retired_indices == 0);
+    assert!(counters.highest_generation == 1);
+}
+
+#[test]
+pub fn reserve_reuses_despawned() {
+    let mut entities = Entities::new();
+    let e1 = entities.spawn();
+    let e2 = entities.spawn();
+    entities.despawn(e1);
+    entities.despawn(e2);
+
+    // Despawned indices are reserved in the same order that ``spawn`` would reuse them
+    let reserved = [entities.reserve(), entities.reserve(), entities.reserve()];
+    assert!(reserved == [EcsId::new(1, 1), EcsId::new(0, 1), EcsId::new(2, 0)]);
+    assert!(reserved.iter().all(|&e| !entities.is_alive(e)));
+    assert!(entities.generations.len() == 2);
+
+    assert!(entities.flush_reserved() == reserved);
+    assert!(reserved.iter().all(|&e| entities.is_alive(e)));
+    assert!(entities.despawned.is_empty());
+    assert!(entities.generations.len() == 3);
+    assert!(entities.spawn() == EcsId::new(3, 0));
+}
+
+#[test]
+pub fn reserve_from_threads() {
+    let mut entities = Entities::new();
+    let spawned = (0..50).map(|_| entities.spawn()).collect::<Vec<_>>();
+    for entity in spawned {
+        entities.despawn(entity);
+    }
+
+    let entities_ref = &entities;
+    let mut reserved = std::thread::scope(|scope| {
+        let handles = (0..4)
+            .map(|_| {
+                scope.spawn(move || (0..100).map(|_| entities_ref.reserve()).collect::<Vec<_>>())
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+
+    reserved.sort();
+    reserved.dedup();
+    assert!(reserved.len() == 400);
+    entities.flush_reserved();
+    assert!(reserved.iter().all(|&e| entities.is_alive(e)));
+    assert!(entities.generations.len() == 400);
+}
+
+#[test]
+pub fn try_reserve_out_of_indices() {
+    let mut entities = Entities::new();
+    entities.set_max_indices(2);
+    let e1 = entities.spawn();
+    assert!(entities.try_reserve() == Ok(EcsId::new(1, 0)));
+    assert!(entities.try_reserve() == Err(SpawnError::OutOfIndices));
+    // Failing to reserve doesn't use up an index
+    assert!(entities.try_reserve() == Err(SpawnError::OutOfIndices));
+    assert!(entities.counters().used_indices == 2);
+
+    entities.flush_reserved();
+    entities.despawn(e1);
+    assert!(entities.try_reserve() == Ok(EcsId::new(0, 1)));
+    assert!(entities.try_reserve() == Err(SpawnError::OutOfIndices));
+}
+
+#[test]
+pub fn world_reserve_entity() {
+    let mut world = World::new();
+    let e1 = spawn!(&mut world, 1_u32);
+    world.despawn(e1);
+
+    let reserved = [world.reserve_entity(), world.reserve_entity()];
+    assert!(reserved.iter().all(|&e| !world.is_alive(e)));
+    world.flush();
+    assert!(reserved.iter().all(|&e| world.is_alive(e)));
+    assert!(reserved.iter().all(|&e| !world.has_component::<u32>(e)));
+
+    // Spawning flushes reserved entities first so ids are never handed out twice
+    let reserved = world.reserve_entity();
+    let spawned = spawn!(&mut world, 2_u32);
+    assert!(world.is_alive(reserved));
+    assert!(reserved != spawned);
+}
+
+#[test]
+pub fn despawn_while_reserved() {
+    let mut world = World::new();
+    let e1 = spawn!(&mut world, 1_u32);
+    let e2 = spawn!(&mut world, 2_u32);
+
+    let reserved = world.reserve_entity();
+    assert!(world.despawn(e1));
+    world.flush();
+    assert!(world.is_alive(reserved) && !world.is_alive(e1));
+
+    // The reserved id reuses a despawned index which must not be handed out again by a later despawn
+    let reserved = world.reserve_entity();
+    assert!(reserved.uindex() == e1.uindex());
+    assert!(world.despawn(e2));
+    world.flush();
+    assert!(world.is_alive(reserved) && !world.is_alive(e2));
+    let spawned = world.spawn().build();
+    assert!(spawned.uindex() == e2.uindex() && spawned != reserved);
+
+    let reserved = world.reserve_entity();
+    assert!(world.despawn(reserved));
+    assert!(!world.is_alive(reserved));
+}
+
+#[test]
+pub fn world_try_reserve_entity() {
+    let mut world = World::new();
+    world.set_max_entity_indices(1);
+    let reserved = world.try_reserve_entity().unwrap();
+    assert!(matches!(
+        world.try_reserve_entity(),
+        Err(EcsError::Spawn(SpawnError::OutOfIndices))
+    ));
+    world.flush();
+    assert!(world.is_alive(reserved));
+}
+
 #[test]
 pub fn build_with_zst() -> () {
+    #[derive(Component)]
     struct Zero;
 
     let mut world = World::new();
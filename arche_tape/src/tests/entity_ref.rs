@@ -0,0 +1,98 @@
+use crate::{Changed, Component, EcsIds, World};
+use std::sync::Arc;
+
+#[derive(Component, Debug, PartialEq)]
+struct Position(f32, f32);
+
+#[test]
+fn get() {
+    let mut world = World::new();
+    let e1 = spawn!(&mut world, 1_u32, Position(1., 2.));
+    let e2 = spawn!(&mut world, 2_u32);
+
+    assert!(world.get::<Position>(e1) == Some(&Position(1., 2.)));
+    assert!(world.get::<Position>(e2).is_none());
+    assert!(world.get::<u64>(e1).is_none());
+
+    world.get_mut::<Position>(e1).unwrap().0 = 3.;
+    assert!(world.get::<Position>(e1) == Some(&Position(3., 2.)));
+
+    world.despawn(e1);
+    assert!(world.get::<u32>(e1).is_none());
+    assert!(world.entity(e1).is_none());
+}
+
+#[test]
+fn get_mut_marks_changed() {
+    let mut world = World::new();
+    let e1 = spawn!(&mut world, 1_u32);
+    let e2 = spawn!(&mut world, 2_u32);
+
+    let last_run = world.query::<(&u32,)>().change_tick();
+    assert!(world.get::<u32>(e1) == Some(&1));
+    *world.get_mut::<u32>(e2).unwrap() += 1;
+
+    let mut query = world.query_since::<(EcsIds, Changed<u32>)>(last_run);
+    assert!(query.iter().map(|(e, &n)| (e, n)).collect::<Vec<_>>() == [(e2, 3)]);
+}
+
+#[derive(Component, Debug, PartialEq)]
+struct Marker;
+
+#[test]
+fn get_zero_sized() {
+    let mut world = World::new();
+    spawn!(&mut world, Marker, 0_u32);
+    let e2 = spawn!(&mut world, Marker, 1_u32);
+
+    assert!(world.get::<Marker>(e2) == Some(&Marker));
+    assert!(world.get_mut::<Marker>(e2) == Some(&mut Marker));
+    assert!(world.entity(e2).unwrap().get::<Marker>() == Some(&Marker));
+    assert!(world.entity_mut(e2).unwrap().get_mut::<Marker>() == Some(&mut Marker));
+}
+
+#[test]
+fn entity_ref() {
+    let mut world = World::new();
+    let e1 = spawn!(&mut world, 1_u32, Position(1., 2.));
+    let u32_id = world.get_or_create_type_id_ecsid::<u32>();
+    let position_id = world.get_or_create_type_id_ecsid::<Position>();
+
+    let entity = world.entity(e1).unwrap();
+    let (a, b) = (
+        entity.get::<u32>().unwrap(),
+        entity.get::<Position>().unwrap(),
+    );
+    assert!((*a, b) == (1, &Position(1., 2.)));
+    assert!(entity.id() == e1);
+    assert!(entity.has::<u32>() && !entity.has::<u64>());
+    assert!(entity.has_dynamic(position_id));
+
+    let mut ids = [u32_id, position_id];
+    ids.sort();
+    assert!(entity.component_ids() == &ids[..]);
+}
+
+#[test]
+fn entity_mut() {
+    let mut world = World::new();
+    let arc = Arc::new(());
+    let e1 = spawn!(&mut world, 1_u32, arc.clone());
+
+    let mut entity = world.entity_mut(e1).unwrap();
+    *entity.get_mut::<u32>().unwrap() += 1;
+    entity.insert(Position(1., 2.)).insert(3_u64);
+    assert!(entity.component_ids().len() == 4);
+    assert!(entity.get::<Position>() == Some(&Position(1., 2.)));
+
+    let removed = entity.remove::<Arc<()>>().unwrap();
+    assert!(Arc::strong_count(&arc) == 2);
+    drop(removed);
+    assert!(entity.remove::<Arc<()>>().is_none());
+    assert!(!entity.has::<Arc<()>>());
+    assert!(entity.get::<u32>() == Some(&2));
+    assert!(entity.get::<u64>() == Some(&3));
+
+    assert!(Arc::strong_count(&arc) == 1);
+    assert!(world.get::<Position>(e1) == Some(&Position(1., 2.)));
+}
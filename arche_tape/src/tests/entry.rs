@@ -0,0 +1,66 @@
+use crate::{Changed, Component, EcsIds, World};
+use std::sync::Arc;
+
+#[derive(Component, Default)]
+struct Counter(u32);
+
+#[test]
+fn insert_component_replaces() {
+    let mut world = World::new();
+    let old = Arc::new(());
+    let e1 = spawn!(&mut world, 1_u32, old.clone());
+    let archetypes = world.archetypes.len();
+
+    world.insert_component(e1, Arc::new(()));
+    assert!(Arc::strong_count(&old) == 1);
+    world.insert_component(e1, 2_u32);
+    assert!(world.archetypes.len() == archetypes);
+    assert!(*world.query::<(&u32,)>().get(e1).unwrap().0 == 2);
+
+    // Components the entity doesn't have get added
+    world.insert_component(e1, 3_u64);
+    assert!(*world.query::<(&u64,)>().get(e1).unwrap().0 == 3);
+}
+
+#[test]
+fn insert_component_marks_changed() {
+    let mut world = World::new();
+    let e1 = spawn!(&mut world, 1_u32);
+    let e2 = spawn!(&mut world, 2_u32);
+    let last_run = world.query::<(&u32,)>().change_tick();
+
+    world.insert_component(e1, 3_u32);
+    let mut query = world.query_since::<(EcsIds, Changed<u32>)>(last_run);
+    assert!(query.iter().map(|(e, _)| e).collect::<Vec<_>>() == [e1]);
+    assert!(world.is_alive(e2));
+}
+
+#[test]
+#[should_panic(expected = "use insert_component")]
+fn add_component_twice() {
+    let mut world = World::new();
+    let e1 = spawn!(&mut world, 1_u32);
+    world.add_component(e1, 2_u32);
+}
+
+#[test]
+fn entry() {
+    let mut world = World::new();
+    let e1 = world.spawn().build();
+
+    assert!(!world.entry::<Counter>(e1).is_occupied());
+    world.entry(e1).or_insert_with(|| Counter(10)).0 += 1;
+    world
+        .entry::<Counter>(e1)
+        .and_modify(|counter| counter.0 *= 2)
+        .or_default();
+    assert!(world.entry::<Counter>(e1).or_default().0 == 22);
+
+    let e2 = world.spawn().build();
+    world
+        .entry::<Counter>(e2)
+        .and_modify(|counter| counter.0 = 5);
+    assert!(!world.has_component::<Counter>(e2));
+    assert!(world.entry::<Counter>(e2).or_default().0 == 0);
+    assert!(world.entry(e2).or_insert(Counter(3)).0 == 0);
+}
@@ -0,0 +1,238 @@
+use crate::{BorrowError, EcsError, EcsId, EcsIds, FetchType, World};
+
+#[test]
+fn dead_entity() {
+    let mut world = World::new();
+    let e1 = spawn!(&mut world, 1_u32);
+    assert!(world.try_despawn(e1) == Ok(()));
+
+    assert!(world.try_despawn(e1) == Err(EcsError::DeadEntity(e1)));
+    assert!(world.try_add_component(e1, 1_u64) == Err(EcsError::DeadEntity(e1)));
+    assert!(world.try_remove_component::<u32>(e1) == Err(EcsError::DeadEntity(e1)));
+    assert!(world.query::<(&u32,)>().try_get(e1).err() == Some(EcsError::DeadEntity(e1)));
+}
+
+#[test]
+fn foreign_entity() {
+    let mut other = World::new();
+    for _ in 0..10 {
+        other.spawn().build();
+    }
+    let foreign = spawn!(&mut other, 1_u32);
+
+    let mut world = World::new();
+    spawn!(&mut world, 1_u32);
+    let dead = EcsError::DeadEntity(foreign);
+    assert!(!world.is_alive(foreign));
+    assert!(world.try_despawn(foreign) == Err(dead));
+    assert!(world.try_add_component(foreign, 1_u64) == Err(dead));
+    assert!(world.try_remove_component::<u32>(foreign) == Err(dead));
+    assert!(world.query::<(&u32,)>().try_get(foreign).err() == Some(dead));
+    assert!(world.get::<u32>(foreign).is_none());
+}
+
+#[test]
+fn add_remove() {
+    let mut world = World::new();
+    let e1 = spawn!(&mut world, 1_u32);
+    let u32_id = world.get_or_create_type_id_ecsid::<u32>();
+    let u64_id = world.get_or_create_type_id_ecsid::<u64>();
+
+    let duplicate = EcsError::DuplicateComponent {
+        entity: e1,
+        component: u32_id,
+    };
+    assert!(world.try_add_component(e1, 2_u32) == Err(duplicate));
+    assert!(world.try_add_component(e1, 2_u64) == Ok(()));
+
+    let missing = EcsError::MissingComponent {
+        entity: e1,
+        component: u32_id,
+    };
+    assert!(world.try_remove_component::<u32>(e1) == Ok(()));
+    assert!(world.try_remove_component::<u32>(e1) == Err(missing));
+    assert!(world.try_remove_component_dynamic(e1, u64_id) == Ok(()));
+}
+
+#[test]
+fn add_dynamic() {
+    let mut world = World::new();
+    let e1 = world.spawn().build();
+    let tag = world.spawn().build();
+    let u32_id = world.get_or_create_type_id_ecsid::<u32>();
+
+    assert!(world.try_add_component_dynamic(e1, u32_id) == Err(EcsError::LayoutMismatch(u32_id)));
+    assert!(world.try_add_component_dynamic(e1, tag) == Ok(()));
+    let duplicate = EcsError::DuplicateComponent {
+        entity: e1,
+        component: tag,
+    };
+    assert!(world.try_add_component_dynamic(e1, tag) == Err(duplicate));
+
+    world.despawn(tag);
+    assert!(world.try_add_component_dynamic(e1, tag) == Err(EcsError::DeadEntity(tag)));
+}
+
+#[test]
+fn entity_builder() {
+    let mut world = World::new();
+    let tag = world.spawn().build();
+    let u32_id = world.get_or_create_type_id_ecsid::<u32>();
+    let alive = world.entity_counters().alive;
+
+    let builder = world.spawn().try_with_dynamic(u32_id);
+    assert!(builder.err() == Some(EcsError::LayoutMismatch(u32_id)));
+    let builder = world.spawn().try_with_dynamic(tag).unwrap();
+    assert!(matches!(
+        builder.try_with_dynamic(tag).err(),
+        Some(EcsError::DuplicateComponent { component, .. }) if component == tag
+    ));
+
+    let mut builder = world.spawn().with(1_u32).with(2_u32);
+    assert!(matches!(
+        builder.try_build(),
+        Err(EcsError::DuplicateComponent { component, .. }) if component == u32_id
+    ));
+    drop(builder);
+    assert!(world.entity_counters().alive == alive);
+
+    let e1 = world
+        .spawn()
+        .try_with_dynamic(tag)
+        .unwrap()
+        .try_build()
+        .unwrap();
+    assert!(world.has_component_dynamic(e1, tag));
+}
+
+#[test]
+fn try_get() {
+    let mut world = World::new();
+    let e1 = spawn!(&mut world, 1_u32);
+    let e2 = spawn!(&mut world, 1_u64);
+
+    let mut query = world.query::<(&u32,)>();
+    assert!(query.try_get(e1).map(|(&n,)| n) == Ok(1));
+    assert!(query.try_get(e2).err() == Some(EcsError::QueryMismatch(e2)));
+}
+
+#[test]
+fn try_query() {
+    let mut world = World::new();
+    let e1 = spawn!(&mut world, 1_u32, 1_u64);
+
+    let query = world.query::<(&mut u32,)>();
+    let borrowed = BorrowError::AlreadyBorrowed("&u32");
+    assert!(world.try_query::<(&u32,)>().err() == Some(EcsError::AlreadyBorrowed(borrowed)));
+    assert!(world.try_query::<(&u64,)>().is_ok());
+    drop(query);
+
+    // Taking the same component mutably twice in one query fails instead of deadlocking
+    let aliased = BorrowError::Aliased("&u32");
+    assert!(
+        world.try_query::<(&mut u32, &u32)>().err() == Some(EcsError::AlreadyBorrowed(aliased))
+    );
+    assert!(world.try_query::<(&u32, &u32)>().is_ok());
+
+    let _read = world.query::<(&u32,)>();
+    let mut query = world.try_query::<(EcsIds, &u32)>().unwrap();
+    assert!(query.iter().map(|(e, _)| e).collect::<Vec<_>>() == [e1]);
+}
+
+#[test]
+fn try_query_dynamic() {
+    let mut world = World::new();
+    let likes = world.spawn().build();
+    let alice = world.spawn().build();
+    let e1 = spawn!(&mut world, 1_u32);
+    world.add_component_dynamic(e1, EcsId::pair(likes, alice));
+    let u32_id = world.get_or_create_type_id_ecsid::<u32>();
+
+    let aliased = EcsError::AlreadyBorrowed(BorrowError::Aliased("FetchType::Mut"));
+    let query = world.try_query_dynamic([FetchType::Mut(u32_id), FetchType::Mut(u32_id)]);
+    assert!(query.err() == Some(aliased));
+    let query = world.try_query_dynamic([
+        FetchType::Immut(EcsId::pair(likes, alice)),
+        FetchType::Mut(EcsId::pair(likes, EcsId::WILDCARD)),
+    ]);
+    assert!(query.err() == Some(aliased));
+
+    let query = world.query_dynamic([FetchType::Immut(u32_id)]);
+    let borrowed = EcsError::AlreadyBorrowed(BorrowError::AlreadyBorrowed("FetchType::Mut"));
+    assert!(world.try_query_dynamic([FetchType::Mut(u32_id)]).err() == Some(borrowed));
+    assert!(world.try_query::<(&mut u32,)>().is_err());
+    assert!(world.try_query_dynamic([FetchType::Immut(u32_id)]).is_ok());
+    drop(query);
+    assert!(world.try_query_dynamic([FetchType::Mut(u32_id)]).is_ok());
+}
+
+#[test]
+fn try_resource() {
+    let mut world = World::new();
+    assert!(world.try_resource::<u32>().err() == Some(EcsError::MissingResource("u32")));
+    world.insert_resource(1_u32);
+
+    let resource = world.resource::<u32>().unwrap();
+    assert!(world.try_resource::<u32>().is_ok());
+    let borrowed = EcsError::AlreadyBorrowed(BorrowError::AlreadyBorrowed("u32"));
+    assert!(world.try_resource_mut::<u32>().err() == Some(borrowed));
+    drop(resource);
+
+    let resource = world.try_resource_mut::<u32>().unwrap();
+    assert!(world.try_resource::<u32>().err() == Some(borrowed));
+    drop(resource);
+
+    world.remove_resource::<u32>();
+    assert!(world.try_resource_mut::<u32>().err() == Some(EcsError::MissingResource("u32")));
+    assert!(world.resource::<u32>().is_none());
+}
+
+#[test]
+fn display() {
+    let mut world = World::new();
+    let e1 = world.spawn().build();
+    world.despawn(e1);
+    let error = world.try_despawn(e1).unwrap_err();
+    assert!(error.to_string() == format!("entity {} is not alive", e1));
+}
+
+#[test]
+#[should_panic(expected = "&mut u32 is already borrowed")]
+fn overlapping_queries_panic() {
+    let mut world = World::new();
+    spawn!(&mut world, 1_u32);
+
+    let _read = world.query::<(&u32,)>();
+    world.query::<(&mut u32,)>();
+}
+
+#[test]
+#[should_panic(expected = "query borrows &mut u32 while also borrowing it elsewhere")]
+fn aliased_query_panics() {
+    let mut world = World::new();
+    spawn!(&mut world, 1_u32);
+    world.query::<(&u32, &mut u32)>();
+}
+
+#[test]
+#[should_panic(expected = "u32 is already borrowed")]
+fn borrowed_resource_panics() {
+    let mut world = World::new();
+    world.insert_resource(1_u32);
+
+    let _resource = world.resource::<u32>().unwrap();
+    world.resource_mut::<u32>();
+}
+
+#[test]
+fn borrowed_resource() {
+    let mut world = World::new();
+    world.insert_resource(1_u32);
+
+    let _resource = world.resource_mut::<u32>().unwrap();
+    let query = world.try_query::<(crate::Res<u32>,)>();
+    assert!(matches!(
+        query.err(),
+        Some(EcsError::AlreadyBorrowed(BorrowError::AlreadyBorrowed(_)))
+    ));
+}
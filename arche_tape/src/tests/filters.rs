@@ -0,0 +1,104 @@
+use crate::{EcsId, EcsIds, FetchType, Or, With, Without, World};
+
+fn ids(iter: impl Iterator<Item = [*mut u8; 1]>) -> Vec<EcsId> {
+    iter.map(|[ptr]| unsafe { *(ptr as *mut EcsId) }).collect()
+}
+
+#[test]
+fn with_without() {
+    let mut world = World::new();
+    let e1 = spawn!(&mut world, 1_u32);
+    let e2 = spawn!(&mut world, 2_u32, 1_u64);
+    let e3 = spawn!(&mut world, 1_u64, 1_u8);
+
+    let mut query = world.query::<(EcsIds, &u32, With<u64>)>();
+    assert!(query.iter().map(|(e, &n, ())| (e, n)).collect::<Vec<_>>() == [(e2, 2)]);
+    assert!(query.get(e1).is_none());
+    assert!(query.get(e2).is_some());
+
+    let mut query = world.query::<(EcsIds, &u64, Without<u32>)>();
+    assert!(query.iter().map(|(e, ..)| e).collect::<Vec<_>>() == [e3]);
+    drop(query);
+
+    let mut query = world.query::<(EcsIds, &u32, Without<u64>)>();
+    assert!(query.iter().map(|(e, ..)| e).collect::<Vec<_>>() == [e1]);
+    assert!(query.get(e2).is_none());
+    drop(query);
+
+    // Filters don't lock their components
+    let mut query = world.query::<(&mut u64, With<u32>)>();
+    let mut other = world.query::<(EcsIds, With<u64>)>();
+    assert!(query.iter().count() == 1);
+    assert!(other.iter().count() == 2);
+}
+
+#[test]
+fn unregistered_components() {
+    let mut world = World::new();
+    let e1 = spawn!(&mut world, 1_u32);
+
+    let mut query = world.query::<(EcsIds, With<u16>)>();
+    assert!(query.iter().count() == 0);
+
+    let mut query = world.query::<(EcsIds, &u32, Without<u16>)>();
+    assert!(query.iter().map(|(e, ..)| e).collect::<Vec<_>>() == [e1]);
+
+    let mut query = world.query::<(EcsIds, Or<(With<u16>, With<u32>)>)>();
+    assert!(query.iter().map(|(e, _)| e).collect::<Vec<_>>() == [e1]);
+}
+
+#[test]
+fn or() {
+    let mut world = World::new();
+    let e1 = spawn!(&mut world, 1_u32);
+    let e2 = spawn!(&mut world, 1_u64);
+    let e3 = spawn!(&mut world, 1_u8);
+    let e4 = spawn!(&mut world, 1_u32, 1_u64);
+    // More archetypes than fit in one word of the bitset
+    for n in 0..100 {
+        let tag = spawn!(&mut world);
+        world.spawn().with(n as u8).with_dynamic(tag).build();
+    }
+
+    let mut query = world.query::<(EcsIds, Or<(With<u32>, With<u64>)>)>();
+    assert!(query.iter().map(|(e, _)| e).collect::<Vec<_>>() == [e1, e2, e4]);
+    assert!(query.get(e3).is_none());
+
+    let mut query = world.query::<(EcsIds, &u8, Or<(With<u32>, Without<u64>)>)>();
+    let matched = query.iter().map(|(e, ..)| e).collect::<Vec<_>>();
+    assert!(matched.len() == 101);
+    assert!(matched[0] == e3);
+
+    let mut query = world.query::<(EcsIds, &u32, Or<(Without<u64>, Or<(With<u8>, With<u16>)>)>)>();
+    assert!(query.iter().map(|(e, ..)| e).collect::<Vec<_>>() == [e1]);
+}
+
+#[test]
+fn dynamic() {
+    let mut world = World::new();
+    let e1 = spawn!(&mut world, 1_u32);
+    let e2 = spawn!(&mut world, 2_u32, 1_u64);
+    let e3 = spawn!(&mut world, 1_u64);
+    let u32_id = world.get_or_create_type_id_ecsid::<u32>();
+    let u64_id = world.get_or_create_type_id_ecsid::<u64>();
+
+    let mut query = world.query_dynamic([
+        FetchType::EcsId,
+        FetchType::With(u32_id),
+        FetchType::Without(u64_id),
+    ]);
+    let matched = query
+        .iter()
+        .map(|[e, with, without]| {
+            assert!(with.is_null() && without.is_null());
+            unsafe { *(e as *mut EcsId) }
+        })
+        .collect::<Vec<_>>();
+    assert!(matched == [e1]);
+    drop(query);
+
+    let or = FetchType::Or(vec![FetchType::With(u32_id), FetchType::With(u64_id)]);
+    let mut query = world.query_dynamic([FetchType::EcsId, or]);
+    let matched = query.iter().map(|[e, _]| [e]);
+    assert!(ids(matched) == [e1, e2, e3]);
+}
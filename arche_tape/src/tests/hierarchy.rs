@@ -0,0 +1,102 @@
+use crate::{ChildOf, EcsId, EcsIds, Relation, World};
+
+/// Builds the tree:
+///    root
+///    ├── a
+///    │   ├── c
+///    │   └── d
+///    └── b
+///        └── e
+fn tree(world: &mut World) -> [EcsId; 6] {
+    let root = world.spawn().build();
+    let [a, b, c, d, e] = [(); 5].map(|_| world.spawn().with(1_u32).build());
+    world.set_parent(a, root);
+    world.set_parent(b, root);
+    world.set_parent(c, a);
+    world.set_parent(d, a);
+    world.set_parent(e, b);
+    [root, a, b, c, d, e]
+}
+
+#[test]
+fn set_parent() {
+    let mut world = World::new();
+    let [root, a, b, c, d, e] = tree(&mut world);
+
+    assert!(world.parent(root).is_none());
+    assert!(world.parent(a) == Some(root));
+    assert!(world.parent(e) == Some(b));
+    assert!(world.children(root).collect::<Vec<_>>() == [a, b]);
+    assert!(world.children(a).collect::<Vec<_>>() == [c, d]);
+    assert!(world.children(c).next().is_none());
+
+    // Reparenting removes the old parent
+    world.set_parent(d, b);
+    assert!(world.parent(d) == Some(b));
+    assert!(world.children(a).collect::<Vec<_>>() == [c]);
+    assert!(world.children(b).collect::<Vec<_>>() == [e, d]);
+
+    assert!(world.remove_parent(d));
+    assert!(world.remove_parent(d) == false);
+    assert!(world.parent(d).is_none());
+}
+
+#[test]
+#[should_panic(expected = "Attempted to parent an entity to one of its descendants")]
+fn set_parent_cycle() {
+    let mut world = World::new();
+    let [root, _, _, c, _, _] = tree(&mut world);
+    world.set_parent(root, c);
+}
+
+#[test]
+fn traversal() {
+    let mut world = World::new();
+    let [root, a, b, c, d, e] = tree(&mut world);
+
+    let depth_first = world.iter_descendants_depth_first(root).collect::<Vec<_>>();
+    assert!(depth_first == [a, c, d, b, e]);
+
+    let breadth_first = world
+        .iter_descendants_breadth_first(root)
+        .collect::<Vec<_>>();
+    assert!(breadth_first == [a, b, c, d, e]);
+
+    assert!(world.iter_descendants_depth_first(c).next().is_none());
+}
+
+#[test]
+fn recursive_despawn() {
+    let mut world = World::new();
+    let [root, a, b, c, d, e] = tree(&mut world);
+    let other = world.spawn().with(2_u32).build();
+
+    assert!(world.despawn(a));
+    assert!(world.is_alive(a) == false);
+    assert!(world.is_alive(c) == false);
+    assert!(world.is_alive(d) == false);
+    assert!(world.children(root).collect::<Vec<_>>() == [b]);
+
+    assert!(world.despawn(root));
+    for entity in [root, b, e] {
+        assert!(world.is_alive(entity) == false);
+    }
+    assert!(world.is_alive(other));
+
+    let mut q = world.query::<(&u32,)>();
+    assert!(q.iter().map(|(data,)| *data).collect::<Vec<_>>() == [2]);
+}
+
+#[test]
+fn query_child_of() {
+    let mut world = World::new();
+    let [root, a, b, _, _, _] = tree(&mut world);
+
+    let mut q = world.query::<(EcsIds, Relation<ChildOf>)>();
+    let root_children = q
+        .iter()
+        .filter(|(_, pair)| world.pair_parts(*pair).unwrap().1 == root)
+        .map(|(e, _)| e)
+        .collect::<Vec<_>>();
+    assert!(root_children == [a, b]);
+}
@@ -0,0 +1,89 @@
+use crate::{Changed, EcsId, EcsIds, FetchType, World};
+
+#[test]
+fn optional_immut() {
+    let mut world = World::new();
+    let e1 = spawn!(&mut world, 1_u32);
+    let e2 = spawn!(&mut world, 2_u32, 20_u64);
+    let e3 = spawn!(&mut world, 30_u64);
+
+    let mut query = world.query::<(EcsIds, &u32, Option<&u64>)>();
+    let matched = query
+        .iter()
+        .map(|(e, &n, opt)| (e, n, opt.copied()))
+        .collect::<Vec<_>>();
+    assert!(matched == [(e1, 1, None), (e2, 2, Some(20))]);
+
+    assert!(query.get(e1).unwrap().2.is_none());
+    assert!(*query.get(e2).unwrap().2.unwrap() == 20);
+    assert!(query.get(e3).is_none());
+}
+
+#[test]
+fn optional_mut() {
+    let mut world = World::new();
+    let e1 = spawn!(&mut world, 1_u32);
+    let e2 = spawn!(&mut world, 2_u32, 20_u64);
+    let e3 = spawn!(&mut world, 3_u32, 30_u64, 1_u8);
+
+    let last_run = world.query::<(&u32,)>().change_tick();
+    let mut query = world.query::<(&u32, Option<&mut u64>)>();
+    for (&n, opt) in query.iter() {
+        if let Some(opt) = opt {
+            *opt += n as u64;
+        }
+    }
+    drop(query);
+
+    let mut query = world.query::<(EcsIds, Option<&u64>)>();
+    assert!(query.get(e1).unwrap().1.is_none());
+    assert!(*query.get(e2).unwrap().1.unwrap() == 22);
+    assert!(*query.get(e3).unwrap().1.unwrap() == 33);
+    drop(query);
+
+    // Only entities that had the component are marked changed
+    let mut query = world.query_since::<(EcsIds, Changed<u64>)>(last_run);
+    assert!(query.iter().map(|(e, _)| e).collect::<Vec<_>>() == [e2, e3]);
+}
+
+#[test]
+fn unregistered_component() {
+    let mut world = World::new();
+    let e1 = spawn!(&mut world, 1_u32);
+
+    let mut query = world.query::<(EcsIds, &u32, Option<&mut u16>)>();
+    let matched = query
+        .iter()
+        .map(|(e, _, opt)| (e, opt.is_some()))
+        .collect::<Vec<_>>();
+    assert!(matched == [(e1, false)]);
+}
+
+#[test]
+fn dynamic() {
+    let mut world = World::new();
+    let e1 = spawn!(&mut world, 1_u32);
+    let e2 = spawn!(&mut world, 2_u32, 20_u64);
+    let u32_id = world.get_or_create_type_id_ecsid::<u32>();
+    let u64_id = world.get_or_create_type_id_ecsid::<u64>();
+    let unused = spawn!(&mut world);
+
+    let mut query = world.query_dynamic([
+        FetchType::EcsId,
+        FetchType::Immut(u32_id),
+        FetchType::Optional(Box::new(FetchType::Mut(u64_id))),
+        FetchType::Optional(Box::new(FetchType::Immut(unused))),
+    ]);
+    let matched = query
+        .iter()
+        .map(|[e, _, opt, unused]| {
+            assert!(unused.is_null());
+            let opt = match opt.is_null() {
+                true => None,
+                false => Some(unsafe { *(opt as *mut u64) }),
+            };
+            (unsafe { *(e as *mut EcsId) }, opt)
+        })
+        .collect::<Vec<_>>();
+    assert!(matched == [(e1, None), (e2, Some(20))]);
+}
@@ -0,0 +1,212 @@
+use crate::{Component, EcsId, EcsIds, FetchType, Relation, World};
+
+#[derive(Component)]
+struct ChildOf;
+#[derive(Component)]
+struct Likes(u32);
+
+#[test]
+fn pair_id_parts() {
+    let relation = EcsId::new(3, 1);
+    let target = EcsId::new(7, 2);
+    let pair = EcsId::pair(relation, target);
+
+    assert!(pair.is_pair());
+    assert!(pair.is_wildcard() == false);
+    assert!(pair.pair_relation() == Some(relation.index()));
+    assert!(pair.pair_target() == Some(target.index()));
+    assert!(relation.is_pair() == false);
+    assert!(relation.pair_relation().is_none());
+
+    let any_target = EcsId::pair(relation, EcsId::WILDCARD);
+    let any_relation = EcsId::pair(EcsId::WILDCARD, target);
+    assert!(any_target.is_wildcard());
+    assert!(any_relation.is_wildcard());
+    assert!(pair.matches(any_target));
+    assert!(pair.matches(any_relation));
+    assert!(pair.matches(EcsId::pair(EcsId::WILDCARD, relation)) == false);
+    assert!(any_target.matches(pair) == false);
+}
+
+#[test]
+#[should_panic(expected = "cannot be used as the relation of a pair")]
+fn pair_relation_with_wildcard_index() {
+    // Would otherwise encode the same as ``(*, target)``
+    EcsId::pair(EcsId::new(0x7FFFFFFF, 0), EcsId::new(7, 0));
+}
+
+#[test]
+fn pair_dyn_query() {
+    let mut world = World::new();
+
+    let likes = world.spawn().build();
+    let bob = world.spawn().build();
+    let alice = world.spawn().build();
+
+    let e1 = world
+        .spawn()
+        .with(1_u32)
+        .with_dynamic(EcsId::pair(likes, bob))
+        .build();
+    let e2 = world
+        .spawn()
+        .with(2_u32)
+        .with_dynamic(EcsId::pair(likes, alice))
+        .build();
+    let e3 = world.spawn().with(3_u32).build();
+    world.add_component_dynamic(e3, EcsId::pair(likes, bob));
+
+    assert!(world.has_component_dynamic(e1, EcsId::pair(likes, bob)));
+    assert!(world.has_component_dynamic(e1, EcsId::pair(likes, alice)) == false);
+    assert!(world.has_component_dynamic(e2, EcsId::pair(likes, EcsId::WILDCARD)));
+
+    let mut query =
+        world.query_dynamic([FetchType::EcsId, FetchType::Immut(EcsId::pair(likes, bob))]);
+    let likes_bob = query
+        .iter()
+        .map(|[e, _]| unsafe { *(e as *mut EcsId) })
+        .collect::<Vec<_>>();
+    assert!(likes_bob == [e1, e3]);
+    drop(query);
+
+    let mut query = world.query_dynamic([
+        FetchType::EcsId,
+        FetchType::MatchedPair(EcsId::pair(likes, EcsId::WILDCARD)),
+    ]);
+    let mut likes_anyone = query
+        .iter()
+        .map(|[e, pair]| unsafe { (*(e as *mut EcsId), *(pair as *mut EcsId)) })
+        .collect::<Vec<_>>();
+    likes_anyone.sort();
+    assert!(
+        likes_anyone
+            == [
+                (e1, EcsId::pair(likes, bob)),
+                (e2, EcsId::pair(likes, alice)),
+                (e3, EcsId::pair(likes, bob)),
+            ]
+    );
+    drop(query);
+
+    let mut query = world.query_dynamic([
+        FetchType::EcsId,
+        FetchType::Immut(EcsId::pair(EcsId::WILDCARD, alice)),
+    ]);
+    let liked_alice = query
+        .iter()
+        .map(|[e, _]| unsafe { *(e as *mut EcsId) })
+        .collect::<Vec<_>>();
+    assert!(liked_alice == [e2]);
+}
+
+#[test]
+fn relation_with_data() {
+    let mut world = World::new();
+
+    let bob = world.spawn().build();
+    let alice = world.spawn().build();
+    let e1 = world.spawn().build();
+    world.add_relation(e1, bob, Likes(10));
+    world.add_relation(e1, alice, Likes(20));
+
+    assert!(world.has_relation::<Likes>(e1, bob));
+    assert!(world.has_relation::<Likes>(e1, alice));
+
+    let likes = world.get_or_create_type_id_ecsid::<Likes>();
+    let mut query = world.query_dynamic([FetchType::Mut(EcsId::pair(likes, alice))]);
+    for [ptr] in query.iter() {
+        let likes = unsafe { &mut *(ptr as *mut Likes) };
+        assert!(likes.0 == 20);
+        likes.0 = 21;
+    }
+    drop(query);
+
+    let pair_ptr = world
+        .get_component_mut_dynamic(e1, EcsId::pair(likes, alice))
+        .unwrap();
+    assert!(unsafe { &*(pair_ptr as *mut Likes) }.0 == 21);
+
+    world.remove_relation::<Likes>(e1, bob);
+    assert!(world.has_relation::<Likes>(e1, bob) == false);
+    assert!(world.has_relation::<Likes>(e1, alice));
+}
+
+#[test]
+fn relation_static_query() {
+    let mut world = World::new();
+
+    let parent = world.spawn().build();
+    let child_1 = world.spawn().with(1_u32).build();
+    let child_2 = world.spawn().with(2_u32).build();
+    world.spawn().with(3_u32).build();
+    world.add_relation(child_1, parent, ChildOf);
+    world.add_relation(child_2, parent, ChildOf);
+
+    let mut query = world.query::<(EcsIds, &u32, Relation<ChildOf>)>();
+    let children = query
+        .iter()
+        .map(|(e, data, pair)| (e, *data, world.pair_parts(pair).unwrap().1))
+        .collect::<Vec<_>>();
+    assert!(children == [(child_1, 1, parent), (child_2, 2, parent)]);
+
+    assert!(query.get(child_1).is_some());
+    assert!(query.get(parent).is_none());
+}
+
+#[test]
+fn despawn_pair_target() {
+    let mut world = World::new();
+
+    let likes = world.spawn().build();
+    let bob = world.spawn().build();
+    let e1 = world
+        .spawn()
+        .with(1_u32)
+        .with_dynamic(EcsId::pair(likes, bob))
+        .build();
+
+    assert!(world.despawn(bob));
+    assert!(world.has_component_dynamic(e1, EcsId::pair(likes, bob)) == false);
+    assert!(world.has_component::<u32>(e1));
+    assert!(world.lock_lookup.contains_key(&EcsId::pair(likes, bob)) == false);
+
+    // The new entity reuses bob's index so the pair id is the same, it must not match e1
+    let new_bob = world.spawn().build();
+    assert!(new_bob.index() == bob.index());
+    let e2 = world
+        .spawn()
+        .with_dynamic(EcsId::pair(likes, new_bob))
+        .build();
+
+    let mut query = world.query_dynamic([
+        FetchType::EcsId,
+        FetchType::Immut(EcsId::pair(EcsId::WILDCARD, new_bob)),
+    ]);
+    let found = query
+        .iter()
+        .map(|[e, _]| unsafe { *(e as *mut EcsId) })
+        .collect::<Vec<_>>();
+    assert!(found == [e2]);
+}
+
+#[test]
+fn despawn_pair_relation() {
+    let mut world = World::new();
+
+    let likes = world.spawn().build();
+    let bob = world.spawn().build();
+    let alice = world.spawn().build();
+    let e1 = world
+        .spawn()
+        .with_dynamic(EcsId::pair(likes, bob))
+        .with_dynamic(EcsId::pair(likes, alice))
+        .build();
+
+    assert!(world.despawn(likes));
+    assert!(world.has_component_dynamic(e1, EcsId::pair(likes, EcsId::WILDCARD)) == false);
+    assert!(world.is_alive(bob));
+    assert!(world.is_alive(alice));
+
+    let mut query = world.query_dynamic([FetchType::Immut(EcsId::pair(likes, EcsId::WILDCARD))]);
+    assert!(query.iter().count() == 0);
+}
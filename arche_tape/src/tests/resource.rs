@@ -0,0 +1,120 @@
+use crate::{Component, FetchType, Res, World};
+use std::any::TypeId;
+
+#[derive(Component)]
+struct Gravity(f32);
+
+#[test]
+fn insert_remove() {
+    let mut world = World::new();
+    assert!(world.has_resource::<Gravity>() == false);
+    assert!(world.insert_resource(Gravity(9.8)).is_none());
+    assert!(world.has_resource::<Gravity>());
+
+    let old = world.insert_resource(Gravity(1.6)).unwrap();
+    assert!(old.0 == 9.8);
+    assert!(world.resource::<Gravity>().unwrap().0 == 1.6);
+
+    assert!(world.remove_resource::<Gravity>().unwrap().0 == 1.6);
+    assert!(world.has_resource::<Gravity>() == false);
+    assert!(world.resource::<Gravity>().is_none());
+    assert!(world.remove_resource::<Gravity>().is_none());
+
+    world.insert_resource(Gravity(3.7));
+    assert!(world.resource::<Gravity>().unwrap().0 == 3.7);
+}
+
+#[test]
+fn borrow() {
+    let mut world = World::new();
+    world.insert_resource(10_u32);
+
+    {
+        let a = world.resource::<u32>().unwrap();
+        let b = world.resource::<u32>().unwrap();
+        assert!(*a == 10 && *b == 10);
+    }
+
+    *world.resource_mut::<u32>().unwrap() += 5;
+    assert!(*world.resource::<u32>().unwrap() == 15);
+    assert!(world.resource_mut::<u64>().is_none());
+}
+
+#[test]
+fn static_query() {
+    let mut world = World::new();
+    world.insert_resource(Gravity(2.0));
+    world.insert_resource(0_u64);
+    spawn!(&mut world, 1.0_f32);
+    spawn!(&mut world, 3.0_f32);
+
+    let mut count = world.resource_mut::<u64>().unwrap();
+    let mut query = world.query::<(&mut f32, Res<Gravity>)>();
+    for (n, gravity) in query.iter() {
+        *n *= gravity.0;
+        *count += 1;
+    }
+    drop(query);
+    drop(count);
+
+    let mut query = world.query::<(&f32,)>();
+    assert!(query.iter().map(|(&n,)| n).collect::<Vec<_>>() == [2.0, 6.0]);
+    drop(query);
+    assert!(*world.resource::<u64>().unwrap() == 2);
+}
+
+#[test]
+fn missing_resource() {
+    let mut world = World::new();
+    spawn!(&mut world, 1_u32);
+
+    let mut query = world.query::<(&u32, Res<Gravity>)>();
+    assert!(query.iter().count() == 0);
+    drop(query);
+
+    world.insert_resource(Gravity(1.0));
+    world.remove_resource::<Gravity>();
+    let mut query = world.query::<(&u32, Res<Gravity>)>();
+    assert!(query.iter().count() == 0);
+}
+
+#[test]
+fn dynamic() {
+    let mut world = World::new();
+    world.insert_resource(Gravity(2.0));
+    spawn!(&mut world, 1_u32);
+    spawn!(&mut world, 2_u32);
+    let u32_id = world.get_or_create_type_id_ecsid::<u32>();
+
+    let mut query = world.query_dynamic([
+        FetchType::Immut(u32_id),
+        FetchType::ResourceMut(TypeId::of::<Gravity>()),
+    ]);
+    for [_, gravity] in query.iter() {
+        unsafe { (*(gravity as *mut Gravity)).0 += 1.0 };
+    }
+    drop(query);
+    assert!(world.resource::<Gravity>().unwrap().0 == 4.0);
+
+    let mut query = world.query_dynamic([
+        FetchType::Immut(u32_id),
+        FetchType::Resource(TypeId::of::<u64>()),
+    ]);
+    assert!(query.iter().count() == 0);
+}
+
+#[test]
+fn despawned_component_lock() {
+    let mut world = World::new();
+    let tag = world.spawn().build();
+    let e1 = world.spawn().build();
+    world.add_component_dynamic(e1, tag);
+    spawn!(&mut world, 1_i8);
+    world.insert_resource(10_u32);
+
+    // Despawning a component frees its lock without moving the locks of other components or resources
+    world.despawn(tag);
+    assert!(*world.try_resource::<u32>().unwrap() == 10);
+    let _query = world.query::<(&mut i8,)>();
+    assert!(world.try_resource_mut::<u32>().is_ok());
+}
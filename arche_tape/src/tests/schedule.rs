@@ -0,0 +1,150 @@
+use crate::{Changed, ResourceMut, ResourceRef, Schedule, StaticQuery, World};
+use std::sync::{Arc, Barrier};
+
+fn add_one(mut query: StaticQuery<(&mut u32,)>) {
+    for (n,) in query.iter() {
+        *n += 1;
+    }
+}
+
+fn double(mut query: StaticQuery<(&mut u32,)>) {
+    for (n,) in query.iter() {
+        *n *= 2;
+    }
+}
+
+fn read_u64(mut query: StaticQuery<(&u64,)>) {
+    query.iter().for_each(drop);
+}
+
+fn read_u32(mut query: StaticQuery<(&u32,)>) {
+    query.iter().for_each(drop);
+}
+
+#[test]
+fn batches() {
+    let mut world = World::new();
+    spawn!(&mut world, 1_u32, 1_u64);
+
+    let mut schedule = Schedule::with_threads(2);
+    schedule
+        .add_system(add_one)
+        .add_system(read_u64)
+        .add_system(read_u32)
+        .add_system(double)
+        .add_system(read_u64);
+    assert!(schedule.batches(&world) == [vec![0, 1, 4], vec![2], vec![3]]);
+}
+
+#[test]
+fn conflicting_systems_run_in_order() {
+    let mut world = World::new();
+    let e1 = spawn!(&mut world, 1_u32);
+    let e2 = spawn!(&mut world, 10_u32);
+
+    let mut schedule = Schedule::with_threads(2);
+    schedule.add_system(add_one).add_system(double);
+    schedule.run(&mut world);
+    schedule.run(&mut world);
+
+    let mut query = world.query::<(&u32,)>();
+    assert!(*query.get(e1).unwrap().0 == 10);
+    assert!(*query.get(e2).unwrap().0 == 46);
+}
+
+#[test]
+fn parallel() {
+    let mut world = World::new();
+    spawn!(&mut world, 1_u32, 1_u64);
+    let barrier = Arc::new(Barrier::new(2));
+
+    // Both systems wait on the barrier so this only finishes if they run at the same time
+    let mut schedule = Schedule::with_threads(2);
+    let barrier_1 = barrier.clone();
+    schedule.add_system(move |mut query: StaticQuery<(&mut u32,)>| {
+        barrier_1.wait();
+        for (n,) in query.iter() {
+            *n += 1;
+        }
+    });
+    schedule.add_system(move |mut query: StaticQuery<(&mut u64,)>| {
+        barrier.wait();
+        for (n,) in query.iter() {
+            *n += 1;
+        }
+    });
+    schedule.run(&mut world);
+
+    let mut query = world.query::<(&u32, &u64)>();
+    assert!(query.iter().map(|(&a, &b)| (a, b)).collect::<Vec<_>>() == [(2, 2)]);
+}
+
+#[test]
+fn resources() {
+    let mut world = World::new();
+    world.insert_resource(0_u64);
+    world.insert_resource(5_u32);
+    spawn!(&mut world, 1_u8);
+    spawn!(&mut world, 2_u8);
+
+    let mut schedule = Schedule::with_threads(2);
+    schedule.add_system(
+        |mut query: StaticQuery<(&u8,)>, step: ResourceRef<u32>, mut total: ResourceMut<u64>| {
+            for (&n,) in query.iter() {
+                *total += n as u64 * *step as u64;
+            }
+        },
+    );
+    schedule.add_system(|mut step: ResourceMut<u32>| *step += 1);
+    assert!(schedule.batches(&world) == [vec![0], vec![1]]);
+
+    schedule.run(&mut world);
+    schedule.run(&mut world);
+    assert!(*world.resource::<u64>().unwrap() == 15 + 18);
+    assert!(*world.resource::<u32>().unwrap() == 7);
+}
+
+#[test]
+fn change_detection() {
+    let mut world = World::new();
+    let e1 = spawn!(&mut world, 1_u32);
+    spawn!(&mut world, 2_u32);
+
+    let changed = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let mut schedule = Schedule::with_threads(1);
+    let changed_2 = changed.clone();
+    schedule.add_system(move |mut query: StaticQuery<(&u32, Changed<u32>)>| {
+        changed_2.lock().unwrap().push(query.iter().count());
+    });
+
+    schedule.run(&mut world);
+    schedule.run(&mut world);
+    *world.query::<(&mut u32,)>().get(e1).unwrap().0 = 3;
+    schedule.run(&mut world);
+    assert!(*changed.lock().unwrap() == [2, 0, 1]);
+}
+
+#[test]
+#[should_panic(
+    expected = "A system cannot borrow a component or resource mutably while also borrowing it elsewhere"
+)]
+fn aliasing_system() {
+    let mut world = World::new();
+    spawn!(&mut world, 1_u32);
+
+    let mut schedule = Schedule::with_threads(1);
+    schedule.add_system(|_: StaticQuery<(&mut u32,)>, _: StaticQuery<(&u32,)>| ());
+    schedule.run(&mut world);
+}
+
+#[test]
+#[should_panic(expected = "system panicked")]
+fn panics_are_propagated() {
+    let mut world = World::new();
+    spawn!(&mut world, 1_u32, 1_u64);
+
+    let mut schedule = Schedule::with_threads(2);
+    schedule.add_system(add_one);
+    schedule.add_system(|_: StaticQuery<(&u64,)>| panic!("system panicked"));
+    schedule.run(&mut world);
+}
@@ -0,0 +1,144 @@
+use crate::entities::MAX_GENERATION;
+use crate::world::ComponentMeta;
+use crate::{Component, ComponentRegistry, EcsId, EcsIds, SnapshotError, World};
+
+#[derive(Copy, Clone, PartialEq, Debug, Component)]
+struct Position(f32, f32);
+
+#[derive(Component)]
+struct Unregistered;
+
+fn get<T: Component>(world: &mut World, entity: EcsId) -> &T {
+    let comp_id = world.get_or_create_type_id_ecsid::<T>();
+    let ptr = world.get_component_mut_dynamic(entity, comp_id).unwrap();
+    unsafe { &*(ptr as *mut T) }
+}
+
+fn registry() -> ComponentRegistry {
+    let mut registry = ComponentRegistry::new();
+    registry.register::<u32>("u32");
+    registry.register::<String>("String");
+    registry.register::<EcsId>("EcsId");
+    // Safe because every bit pattern is a valid ``Position``
+    unsafe { registry.register_raw::<Position>("Position") };
+    registry
+}
+
+#[test]
+fn round_trip() {
+    let mut world = World::new();
+    let e1 = world.spawn().with(10_u32).with(String::from("e1")).build();
+    let dead = world.spawn().with(20_u32).build();
+    let e2 = world.spawn().with(30_u32).with(Position(1.0, 2.0)).build();
+    world.despawn(dead);
+    // Reuses the index of ``dead`` so the generation is no longer 0
+    let e3 = world.spawn().with(e1).with(String::from("e3")).build();
+    assert!(e3.index() == dead.index() && e3 != dead);
+
+    let registry = registry();
+    let data = world.save_snapshot(&registry).unwrap();
+    let mut loaded = World::load_snapshot(&data, &registry).unwrap();
+
+    assert!(loaded.is_alive(e1));
+    assert!(loaded.is_alive(e2));
+    assert!(loaded.is_alive(e3));
+    assert!(loaded.is_alive(dead) == false);
+
+    assert!(*get::<u32>(&mut loaded, e1) == 10);
+    assert!(get::<String>(&mut loaded, e1) == "e1");
+    assert!(*get::<Position>(&mut loaded, e2) == Position(1.0, 2.0));
+    assert!(*get::<EcsId>(&mut loaded, e3) == e1);
+
+    let mut query = loaded.query::<(EcsIds, &u32)>();
+    let found = query.iter().map(|(e, data)| (e, *data)).collect::<Vec<_>>();
+    assert!(found == [(e1, 10), (e2, 30)]);
+    drop(query);
+
+    // The despawned list is kept so spawning continues where the original world would have
+    assert!(world.spawn().build() == loaded.spawn().build());
+    assert!(
+        world.get_or_create_type_id_ecsid::<u32>() == loaded.get_or_create_type_id_ecsid::<u32>()
+    );
+}
+
+#[test]
+fn round_trip_raw_components() {
+    let mut world = World::new();
+    let tag = world.spawn().build();
+    let raw =
+        unsafe { world.spawn_with_component_meta(ComponentMeta::from_size_align(8, 8)) }.build();
+    let mut data = 0x0102_0304_0506_0708_u64;
+    let e1 = unsafe {
+        world
+            .spawn()
+            .with_dynamic(tag)
+            .with_dynamic_with_data(&mut data as *mut u64 as *mut u8, raw)
+            .build()
+    };
+
+    let registry = registry();
+    let snapshot = world.save_snapshot(&registry).unwrap();
+    let mut loaded = World::load_snapshot(&snapshot, &registry).unwrap();
+
+    assert!(loaded.has_component_dynamic(e1, tag));
+    let ptr = loaded.get_component_mut_dynamic(e1, raw).unwrap();
+    assert!(unsafe { *(ptr as *mut u64) } == data);
+}
+
+#[test]
+fn round_trip_hierarchy() {
+    let mut world = World::new();
+    let parent = world.spawn().build();
+    let child = world.spawn().with(1_u32).build();
+    world.set_parent(child, parent);
+
+    let registry = registry();
+    let data = world.save_snapshot(&registry).unwrap();
+    let loaded = World::load_snapshot(&data, &registry).unwrap();
+
+    assert!(loaded.parent(child) == Some(parent));
+    assert!(loaded.children(parent).collect::<Vec<_>>() == [child]);
+}
+
+#[test]
+fn round_trip_retired() {
+    let mut world = World::new();
+    world.entities.generations.push((false, MAX_GENERATION));
+    world.entities.retired += 1;
+    let e1 = world.spawn().with(10_u32).build();
+
+    let registry = registry();
+    let data = world.save_snapshot(&registry).unwrap();
+    let loaded = World::load_snapshot(&data, &registry).unwrap();
+
+    assert!(loaded.is_alive(e1));
+    assert!(loaded.entity_counters().retired_indices == 1);
+    assert!(loaded.entity_counters().highest_generation == MAX_GENERATION);
+    assert!(loaded.entities.despawned.is_empty());
+}
+
+#[test]
+fn unregistered_component() {
+    let mut world = World::new();
+    world.spawn().with(Unregistered).build();
+    let unregistered = world.get_or_create_type_id_ecsid::<Unregistered>();
+
+    let result = world.save_snapshot(&registry());
+    assert!(result == Err(SnapshotError::UnregisteredComponent(unregistered)));
+}
+
+#[test]
+fn load_errors() {
+    let mut world = World::new();
+    world.spawn().with(10_u32).build();
+    let data = world.save_snapshot(&registry()).unwrap();
+
+    let truncated = World::load_snapshot(&data[..data.len() - 1], &registry());
+    assert!(matches!(truncated, Err(SnapshotError::UnexpectedEof)));
+
+    let missing = World::load_snapshot(&data, &ComponentRegistry::new());
+    assert!(matches!(missing, Err(SnapshotError::UnknownComponentName(name)) if name == "u32"));
+
+    let garbage = World::load_snapshot(b"not a snapshot", &registry());
+    assert!(matches!(garbage, Err(SnapshotError::InvalidData(_))));
+}
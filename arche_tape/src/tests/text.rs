@@ -0,0 +1,204 @@
+use crate::text::{parse_text, EntityMap};
+use crate::world::ComponentMeta;
+use crate::{
+    Component, ComponentRegistry, EcsId, EcsIds, TextComponent, TextError, TextValue, World,
+};
+
+#[derive(Debug, PartialEq, Component)]
+struct Position {
+    x: f32,
+    y: f32,
+}
+
+impl TextComponent for Position {
+    fn to_text(&self, _: &EntityMap) -> Result<TextValue, TextError> {
+        Ok(TextValue::Struct(vec![
+            ("x".to_owned(), TextValue::Float(self.x as f64)),
+            ("y".to_owned(), TextValue::Float(self.y as f64)),
+        ]))
+    }
+
+    fn from_text(value: &TextValue, entities: &EntityMap) -> Result<Self, TextError> {
+        let field = |name| {
+            value
+                .field(name)
+                .ok_or_else(|| TextError::InvalidValue(format!("missing field {}", name)))
+        };
+        Ok(Position {
+            x: f32::from_text(field("x")?, entities)?,
+            y: f32::from_text(field("y")?, entities)?,
+        })
+    }
+}
+
+fn get<T: Component>(world: &mut World, entity: EcsId) -> &T {
+    let comp_id = world.get_or_create_type_id_ecsid::<T>();
+    let ptr = world.get_component_mut_dynamic(entity, comp_id).unwrap();
+    unsafe { &*(ptr as *mut T) }
+}
+
+fn registry() -> ComponentRegistry {
+    let mut registry = ComponentRegistry::new();
+    registry.register_text::<u32>("u32");
+    registry.register_text::<String>("Name");
+    registry.register_text::<EcsId>("Target");
+    registry.register_text::<Position>("Position");
+    registry
+}
+
+#[test]
+fn parse_values() {
+    let value = parse_text(
+        r#"{
+            "a": [1, -2, 3.5, 1e3], // comment
+            #4: (x: true, y: "q\"uote\n"),
+            (#1, "b"): (),
+        }"#,
+    )
+    .unwrap();
+
+    assert!(
+        value
+            == TextValue::Map(vec![
+                (
+                    TextValue::Str("a".to_owned()),
+                    TextValue::List(vec![
+                        TextValue::Int(1),
+                        TextValue::Int(-2),
+                        TextValue::Float(3.5),
+                        TextValue::Float(1000.0),
+                    ])
+                ),
+                (
+                    TextValue::Entity(4),
+                    TextValue::Struct(vec![
+                        ("x".to_owned(), TextValue::Bool(true)),
+                        ("y".to_owned(), TextValue::Str("q\"uote\n".to_owned())),
+                    ])
+                ),
+                (
+                    TextValue::Tuple(vec![TextValue::Entity(1), TextValue::Str("b".to_owned())]),
+                    TextValue::unit()
+                ),
+            ])
+    );
+
+    assert!(matches!(
+        parse_text("[1,\n 2,\n ?]"),
+        Err(TextError::Syntax { line: 3, .. })
+    ));
+    assert!(matches!(parse_text("[1] 2"), Err(TextError::Syntax { .. })));
+}
+
+#[test]
+fn round_trip() {
+    let mut world = World::new();
+    let tag = world.spawn().build();
+    let e1 = world
+        .spawn()
+        .with(10_u32)
+        .with(String::from("e1"))
+        .with_dynamic(tag)
+        .build();
+    let e2 = world
+        .spawn()
+        .with(Position { x: 1.5, y: -2.0 })
+        .with(e1)
+        .build();
+    world.set_parent(e2, e1);
+
+    let registry = registry();
+    let text = world.save_text(&registry).unwrap();
+
+    let mut loaded = World::new();
+    let spawned = loaded.load_text(&text, &registry).unwrap();
+    let [new_tag, new_e1, new_e2] = [spawned[0], spawned[1], spawned[2]];
+
+    assert!(*get::<u32>(&mut loaded, new_e1) == 10);
+    assert!(get::<String>(&mut loaded, new_e1) == "e1");
+    assert!(loaded.has_component_dynamic(new_e1, new_tag));
+    assert!(*get::<Position>(&mut loaded, new_e2) == Position { x: 1.5, y: -2.0 });
+    assert!(*get::<EcsId>(&mut loaded, new_e2) == new_e1);
+    assert!(loaded.parent(new_e2) == Some(new_e1));
+
+    // Saving the loaded world gives back the same text
+    assert!(loaded.save_text(&registry).unwrap() == text);
+}
+
+#[test]
+fn load_remaps_entities() {
+    let text = r#"[
+        (id: #0, components: {"Name": "parent"}),
+        (
+            id: #1,
+            components: {
+                "Target": #0,
+                ("ChildOf", #0): (),
+            },
+        ),
+    ]"#;
+
+    let mut world = World::new();
+    let existing = world.spawn().with(1_u32).build();
+    let first = world.load_text(text, &registry()).unwrap();
+    let second = world.load_text(text, &registry()).unwrap();
+
+    assert!(first.iter().chain(second.iter()).all(|&e| e != existing));
+    assert!(first != second);
+    for spawned in [first, second] {
+        assert!(*get::<EcsId>(&mut world, spawned[1]) == spawned[0]);
+        assert!(world.parent(spawned[1]) == Some(spawned[0]));
+    }
+
+    let mut query = world.query::<(EcsIds, &String)>();
+    assert!(query.iter().count() == 2);
+}
+
+#[test]
+fn raw_components() {
+    let mut world = World::new();
+    let raw =
+        unsafe { world.spawn_with_component_meta(ComponentMeta::from_size_align(4, 4)) }.build();
+    let mut data = 0x0403_0201_u32;
+    let e1 = unsafe {
+        world
+            .spawn()
+            .with_dynamic_with_data(&mut data as *mut u32 as *mut u8, raw)
+            .build()
+    };
+
+    let registry = registry();
+    let text = world.save_text(&registry).unwrap();
+    assert!(text.contains("layout: (4, 4)"));
+
+    let mut loaded = World::new();
+    let spawned = loaded.load_text(&text, &registry).unwrap();
+    assert!(spawned.len() == 2);
+    let ptr = loaded
+        .get_component_mut_dynamic(spawned[1], spawned[0])
+        .unwrap();
+    assert!(unsafe { *(ptr as *mut u32) } == data);
+    assert!(world.is_alive(e1));
+}
+
+#[test]
+fn load_errors() {
+    let registry = registry();
+    let mut world = World::new();
+    let existing = world.spawn().build();
+
+    let unknown_name = world.load_text(r#"[(id: #0, components: {"Velocity": 1})]"#, &registry);
+    assert!(unknown_name == Err(TextError::UnknownComponentName("Velocity".to_owned())));
+
+    let unknown_entity = world.load_text(r#"[(id: #0, components: {"Target": #5})]"#, &registry);
+    assert!(unknown_entity == Err(TextError::UnknownEntity(5)));
+
+    let bad_value = world.load_text(r#"[(id: #0, components: {"u32": -1})]"#, &registry);
+    assert!(matches!(bad_value, Err(TextError::InvalidValue(_))));
+
+    // Failed loads must not leave any entities behind
+    let next = world.spawn().build();
+    let mut query = world.query::<(EcsIds,)>();
+    let alive = query.iter().map(|(e,)| e).collect::<Vec<_>>();
+    assert!(alive == [existing, next]);
+}
@@ -1,4 +1,4 @@
-use crate::{spawn, world::ComponentMeta, EcsId, World};
+use crate::{spawn, world::ComponentMeta, Component, EcsId, EcsIds, Without, World};
 
 #[test]
 pub fn get() {
@@ -41,7 +41,7 @@ pub fn add_component() {
     // The two component entities
     assert!(world.archetypes[0].entities.len() == 2);
     assert!(world.archetypes[0].component_storages.len() == 0);
-    for (_, lock) in world.archetypes[0].component_storages.iter_mut() {
+    for (_, lock, _) in world.archetypes[0].component_storages.iter_mut() {
         let storage = lock.get_mut();
         assert!(storage.len() == 0);
     }
@@ -49,7 +49,7 @@ pub fn add_component() {
     // The first archetype entity was in
     assert!(world.archetypes[1].entities.len() == 0);
     assert!(world.archetypes[1].component_storages.len() == 1);
-    for (_, lock) in world.archetypes[1].component_storages.iter_mut() {
+    for (_, lock, _) in world.archetypes[1].component_storages.iter_mut() {
         let storage = lock.get_mut();
         assert!(storage.len() == 0);
     }
@@ -57,7 +57,7 @@ pub fn add_component() {
     // The current archetype entity was in
     assert!(world.archetypes[2].entities.len() == 1);
     assert!(world.archetypes[2].component_storages.len() == 2);
-    for (_, lock) in world.archetypes[2].component_storages.iter_mut() {
+    for (_, lock, _) in world.archetypes[2].component_storages.iter_mut() {
         let storage = lock.get_mut();
         assert!(storage.len() == 1);
     }
@@ -117,7 +117,9 @@ pub fn add_component_then_spawn() {
 
 #[test]
 pub fn add_two() {
+    #[derive(Component)]
     struct A(f32);
+    #[derive(Component)]
     struct B(f32);
 
     let mut world = World::new();
@@ -172,7 +174,9 @@ pub fn add_two() {
 
 #[test]
 pub fn add_multiple() {
+    #[derive(Component)]
     struct A(f32);
+    #[derive(Component)]
     struct B(f32);
 
     let mut world = World::new();
@@ -213,8 +217,6 @@ pub fn despawn_meta_update() {
 
 #[test]
 pub fn despawn_component_entity() {
-    // TODO: Removing entities when they despawn not yet implemented
-    return;
     let mut world = World::new();
 
     unsafe {
@@ -255,6 +257,83 @@ pub fn despawn_component_entity() {
     }
 }
 
+#[test]
+pub fn despawn_component_entity_drops_data() {
+    use std::sync::Arc;
+
+    let mut world = World::new();
+    let arc = Arc::new(());
+
+    unsafe {
+        let component_entity = world
+            .spawn_with_component_meta(ComponentMeta::from_generic::<Arc<()>>())
+            .build();
+
+        for _ in 0..3 {
+            let mut data = core::mem::ManuallyDrop::new(arc.clone());
+            world
+                .spawn()
+                .with(10_u32)
+                .with_dynamic_with_data(&mut data as *mut _ as *mut _, component_entity)
+                .build();
+        }
+        assert!(Arc::strong_count(&arc) == 4);
+
+        assert!(world.despawn(component_entity));
+        assert!(Arc::strong_count(&arc) == 1);
+        assert!(world.lock_lookup.contains_key(&component_entity) == false);
+    }
+
+    let mut run_times = 0;
+    for (data,) in world.query::<(&u32,)>().iter() {
+        assert!(*data == 10);
+        run_times += 1;
+    }
+    assert!(run_times == 3);
+}
+
+#[test]
+pub fn despawn_tag_then_reuse_index() {
+    let mut world = World::new();
+
+    let tag = world.spawn().build();
+    let e1 = world.spawn().with(1_u32).with_dynamic(tag).build();
+    let e2 = world.spawn().with(2_u32).with_dynamic(tag).build();
+
+    assert!(world.despawn(tag));
+    assert!(world.is_alive(e1));
+    assert!(world.is_alive(e2));
+
+    // The new tag reuses the index of the despawned tag
+    let new_tag = world.spawn().build();
+    assert!(new_tag.uindex() == tag.uindex());
+
+    world.add_component_dynamic(e1, new_tag);
+
+    let mut q = world.query_dynamic([crate::FetchType::EcsId, crate::FetchType::Immut(new_tag)]);
+    let found = q
+        .iter()
+        .map(|[e, _]| unsafe { *(e as *mut EcsId) })
+        .collect::<Vec<_>>();
+    assert!(found == [e1]);
+}
+
+#[test]
+pub fn despawn_type_component_entity() {
+    let mut world = World::new();
+
+    let entity = spawn!(&mut world, 10_u32, 12_u64);
+    let u32_id = world.get_or_create_type_id_ecsid::<u32>();
+
+    assert!(world.despawn(u32_id));
+    assert!(world.has_component::<u32>(entity) == false);
+    assert!(world.has_component::<u64>(entity));
+
+    world.add_component(entity, 14_u32);
+    let mut q = world.query::<(&u32, &u64)>();
+    assert!(q.get(entity).unwrap() == (&14, &12));
+}
+
 // TODO: Boxy can you make the following tests actually work?
 // Currently they basically just want to not panic, but they should check capacity if possible
 #[test]
@@ -277,3 +356,297 @@ pub fn spawn_with_capacity_zero() -> () {
     let entity = world.spawn_with_capacity(0).build();
     assert_eq!(entity, EcsId::new(0, 0));
 }
+
+#[test]
+pub fn take_component() {
+    let mut world = World::new();
+    let arc = std::sync::Arc::new(10_u32);
+    let e1 = spawn!(&mut world, 1_u32, arc.clone());
+    let e2 = spawn!(&mut world, 2_u32, arc.clone());
+
+    let taken = world.take_component::<std::sync::Arc<u32>>(e1).unwrap();
+    assert!(std::sync::Arc::strong_count(&arc) == 3);
+    assert!(!world.has_component::<std::sync::Arc<u32>>(e1));
+    assert!(world.take_component::<std::sync::Arc<u32>>(e1).is_none());
+    assert!(world.take_component::<u64>(e1).is_none());
+    drop(taken);
+    assert!(std::sync::Arc::strong_count(&arc) == 2);
+
+    // The entity that got swapped into e1's row still has its components
+    let mut q = world.query::<(&u32, &std::sync::Arc<u32>)>();
+    assert!(*q.get(e2).unwrap().0 == 2);
+    drop(q);
+    assert!(*world.query::<(&u32,)>().get(e1).unwrap().0 == 1);
+}
+
+#[test]
+pub fn take_component_dynamic_between_worlds() {
+    let mut world_1 = World::new();
+    let mut world_2 = World::new();
+    let e1 = spawn!(&mut world_1, 1_u32, String::from("Hello"));
+    let e2 = world_2.spawn().build();
+
+    let id_1 = world_1.get_or_create_type_id_ecsid::<String>();
+    let id_2 = world_2.get_or_create_type_id_ecsid::<String>();
+    let mut buffer = vec![0_u8; std::mem::size_of::<String>()];
+    unsafe {
+        assert!(world_1.take_component_dynamic(e1, id_1, buffer.as_mut_ptr()));
+        world_2.add_component_dynamic_with_data(e2, id_2, buffer.as_mut_ptr());
+    }
+
+    assert!(!world_1.has_component::<String>(e1));
+    assert!(world_1.has_component::<u32>(e1));
+    assert!(world_2.query::<(&String,)>().get(e2).unwrap().0 == "Hello");
+}
+
+#[test]
+pub fn abandoned_entity_builder() {
+    let mut world = World::new();
+    let arc = std::sync::Arc::new(());
+    // Component ids are entities too so create them up front
+    spawn!(&mut world, 1_u32, arc.clone(), 1_u8, String::new());
+    let alive = world.entity_counters().alive;
+
+    let builder = world.spawn().with(1_u32).with(arc.clone()).with(1_u8);
+    drop(builder);
+    assert!(world.entity_counters().alive == alive);
+    assert!(std::sync::Arc::strong_count(&arc) == 2);
+
+    world
+        .spawn()
+        .with(arc.clone())
+        .with(String::from("Hello"))
+        .cancel();
+    assert!(std::sync::Arc::strong_count(&arc) == 2);
+    assert!(world.entity_counters().alive == alive);
+
+    // The freed index is reused with the next generation
+    let e1 = world.spawn().build();
+    world.despawn(e1);
+    world.spawn().with(arc.clone()).cancel();
+    let e2 = world.spawn().build();
+    assert!(e2.uindex() == e1.uindex() && e2 != e1);
+    assert!(world.query::<(&u32,)>().iter().count() == 1);
+}
+
+#[test]
+pub fn duplicate_component_entity_builder() {
+    let mut world = World::new();
+    let arc = std::sync::Arc::new(());
+    spawn!(&mut world, 1_u32, arc.clone());
+    let alive = world.entity_counters().alive;
+
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        world
+            .spawn()
+            .with(arc.clone())
+            .with(1_u32)
+            .with(2_u32)
+            .build();
+    }));
+    assert!(result.is_err());
+    assert!(std::sync::Arc::strong_count(&arc) == 2);
+    assert!(world.entity_counters().alive == alive);
+    assert!(world.query::<(&u32,)>().iter().count() == 1);
+}
+
+#[test]
+pub fn add_component_to_query() {
+    let mut world = World::new();
+    let e1 = spawn!(&mut world, 1_u32);
+    let e2 = spawn!(&mut world, 2_u32, 1_u8);
+    let e3 = spawn!(&mut world, 3_u32, 10_u64);
+    let e4 = spawn!(&mut world, 1_u8);
+
+    world.add_component_to_query::<(&u32,), _>(20_u64);
+
+    let mut query = world.query::<(EcsIds, &u32, &u64)>();
+    let mut entities = query
+        .iter()
+        .map(|(e, &a, &b)| (e, a, b))
+        .collect::<Vec<_>>();
+    entities.sort();
+    assert!(entities == [(e1, 1, 20), (e2, 2, 20), (e3, 3, 20)]);
+    drop(query);
+    assert!(!world.has_component::<u64>(e4));
+
+    // Every entity keeps its other components and can still be found through its meta
+    assert!(*world.query::<(&u8,)>().get(e2).unwrap().0 == 1);
+    world.despawn(e1);
+    assert!(*world.query::<(&u32,)>().get(e2).unwrap().0 == 2);
+}
+
+#[test]
+pub fn add_component_to_query_drops_replaced() {
+    let mut world = World::new();
+    let old = std::sync::Arc::new(());
+    let new = std::sync::Arc::new(());
+    let e1 = spawn!(&mut world, 1_u32, old.clone());
+    let e2 = spawn!(&mut world, 2_u32, 1_u8);
+    spawn!(&mut world, 1_u8);
+
+    world.add_component_to_query::<(&u32,), _>(new.clone());
+    assert!(std::sync::Arc::strong_count(&old) == 1);
+    assert!(std::sync::Arc::strong_count(&new) == 3);
+
+    let mut query = world.query::<(&std::sync::Arc<()>,)>();
+    assert!(std::sync::Arc::ptr_eq(query.get(e1).unwrap().0, &new));
+    assert!(std::sync::Arc::ptr_eq(query.get(e2).unwrap().0, &new));
+    drop(query);
+
+    world.remove_component_from_query::<(&u8,), std::sync::Arc<()>>();
+    assert!(std::sync::Arc::strong_count(&new) == 2);
+    assert!(world.has_component::<std::sync::Arc<()>>(e1));
+}
+
+#[test]
+pub fn remove_component_from_query() {
+    let mut world = World::new();
+    let e1 = spawn!(&mut world, 1_u32, 1_u64);
+    let e2 = spawn!(&mut world, 2_u32, 2_u64, 1_u8);
+    let e3 = spawn!(&mut world, 3_u32, 3_u64, 1_u16);
+    let e4 = spawn!(&mut world, 4_u64);
+
+    world.remove_component_from_query::<(&u32, Without<u16>), u64>();
+    assert!(!world.has_component::<u64>(e1));
+    assert!(!world.has_component::<u64>(e2));
+    assert!(world.has_component::<u64>(e3));
+    assert!(world.has_component::<u64>(e4));
+
+    let mut query = world.query::<(EcsIds, &u32)>();
+    let mut entities = query.iter().map(|(e, &n)| (e, n)).collect::<Vec<_>>();
+    entities.sort();
+    assert!(entities == [(e1, 1), (e2, 2), (e3, 3)]);
+    drop(query);
+
+    // Removing a component that was never created does nothing
+    world.remove_component_from_query::<(&u32,), String>();
+}
+
+#[test]
+#[should_panic(
+    expected = "Queries used to change components in bulk cannot contain Added or Changed"
+)]
+pub fn add_component_to_query_added() {
+    let mut world = World::new();
+    spawn!(&mut world, 1_u32);
+    world.add_component_to_query::<(crate::Added<u32>,), _>(1_u64);
+}
+
+#[test]
+pub fn add_component_to_query_panicking_clone() {
+    #[derive(Component)]
+    struct PanicClone;
+    impl Clone for PanicClone {
+        fn clone(&self) -> Self {
+            panic!("clone");
+        }
+    }
+
+    let mut world = World::new();
+    let e1 = spawn!(&mut world, 1_u32);
+    let e2 = spawn!(&mut world, 2_u32);
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        world.add_component_to_query::<(&u32,), _>(PanicClone);
+    }));
+    assert!(result.is_err());
+
+    assert!(!world.has_component::<PanicClone>(e1));
+    let mut query = world.query::<(&u32,)>();
+    assert!(*query.get(e1).unwrap().0 == 1);
+    assert!(*query.get(e2).unwrap().0 == 2);
+    drop(query);
+    assert!(world.despawn(e1) && world.despawn(e2));
+}
+
+#[test]
+pub fn archetype_lookup() {
+    let mut world = World::new();
+    let e1 = spawn!(&mut world, 1_u32, 1_u64);
+    let e2 = spawn!(&mut world, 2_u32);
+    let archetypes = world.archetypes.len();
+
+    // Reaching the same set of components through a different path finds the existing archetype
+    world.add_component(e2, 2_u64);
+    assert!(world.archetypes.len() == archetypes);
+    assert!(
+        world.get_entity_meta(e1).unwrap().instance_meta.archetype.0
+            == world.get_entity_meta(e2).unwrap().instance_meta.archetype.0
+    );
+
+    // Edges are added in both directions
+    let u64_id = world.get_or_create_type_id_ecsid::<u64>();
+    let with = world.get_entity_meta(e2).unwrap().instance_meta.archetype.0;
+    let u32_id = world.get_or_create_type_id_ecsid::<u32>();
+    let without = world.archetype_lookup[&vec![u32_id]].0;
+    assert!(world.archetypes[without].try_find_next_archetype(u64_id) == Some(with));
+    assert!(world.archetypes[with].try_find_next_archetype(u64_id) == Some(without));
+
+    world.remove_component::<u64>(e1);
+    assert!(world.get_entity_meta(e1).unwrap().instance_meta.archetype.0 == without);
+    assert!(world.archetypes.len() == archetypes);
+}
+
+#[test]
+pub fn compact_archetypes() {
+    let mut world = World::new();
+    let e1 = spawn!(&mut world, 1_u32, 1_u8);
+    let e2 = spawn!(&mut world, 2_u32, 2_u64);
+    let e3 = spawn!(&mut world, 3_u32, 3_u64, 3_u16);
+    world.remove_component::<u8>(e1);
+    world.remove_component::<u16>(e3);
+    let archetypes = world.archetypes.len();
+
+    // {u32, u8} and {u32, u64, u16} are now empty
+    world.compact_archetypes();
+    assert!(world.archetypes.len() == archetypes - 2);
+    assert!(world
+        .archetypes
+        .iter()
+        .all(|archetype| !archetype.entities.is_empty() || archetype.comp_ids.is_empty()));
+
+    let mut query = world.query::<(EcsIds, &u32, Option<&u64>)>();
+    let mut entities = query
+        .iter()
+        .map(|(e, &a, b)| (e, a, b.copied()))
+        .collect::<Vec<_>>();
+    entities.sort();
+    assert!(entities == [(e1, 1, None), (e2, 2, Some(2)), (e3, 3, Some(3))]);
+    drop(query);
+    assert!(world.query::<(Without<u64>, &u32)>().iter().count() == 1);
+
+    // Edges to removed archetypes are gone so the archetypes get created again
+    world.add_component(e1, 1_u8);
+    world.add_component(e2, 2_u16);
+    assert!(world.archetypes.len() == archetypes);
+    assert!(*world.query::<(&u8,)>().get(e1).unwrap().0 == 1);
+    assert!(*world.query::<(&u16,)>().get(e2).unwrap().0 == 2);
+
+    world.despawn(e3);
+    world.compact_archetypes();
+    world.remove_component::<u16>(e2);
+    let mut query = world.query::<(EcsIds, &u32, &u64)>();
+    assert!(query.iter().map(|(e, ..)| e).collect::<Vec<_>>() == [e2]);
+}
+
+#[test]
+pub fn compaction_threshold() {
+    let mut world = World::new();
+    let e1 = spawn!(&mut world, 1_u32, 1_u8);
+    let e2 = spawn!(&mut world, 2_u32, 1_u16);
+    world.set_archetype_compaction_threshold(Some(2));
+
+    let mut commands = crate::Commands::new();
+    commands.remove_component::<u8>(e1);
+    world.apply_commands(&mut commands);
+    let archetypes = world.archetypes.len();
+    assert!(world
+        .archetypes
+        .iter()
+        .any(|archetype| archetype.entities.is_empty()));
+
+    commands.remove_component::<u16>(e2);
+    world.apply_commands(&mut commands);
+    assert!(world.archetypes.len() == archetypes - 2);
+    assert!(world.query::<(&u32,)>().iter().count() == 2);
+}
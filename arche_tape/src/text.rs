@@ -0,0 +1,918 @@
+use crate::entity_builder::EntityBuilder;
+use crate::registry::{ComponentRegistry, RegistryEntry};
+use crate::world::{ArchIndex, ComponentMeta, EntityMeta, InstanceMeta};
+use crate::{Component, EcsId, World};
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::mem::ManuallyDrop;
+
+/// A value in the text format, the syntax is similar to RON:
+///
+/// ``true``, ``10``, ``-1.5``, ``"text"``, ``#3`` (an entity), ``[a, b]``, ``(a, b)``, ``(x: a, y: b)`` and ``{a: b}``
+#[derive(Clone, Debug, PartialEq)]
+pub enum TextValue {
+    Bool(bool),
+    Int(i128),
+    Float(f64),
+    Str(String),
+    /// A reference to an entity by its label in the file
+    Entity(u64),
+    List(Vec<TextValue>),
+    /// An empty tuple is used for components with no data
+    Tuple(Vec<TextValue>),
+    Struct(Vec<(String, TextValue)>),
+    Map(Vec<(TextValue, TextValue)>),
+}
+
+impl TextValue {
+    pub fn unit() -> Self {
+        TextValue::Tuple(Vec::new())
+    }
+
+    /// Gets the field called ``name`` if this is a struct
+    pub fn field(&self, name: &str) -> Option<&TextValue> {
+        match self {
+            TextValue::Struct(fields) => fields.iter().find(|(n, _)| n == name).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TextError {
+    Syntax {
+        line: usize,
+        message: String,
+    },
+    /// The component has a drop_fn or a rust type but was not registered in the ComponentRegistry
+    UnregisteredComponent(EcsId),
+    /// The text refers to a component name that has no text functions registered
+    UnknownComponentName(String),
+    /// The text refers to an entity label that isn't defined in the file
+    UnknownEntity(u64),
+    InvalidValue(String),
+}
+
+impl std::fmt::Display for TextError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TextError::Syntax { line, message } => write!(f, "line {}: {}", line, message),
+            TextError::UnregisteredComponent(id) => {
+                write!(f, "component {} is not registered", id)
+            }
+            TextError::UnknownComponentName(name) => {
+                write!(f, "no component registered with the name {}", name)
+            }
+            TextError::UnknownEntity(label) => write!(f, "entity #{} is not defined", label),
+            TextError::InvalidValue(message) => write!(f, "invalid value: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for TextError {}
+
+fn invalid(expected: &str, found: &TextValue) -> TextError {
+    TextError::InvalidValue(format!("expected {}, found {:?}", expected, found))
+}
+
+/// Maps between the EcsIds in a World and the labels used for them in a text file
+pub struct EntityMap {
+    labels: HashMap<EcsId, u64>,
+    entities: HashMap<u64, EcsId>,
+}
+
+impl EntityMap {
+    fn new() -> Self {
+        Self {
+            labels: HashMap::new(),
+            entities: HashMap::new(),
+        }
+    }
+
+    fn insert(&mut self, entity: EcsId, label: u64) {
+        self.labels.insert(entity, label);
+        self.entities.insert(label, entity);
+    }
+
+    pub fn label(&self, entity: EcsId) -> Option<u64> {
+        self.labels.get(&entity).copied()
+    }
+
+    pub fn entity(&self, label: u64) -> Option<EcsId> {
+        self.entities.get(&label).copied()
+    }
+}
+
+/// A component that can be written to and read from the text format, EcsIds inside the component should go through the ``EntityMap``
+/// so that they get remapped when loading
+pub trait TextComponent: Component + Sized {
+    fn to_text(&self, entities: &EntityMap) -> Result<TextValue, TextError>;
+    fn from_text(value: &TextValue, entities: &EntityMap) -> Result<Self, TextError>;
+}
+
+macro_rules! impl_text_component_int {
+    ($($T:ty)*) => {
+        $(
+            impl TextComponent for $T {
+                fn to_text(&self, _: &EntityMap) -> Result<TextValue, TextError> {
+                    Ok(TextValue::Int(*self as i128))
+                }
+
+                fn from_text(value: &TextValue, _: &EntityMap) -> Result<Self, TextError> {
+                    use std::convert::TryFrom;
+                    match value {
+                        TextValue::Int(n) => <$T>::try_from(*n).map_err(|_| invalid(stringify!($T), value)),
+                        _ => Err(invalid(stringify!($T), value)),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_text_component_int!(u8 u16 u32 u64 usize i8 i16 i32 i64 i128);
+
+macro_rules! impl_text_component_float {
+    ($($T:ty)*) => {
+        $(
+            impl TextComponent for $T {
+                fn to_text(&self, _: &EntityMap) -> Result<TextValue, TextError> {
+                    Ok(TextValue::Float(*self as f64))
+                }
+
+                fn from_text(value: &TextValue, _: &EntityMap) -> Result<Self, TextError> {
+                    match value {
+                        TextValue::Float(n) => Ok(*n as $T),
+                        TextValue::Int(n) => Ok(*n as $T),
+                        _ => Err(invalid(stringify!($T), value)),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_text_component_float!(f32 f64);
+
+impl TextComponent for bool {
+    fn to_text(&self, _: &EntityMap) -> Result<TextValue, TextError> {
+        Ok(TextValue::Bool(*self))
+    }
+
+    fn from_text(value: &TextValue, _: &EntityMap) -> Result<Self, TextError> {
+        match value {
+            TextValue::Bool(b) => Ok(*b),
+            _ => Err(invalid("bool", value)),
+        }
+    }
+}
+
+impl TextComponent for String {
+    fn to_text(&self, _: &EntityMap) -> Result<TextValue, TextError> {
+        Ok(TextValue::Str(self.clone()))
+    }
+
+    fn from_text(value: &TextValue, _: &EntityMap) -> Result<Self, TextError> {
+        match value {
+            TextValue::Str(s) => Ok(s.clone()),
+            _ => Err(invalid("string", value)),
+        }
+    }
+}
+
+impl TextComponent for EcsId {
+    fn to_text(&self, entities: &EntityMap) -> Result<TextValue, TextError> {
+        match entities.label(*self) {
+            Some(label) => Ok(TextValue::Entity(label)),
+            None => Err(TextError::InvalidValue(format!(
+                "entity {} is not being saved",
+                self
+            ))),
+        }
+    }
+
+    fn from_text(value: &TextValue, entities: &EntityMap) -> Result<Self, TextError> {
+        match value {
+            TextValue::Entity(label) => entities
+                .entity(*label)
+                .ok_or(TextError::UnknownEntity(*label)),
+            _ => Err(invalid("entity", value)),
+        }
+    }
+}
+
+impl<T: TextComponent> TextComponent for Vec<T> {
+    fn to_text(&self, entities: &EntityMap) -> Result<TextValue, TextError> {
+        self.iter()
+            .map(|data| data.to_text(entities))
+            .collect::<Result<_, _>>()
+            .map(TextValue::List)
+    }
+
+    fn from_text(value: &TextValue, entities: &EntityMap) -> Result<Self, TextError> {
+        match value {
+            TextValue::List(values) => values.iter().map(|v| T::from_text(v, entities)).collect(),
+            _ => Err(invalid("list", value)),
+        }
+    }
+}
+
+fn bytes_to_text(bytes: &[u8]) -> TextValue {
+    match bytes.len() {
+        0 => TextValue::unit(),
+        _ => TextValue::List(bytes.iter().map(|&b| TextValue::Int(b as i128)).collect()),
+    }
+}
+
+fn bytes_from_text(value: &TextValue, size: usize) -> Result<Vec<u8>, TextError> {
+    let expected = || format!("{} bytes", size);
+    match value {
+        TextValue::Tuple(values) if values.is_empty() && size == 0 => Ok(Vec::new()),
+        TextValue::List(values) if values.len() == size && size > 0 => values
+            .iter()
+            .map(|v| match v {
+                TextValue::Int(b) if (0..=255).contains(b) => Ok(*b as u8),
+                _ => Err(invalid(&expected(), value)),
+            })
+            .collect(),
+        _ => Err(invalid(&expected(), value)),
+    }
+}
+
+fn to_text_erased<T: TextComponent>(
+    ptr: *const u8,
+    entities: &EntityMap,
+) -> Result<TextValue, TextError> {
+    let data = unsafe { &*(ptr as *const T) };
+    data.to_text(entities)
+}
+
+fn from_text_erased<T: TextComponent>(
+    value: &TextValue,
+    entities: &EntityMap,
+) -> Result<Box<dyn Any>, TextError> {
+    Ok(Box::new(T::from_text(value, entities)?))
+}
+
+fn to_text_raw<T: Copy + Component>(ptr: *const u8, _: &EntityMap) -> Result<TextValue, TextError> {
+    let bytes = unsafe { std::slice::from_raw_parts(ptr, core::mem::size_of::<T>()) };
+    Ok(bytes_to_text(bytes))
+}
+
+fn from_text_raw<T: Copy + Component>(
+    value: &TextValue,
+    _: &EntityMap,
+) -> Result<Box<dyn Any>, TextError> {
+    let bytes = bytes_from_text(value, core::mem::size_of::<T>())?;
+    // Safe because the caller of register_raw promised that T is plain old data
+    let data = unsafe { core::ptr::read_unaligned(bytes.as_ptr() as *const T) };
+    Ok(Box::new(data))
+}
+
+fn with_boxed<'a, T: Component>(
+    builder: EntityBuilder<'a>,
+    data: Box<dyn Any>,
+    comp_id: EcsId,
+) -> EntityBuilder<'a> {
+    let mut data = ManuallyDrop::new(*data.downcast::<T>().unwrap());
+    // Safe because comp_id is always the id of T or a pair with T as the relation
+    unsafe { builder.with_dynamic_with_data(&mut *data as *mut T as *mut u8, comp_id) }
+}
+
+type FromTextFn = fn(&TextValue, &EntityMap) -> Result<Box<dyn Any>, TextError>;
+
+#[derive(Clone, Copy)]
+pub(crate) struct TextFns {
+    to_text: fn(*const u8, &EntityMap) -> Result<TextValue, TextError>,
+    from_text: FromTextFn,
+    with_boxed: for<'a> fn(EntityBuilder<'a>, Box<dyn Any>, EcsId) -> EntityBuilder<'a>,
+}
+
+impl TextFns {
+    pub(crate) fn new<T: TextComponent>() -> Self {
+        Self {
+            to_text: to_text_erased::<T>,
+            from_text: from_text_erased::<T>,
+            with_boxed: with_boxed::<T>,
+        }
+    }
+
+    /// # Safety
+    ///
+    ///    Same as ``ComponentRegistry::register_raw``
+    pub(crate) unsafe fn new_raw<T: Copy + Component>() -> Self {
+        Self {
+            to_text: to_text_raw::<T>,
+            from_text: from_text_raw::<T>,
+            with_boxed: with_boxed::<T>,
+        }
+    }
+}
+
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+    line: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: impl Into<String>) -> TextError {
+        TextError::Syntax {
+            line: self.line,
+            message: message.into(),
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.src[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    /// Skips whitespace and ``//`` comments
+    fn skip_whitespace(&mut self) {
+        loop {
+            match self.peek() {
+                Some(c) if c.is_whitespace() => {
+                    self.bump();
+                }
+                Some('/') if self.src[self.pos..].starts_with("//") => {
+                    while !matches!(self.bump(), Some('\n') | None) {}
+                }
+                _ => return,
+            }
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), TextError> {
+        self.skip_whitespace();
+        match self.bump() {
+            Some(c) if c == expected => Ok(()),
+            Some(c) => Err(self.error(format!("expected '{}', found '{}'", expected, c))),
+            None => Err(self.error(format!("expected '{}', found end of file", expected))),
+        }
+    }
+
+    /// Parses values separated by commas until ``end``, a trailing comma is allowed
+    fn parse_seq<T>(
+        &mut self,
+        end: char,
+        mut parse: impl FnMut(&mut Self) -> Result<T, TextError>,
+    ) -> Result<Vec<T>, TextError> {
+        let mut values = Vec::new();
+        loop {
+            self.skip_whitespace();
+            if self.peek() == Some(end) {
+                self.bump();
+                return Ok(values);
+            }
+
+            values.push(parse(self)?);
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => {
+                    self.bump();
+                }
+                Some(c) if c == end => (),
+                _ => return Err(self.error(format!("expected ',' or '{}'", end))),
+            }
+        }
+    }
+
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> &'a str {
+        let start = self.pos;
+        while matches!(self.peek(), Some(c) if f(c)) {
+            self.bump();
+        }
+        &self.src[start..self.pos]
+    }
+
+    fn parse_ident(&mut self) -> Option<&'a str> {
+        match self.peek() {
+            Some(c) if c.is_alphabetic() || c == '_' => {
+                Some(self.take_while(|c| c.is_alphanumeric() || c == '_'))
+            }
+            _ => None,
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<String, TextError> {
+        self.expect('"')?;
+        let mut string = String::new();
+        loop {
+            match self.bump() {
+                Some('"') => return Ok(string),
+                Some('\\') => string.push(match self.bump() {
+                    Some('n') => '\n',
+                    Some('r') => '\r',
+                    Some('t') => '\t',
+                    Some('0') => '\0',
+                    Some('\\') => '\\',
+                    Some('"') => '"',
+                    _ => return Err(self.error("invalid escape in string")),
+                }),
+                Some(c) => string.push(c),
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<TextValue, TextError> {
+        let start = self.pos;
+        let mut prev = None;
+        // Signs are allowed at the start and after the exponent
+        while let Some(c) = self.peek() {
+            let sign = (c == '-' || c == '+') && matches!(prev, None | Some('e') | Some('E'));
+            if !(sign || c.is_ascii_alphanumeric() || c == '.' || c == '_') {
+                break;
+            }
+            prev = self.bump();
+        }
+
+        let text = self.src[start..self.pos].replace('_', "");
+        let value = match text.contains('.') || text.contains('e') || text.contains('E') {
+            true => text.parse().ok().map(TextValue::Float),
+            false => text.parse().ok().map(TextValue::Int),
+        };
+        value.ok_or_else(|| self.error(format!("invalid number '{}'", text)))
+    }
+
+    fn parse_value(&mut self) -> Result<TextValue, TextError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('"') => self.parse_string().map(TextValue::Str),
+            Some('#') => {
+                self.bump();
+                let digits = self.take_while(|c| c.is_ascii_digit());
+                digits
+                    .parse()
+                    .map(TextValue::Entity)
+                    .map_err(|_| self.error("expected an entity label after '#'"))
+            }
+            Some('[') => {
+                self.bump();
+                self.parse_seq(']', Self::parse_value).map(TextValue::List)
+            }
+            Some('{') => {
+                self.bump();
+                let entries = self.parse_seq('}', |parser| {
+                    let key = parser.parse_value()?;
+                    parser.expect(':')?;
+                    Ok((key, parser.parse_value()?))
+                })?;
+                Ok(TextValue::Map(entries))
+            }
+            Some('(') => {
+                self.bump();
+                self.skip_whitespace();
+
+                // Look ahead for ``ident:`` to tell structs apart from tuples
+                let (pos, line) = (self.pos, self.line);
+                let is_struct = self.parse_ident().is_some() && {
+                    self.skip_whitespace();
+                    self.peek() == Some(':')
+                };
+                self.pos = pos;
+                self.line = line;
+
+                match is_struct {
+                    true => self
+                        .parse_seq(')', |parser| {
+                            parser.skip_whitespace();
+                            let name = parser
+                                .parse_ident()
+                                .ok_or_else(|| parser.error("expected a field name"))?;
+                            parser.expect(':')?;
+                            Ok((name.to_owned(), parser.parse_value()?))
+                        })
+                        .map(TextValue::Struct),
+                    false => self.parse_seq(')', Self::parse_value).map(TextValue::Tuple),
+                }
+            }
+            Some(c) if c.is_ascii_digit() || c == '-' || c == '+' => self.parse_number(),
+            Some(_) => match self.parse_ident() {
+                Some("true") => Ok(TextValue::Bool(true)),
+                Some("false") => Ok(TextValue::Bool(false)),
+                _ => Err(self.error("expected a value")),
+            },
+            None => Err(self.error("expected a value, found end of file")),
+        }
+    }
+}
+
+/// Parses a single value, e.g. a whole file written by ``World::save_text``
+pub fn parse_text(src: &str) -> Result<TextValue, TextError> {
+    let mut parser = Parser {
+        src,
+        pos: 0,
+        line: 1,
+    };
+    let value = parser.parse_value()?;
+    parser.skip_whitespace();
+    match parser.peek() {
+        Some(_) => Err(parser.error("expected end of file")),
+        None => Ok(value),
+    }
+}
+
+fn write_value(out: &mut String, value: &TextValue) {
+    fn write_seq<T>(
+        out: &mut String,
+        values: &[T],
+        start: char,
+        end: char,
+        mut f: impl FnMut(&mut String, &T),
+    ) {
+        out.push(start);
+        for (n, value) in values.iter().enumerate() {
+            if n != 0 {
+                out.push_str(", ");
+            }
+            f(out, value);
+        }
+        out.push(end);
+    }
+
+    match value {
+        TextValue::Bool(b) => write!(out, "{}", b).unwrap(),
+        TextValue::Int(n) => write!(out, "{}", n).unwrap(),
+        // Debug always writes a '.' or exponent so it reads back as a float
+        TextValue::Float(n) => write!(out, "{:?}", n).unwrap(),
+        TextValue::Str(s) => {
+            out.push('"');
+            for c in s.chars() {
+                match c {
+                    '\n' => out.push_str("\\n"),
+                    '\r' => out.push_str("\\r"),
+                    '\t' => out.push_str("\\t"),
+                    '\0' => out.push_str("\\0"),
+                    '\\' => out.push_str("\\\\"),
+                    '"' => out.push_str("\\\""),
+                    c => out.push(c),
+                }
+            }
+            out.push('"');
+        }
+        TextValue::Entity(label) => write!(out, "#{}", label).unwrap(),
+        TextValue::List(values) => write_seq(out, values, '[', ']', write_value),
+        TextValue::Tuple(values) => write_seq(out, values, '(', ')', write_value),
+        TextValue::Struct(fields) => write_seq(out, fields, '(', ')', |out, (name, value)| {
+            out.push_str(name);
+            out.push_str(": ");
+            write_value(out, value);
+        }),
+        TextValue::Map(entries) => write_seq(out, entries, '{', '}', |out, (key, value)| {
+            write_value(out, key);
+            out.push_str(": ");
+            write_value(out, value);
+        }),
+    }
+}
+
+/// How the data for a component is written, either through the registered functions or as raw bytes
+enum TextData<'r> {
+    Registered(&'r RegistryEntry, Box<dyn Any>),
+    Raw(Vec<u8>),
+}
+
+#[derive(Clone, Copy)]
+enum KeyPart<'r> {
+    Registered(&'r RegistryEntry),
+    Entity(u64),
+}
+
+impl<'r> PartialEq for KeyPart<'r> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (KeyPart::Registered(a), KeyPart::Registered(b)) => a.type_id == b.type_id,
+            (KeyPart::Entity(a), KeyPart::Entity(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+struct TextEntity<'a, 'r> {
+    label: u64,
+    component_meta: ComponentMeta,
+    components: Vec<(KeyPart<'r>, Option<KeyPart<'r>>, &'a TextValue)>,
+}
+
+impl World {
+    /// Gets the registry entry for ``entity`` if it is the EcsId of a rust type
+    fn text_entry<'r>(
+        &self,
+        registry: &'r ComponentRegistry,
+        type_ids: &HashMap<EcsId, TypeId>,
+        entity: EcsId,
+    ) -> Result<Option<&'r RegistryEntry>, TextError> {
+        match type_ids.get(&entity) {
+            Some(type_id) => match registry.get_by_type(*type_id) {
+                Some(entry) if entry.text.is_some() => Ok(Some(entry)),
+                _ => Err(TextError::UnregisteredComponent(entity)),
+            },
+            None => Ok(None),
+        }
+    }
+
+    fn key_part_to_text(
+        &self,
+        registry: &ComponentRegistry,
+        type_ids: &HashMap<EcsId, TypeId>,
+        entities: &EntityMap,
+        entity: EcsId,
+    ) -> Result<TextValue, TextError> {
+        if let Some(entry) = self.text_entry(registry, type_ids, entity)? {
+            return Ok(TextValue::Str(entry.name.clone()));
+        }
+        entity.to_text(entities)
+    }
+
+    /// Writes every entity in the world to the text format, components are written by their name in ``registry``.
+    ///
+    /// The EcsIds for rust types are not written as they are created again from the registered names when loading.
+    /// Entities used as components can only be written if they have no drop_fn, their data is written as a list of bytes
+    pub fn save_text(&mut self, registry: &ComponentRegistry) -> Result<String, TextError> {
+        let type_ids = self
+            .type_id_to_ecs_id
+            .iter()
+            .map(|(&type_id, &entity)| (entity, type_id))
+            .collect::<HashMap<_, _>>();
+
+        let mut entities = EntityMap::new();
+        let mut saved = Vec::new();
+        for (idx, &(alive, generation)) in self.entities.generations.iter().enumerate() {
+            let entity = EcsId::new(idx as u32, generation);
+            if !alive || self.ecs_id_meta[idx].is_none() || type_ids.contains_key(&entity) {
+                continue;
+            }
+            entities.insert(entity, saved.len() as u64);
+            saved.push(entity);
+        }
+
+        let mut out = String::from("[\n");
+        for &entity in saved.iter() {
+            let meta = self.ecs_id_meta[entity.uindex()].as_ref().unwrap();
+            writeln!(out, "    (").unwrap();
+            writeln!(out, "        id: #{},", entities.label(entity).unwrap()).unwrap();
+            let layout = meta.component_meta.layout;
+            if layout.size() > 0 {
+                writeln!(
+                    out,
+                    "        layout: ({}, {}),",
+                    layout.size(),
+                    layout.align()
+                )
+                .unwrap();
+            }
+            let archetype = &self.archetypes[meta.instance_meta.archetype.0];
+            if archetype.comp_ids.is_empty() {
+                writeln!(out, "        components: {{}},").unwrap();
+                writeln!(out, "    ),").unwrap();
+                continue;
+            }
+            writeln!(out, "        components: {{").unwrap();
+
+            for (comp_id, storage, _) in archetype.component_storages.iter() {
+                let (relation, key) = match self.pair_parts(*comp_id) {
+                    Some((relation, target)) => (
+                        relation,
+                        TextValue::Tuple(vec![
+                            self.key_part_to_text(registry, &type_ids, &entities, relation)?,
+                            self.key_part_to_text(registry, &type_ids, &entities, target)?,
+                        ]),
+                    ),
+                    None => (
+                        *comp_id,
+                        self.key_part_to_text(registry, &type_ids, &entities, *comp_id)?,
+                    ),
+                };
+
+                // Safe because we have a mutable borrow of the world so nothing else can be accessing the storage
+                let storage = unsafe { &*storage.get() };
+                let ptr = storage.get_raw(meta.instance_meta.index).unwrap();
+                let value = match self.text_entry(registry, &type_ids, relation)? {
+                    Some(entry) => (entry.text.unwrap().to_text)(ptr, &entities)?,
+                    None => {
+                        let component_meta = self.get_component_meta(relation).unwrap();
+                        if component_meta.drop_fn.is_some() {
+                            return Err(TextError::UnregisteredComponent(relation));
+                        }
+                        let size = component_meta.layout.size();
+                        bytes_to_text(unsafe { std::slice::from_raw_parts(ptr, size) })
+                    }
+                };
+
+                out.push_str("            ");
+                write_value(&mut out, &key);
+                out.push_str(": ");
+                write_value(&mut out, &value);
+                out.push_str(",\n");
+            }
+
+            writeln!(out, "        }},").unwrap();
+            writeln!(out, "    ),").unwrap();
+        }
+        out.push_str("]\n");
+
+        Ok(out)
+    }
+
+    /// Spawns every entity in ``text``, the entities are given new EcsIds and entity references in components are remapped to them
+    /// so the same text can be loaded into a world more than once, e.g. as a prefab.
+    ///
+    /// Returns the spawned entities in the order they appear in the text. If an error is returned no entities are spawned
+    pub fn load_text(
+        &mut self,
+        text: &str,
+        registry: &ComponentRegistry,
+    ) -> Result<Vec<EcsId>, TextError> {
+        let document = parse_text(text)?;
+        let entries = match &document {
+            TextValue::List(entries) => entries,
+            value => return Err(invalid("a list of entities", value)),
+        };
+
+        let mut labels = HashMap::new();
+        for entry in entries.iter() {
+            match entry.field("id") {
+                Some(&TextValue::Entity(label)) => {
+                    if labels.insert(label, ()).is_some() {
+                        return Err(TextError::InvalidValue(format!(
+                            "entity #{} is defined twice",
+                            label
+                        )));
+                    }
+                }
+                _ => return Err(invalid("an entity with an id", entry)),
+            }
+        }
+
+        let key_part = |value: &TextValue| match value {
+            TextValue::Str(name) => match registry.get_by_name(name) {
+                Some(entry) if entry.text.is_some() => Ok(KeyPart::Registered(entry)),
+                _ => Err(TextError::UnknownComponentName(name.clone())),
+            },
+            &TextValue::Entity(label) => match labels.contains_key(&label) {
+                true => Ok(KeyPart::Entity(label)),
+                false => Err(TextError::UnknownEntity(label)),
+            },
+            value => Err(invalid("a component name or entity", value)),
+        };
+
+        let mut text_entities = Vec::with_capacity(entries.len());
+        for entry in entries.iter() {
+            let component_meta = match entry.field("layout") {
+                Some(value) => {
+                    let layout = match value {
+                        TextValue::Tuple(layout) => match layout[..] {
+                            [TextValue::Int(size), TextValue::Int(align)]
+                                if size >= 0 && align >= 0 =>
+                            {
+                                core::alloc::Layout::from_size_align(size as usize, align as usize)
+                                    .ok()
+                            }
+                            _ => None,
+                        },
+                        _ => None,
+                    };
+                    ComponentMeta {
+                        drop_fn: None,
+                        layout: layout.ok_or_else(|| invalid("(size, align)", value))?,
+                        on_add: None,
+                        on_remove: None,
+                    }
+                }
+                None => ComponentMeta::unit(),
+            };
+
+            let mut components = Vec::new();
+            match entry.field("components") {
+                Some(TextValue::Map(map)) => {
+                    for (key, value) in map.iter() {
+                        let (relation, target) = match key {
+                            TextValue::Tuple(pair) if pair.len() == 2 => {
+                                (key_part(&pair[0])?, Some(key_part(&pair[1])?))
+                            }
+                            key => (key_part(key)?, None),
+                        };
+                        if components
+                            .iter()
+                            .any(|&(r, t, _)| (r, t) == (relation, target))
+                        {
+                            return Err(invalid("each component once", key));
+                        }
+                        components.push((relation, target, value));
+                    }
+                }
+                None => (),
+                Some(value) => return Err(invalid("a map of components", value)),
+            }
+
+            let label = match entry.field("id") {
+                Some(&TextValue::Entity(label)) => label,
+                _ => unreachable!(),
+            };
+            text_entities.push(TextEntity {
+                label,
+                component_meta,
+                components,
+            });
+        }
+
+        // Reserve the ids first so that components can refer to entities that come later in the text
+        let mut entity_map = EntityMap::new();
+        for text_entity in text_entities.iter() {
+            entity_map.insert(self.spawn_entity_id(), text_entity.label);
+        }
+
+        let mut datas = Vec::with_capacity(text_entities.len());
+        for text_entity in text_entities.iter() {
+            let mut entity_datas = Vec::with_capacity(text_entity.components.len());
+            for &(relation, _, value) in text_entity.components.iter() {
+                let data = match relation {
+                    KeyPart::Registered(entry) => {
+                        (entry.text.unwrap().from_text)(value, &entity_map)
+                            .map(|data| TextData::Registered(entry, data))
+                    }
+                    KeyPart::Entity(label) => {
+                        let text_entity = text_entities.iter().find(|e| e.label == label).unwrap();
+                        bytes_from_text(value, text_entity.component_meta.layout.size())
+                            .map(TextData::Raw)
+                    }
+                };
+
+                match data {
+                    Ok(data) => entity_datas.push(data),
+                    Err(e) => {
+                        // None of the entities have been built yet so they only need to be freed
+                        for &entity in entity_map.labels.keys() {
+                            self.entities.despawn(entity);
+                        }
+                        return Err(e);
+                    }
+                }
+            }
+            datas.push(entity_datas);
+        }
+
+        // Entities used as components need their ComponentMeta set before anything can be built with them,
+        // the instance meta is a placeholder that gets overwritten when the entity is built
+        for text_entity in text_entities.iter() {
+            let entity = entity_map.entity(text_entity.label).unwrap();
+            self.set_entity_meta(
+                entity,
+                EntityMeta {
+                    instance_meta: InstanceMeta {
+                        archetype: ArchIndex(0),
+                        index: 0,
+                    },
+                    component_meta: text_entity.component_meta.clone(),
+                },
+            );
+        }
+
+        let mut spawned = Vec::with_capacity(text_entities.len());
+        for (text_entity, entity_datas) in text_entities.iter().zip(datas) {
+            let mut comp_id = |part: KeyPart| match part {
+                KeyPart::Registered(entry) => self
+                    .get_or_create_type_id_ecsid_dynamic(entry.type_id, || {
+                        entry.component_meta.clone()
+                    }),
+                KeyPart::Entity(label) => entity_map.entity(label).unwrap(),
+            };
+            let comp_ids = text_entity
+                .components
+                .iter()
+                .map(|&(relation, target, _)| match target {
+                    Some(target) => EcsId::pair(comp_id(relation), comp_id(target)),
+                    None => comp_id(relation),
+                })
+                .collect::<Vec<_>>();
+
+            let entity = entity_map.entity(text_entity.label).unwrap();
+            let mut builder = EntityBuilder::new(self, entity, text_entity.component_meta.clone());
+            for (comp_id, data) in comp_ids.into_iter().zip(entity_datas) {
+                builder = match data {
+                    TextData::Registered(entry, data) => {
+                        (entry.text.unwrap().with_boxed)(builder, data, comp_id)
+                    }
+                    TextData::Raw(mut bytes) => unsafe {
+                        builder.with_dynamic_with_data(bytes.as_mut_ptr(), comp_id)
+                    },
+                };
+            }
+            spawned.push(builder.build());
+        }
+
+        Ok(spawned)
+    }
+}
@@ -0,0 +1,90 @@
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/// A fixed set of worker threads that ``Schedule`` runs its batches of systems on
+pub(crate) struct ThreadPool {
+    sender: Option<Sender<Job>>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+impl ThreadPool {
+    pub(crate) fn new(threads: usize) -> Self {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        let threads = (0..usize::max(threads, 1))
+            .map(|n| {
+                let receiver = receiver.clone();
+                thread::Builder::new()
+                    .name(format!("arche_tape worker {}", n))
+                    .spawn(move || Self::worker(&receiver))
+                    .unwrap()
+            })
+            .collect();
+
+        Self {
+            sender: Some(sender),
+            threads,
+        }
+    }
+
+    fn worker(receiver: &Mutex<Receiver<Job>>) {
+        loop {
+            // The lock guard must be dropped before running the job so that other workers can take jobs
+            let job = receiver.lock().unwrap().recv();
+            match job {
+                Ok(job) => job(),
+                Err(_) => return,
+            }
+        }
+    }
+
+    pub(crate) fn thread_count(&self) -> usize {
+        self.threads.len()
+    }
+
+    /// Runs every job on the pool and blocks until all of them have finished. If any of the jobs panicked the
+    /// panic is resumed on this thread once every job is done.
+    pub(crate) fn scope<'a>(&self, jobs: Vec<Box<dyn FnOnce() + Send + 'a>>) {
+        let job_count = jobs.len();
+        let (done_sender, done_receiver) = mpsc::channel::<Result<(), Box<dyn Any + Send>>>();
+
+        for job in jobs {
+            let done_sender = done_sender.clone();
+            let job: Box<dyn FnOnce() + Send + 'a> = Box::new(move || {
+                let result = panic::catch_unwind(AssertUnwindSafe(job));
+                done_sender.send(result).unwrap();
+            });
+            // Safety: we don't return until every job has sent its result which means nothing borrowed for ``'a``
+            // is used after this function returns, even if a job panics
+            let job = unsafe { std::mem::transmute::<Box<dyn FnOnce() + Send + 'a>, Job>(job) };
+            self.sender.as_ref().unwrap().send(job).unwrap();
+        }
+
+        let mut panic = None;
+        for _ in 0..job_count {
+            if let Err(payload) = done_receiver.recv().unwrap() {
+                panic.get_or_insert(payload);
+            }
+        }
+
+        if let Some(payload) = panic {
+            panic::resume_unwind(payload);
+        }
+    }
+}
+
+impl Drop for ThreadPool {
+    fn drop(&mut self) {
+        // Dropping the sender makes every worker's ``recv`` fail so that they return
+        drop(self.sender.take());
+        for thread in self.threads.drain(..) {
+            let _ = thread.join();
+        }
+    }
+}
@@ -1,16 +1,20 @@
-use super::entities::{EcsId, Entities};
+use super::entities::{EcsId, Entities, EntityCounters, GenerationPolicy};
 use crate::{
     array_vec::ArrayVec,
     bitset_iterator::{BitsetIterator, Bitsetsss, Bitvec},
+    change_detection::{ColumnTicks, ComponentTicks, CHECK_TICK_THRESHOLD},
     dyn_query::{DynQuery, FetchType},
+    resource::Resource,
     static_query::StaticQuery,
-    Component,
+    Component, ComponentHook, EcsError,
 };
 use std::cell::UnsafeCell;
 use std::collections::HashMap;
+use std::mem::{ManuallyDrop, MaybeUninit};
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::RwLock;
 use std::{any::TypeId, slice::Iter};
-use untyped_vec::UntypedVec;
+use untyped_vec::{TypeInfo, UntypedVec};
 
 pub struct ArchetypeIter<'a, const N: usize> {
     archetypes: &'a [Archetype],
@@ -26,6 +30,8 @@ impl<'a, const N: usize> Iterator for ArchetypeIter<'a, N> {
 }
 
 const CACHE_SIZE: usize = 4;
+/// The archetype edges for an archetype, each component id maps to the archetype with that component added or removed.
+/// Edges are added to both archetypes when a move between them is first looked up
 pub struct AddRemoveCache {
     cache: ArrayVec<(EcsId, usize), CACHE_SIZE>,
     lookup: HashMap<EcsId, usize, crate::utils::TypeIdHasherBuilder>,
@@ -58,6 +64,30 @@ impl AddRemoveCache {
         self.cache.push_start((component_id, archetype));
         self.lookup.insert(component_id, archetype);
     }
+
+    /// Changes the archetype of every edge to ``remap(archetype)``, edges that ``remap`` returns ``None`` for are removed
+    pub(crate) fn remap(&mut self, remap: impl Fn(usize) -> Option<usize>) {
+        self.cache = ArrayVec::new();
+        self.lookup.retain(|_, archetype| match remap(*archetype) {
+            Some(new_archetype) => {
+                *archetype = new_archetype;
+                true
+            }
+            None => false,
+        });
+    }
+
+    pub fn remove_id(&mut self, component_id: EcsId) {
+        if self.lookup.remove(&component_id).is_some() {
+            // Everything in the cache is also in the lookup so it'll get refilled on the next lookup_id calls
+            self.cache = ArrayVec::new();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.cache = ArrayVec::new();
+        self.lookup.clear();
+    }
 }
 pub struct Archetype {
     /// A lookup of a component's TypeId to the index into component_storages/type_ids
@@ -69,7 +99,8 @@ pub struct Archetype {
     /// Component storages are sorted such that lower type_ids are first, this means that when adding/removing components we dont need to
     /// go through the lookup hashmap on the other archetype, we can just zip two iterators over component storages and skip the index
     /// for the removed/added type
-    pub(crate) component_storages: Vec<(EcsId, UnsafeCell<UntypedVec>)>, // We need the EcsId here so that we can sort the vec :( the EcsId here should be the same as the one in comp_ids at the same index
+    /// Each storage has the change ticks for its rows alongside it, see ``ColumnTicks``
+    pub(crate) component_storages: Vec<(EcsId, UnsafeCell<UntypedVec>, UnsafeCell<ColumnTicks>)>, // We need the EcsId here so that we can sort the vec :( the EcsId here should be the same as the one in comp_ids at the same index
 
     /// The order of this vec is guaranteed to be the same as the order of component storages,
     /// this means that you can .iter().position(|id| ...) to find the index in component_storages for an EcsId
@@ -90,7 +121,11 @@ impl Archetype {
                 let mut storages = Vec::with_capacity(from.component_storages.len() + 1);
                 for storage in from.component_storages.iter_mut() {
                     let untyped_vec = UntypedVec::new_from_untyped_vec(storage.1.get_mut());
-                    storages.push((storage.0, UnsafeCell::new(untyped_vec)));
+                    storages.push((
+                        storage.0,
+                        UnsafeCell::new(untyped_vec),
+                        UnsafeCell::new(ColumnTicks::new()),
+                    ));
                 }
                 storages
             },
@@ -115,19 +150,20 @@ impl Archetype {
         new_archetype.component_storages.push((
             with_id,
             UnsafeCell::new(unsafe { UntypedVec::new_from_raw(with_type_info) }),
+            UnsafeCell::new(ColumnTicks::new()),
         ));
 
         // TODO there's no need to sort twice they should have the same ordering
         new_archetype.comp_ids.sort();
         new_archetype
             .component_storages
-            .sort_by(|(id1, _), (id2, _)| Ord::cmp(&id1, &id2));
+            .sort_by(|(id1, ..), (id2, ..)| Ord::cmp(&id1, &id2));
 
         assert!(
             new_archetype
                 .comp_ids
                 .iter()
-                .zip(new_archetype.component_storages.iter().map(|(id, _)| id))
+                .zip(new_archetype.component_storages.iter().map(|(id, ..)| id))
                 .all(|(id1, id2)| id1 == id2)
         );
 
@@ -152,13 +188,13 @@ impl Archetype {
         new_archetype.comp_ids.sort();
         new_archetype
             .component_storages
-            .sort_by(|(id_1, _), (id_2, _)| Ord::cmp(&id_1, &id_2));
+            .sort_by(|(id_1, ..), (id_2, ..)| Ord::cmp(&id_1, &id_2));
 
         assert!(
             new_archetype
                 .comp_ids
                 .iter()
-                .zip(new_archetype.component_storages.iter().map(|(id, _)| id))
+                .zip(new_archetype.component_storages.iter().map(|(id, ..)| id))
                 .all(|(id1, id2)| id1 == id2)
         );
 
@@ -178,12 +214,9 @@ impl Archetype {
     ) -> bool {
         assert!(self.entities[entity_idx] == entity);
         self.entities.swap_remove(entity_idx);
-        for storage in self
-            .component_storages
-            .iter_mut()
-            .map(|(_, cell)| cell.get_mut())
-        {
-            storage.swap_remove(entity_idx);
+        for (_, storage, ticks) in self.component_storages.iter_mut() {
+            storage.get_mut().swap_remove(entity_idx);
+            ticks.get_mut().swap_remove(entity_idx);
         }
         entity_metas[entity.uindex()] = None;
 
@@ -198,6 +231,41 @@ impl Archetype {
         false
     }
 
+    /// Returns the index into component_storages for the component, if ``id`` is a wildcard pair
+    /// the first component that matches it is used
+    pub(crate) fn storage_index(&self, id: EcsId) -> Option<usize> {
+        match id.is_wildcard() {
+            true => self.comp_ids.iter().position(|comp_id| comp_id.matches(id)),
+            false => self.comp_lookup.get(&id).copied(),
+        }
+    }
+
+    /// Drops the entity's component in ``component_storages[storage_idx]`` and replaces it with the data at ``new``
+    ///
+    /// # Safety
+    ///
+    ///    ``new`` must point to a valid instance of the storage's component that must not be used again, it does not
+    ///    need to be aligned
+    pub(crate) unsafe fn replace_component(
+        &mut self,
+        storage_idx: usize,
+        entity_idx: usize,
+        new: *mut MaybeUninit<u8>,
+        change_tick: u32,
+    ) {
+        let (_, storage, ticks) = &mut self.component_storages[storage_idx];
+        let storage = storage.get_mut();
+        let TypeInfo { layout, drop_fn } = storage.get_type_info();
+        let dst = storage.get_mut_raw(entity_idx).unwrap() as *mut MaybeUninit<u8>;
+        unsafe {
+            if let Some(drop_fn) = drop_fn {
+                drop_fn(dst);
+            }
+            std::ptr::copy_nonoverlapping(new, dst, layout.size());
+        }
+        ticks.get_mut().mark_changed(entity_idx, change_tick);
+    }
+
     pub fn try_find_next_archetype(&mut self, id: EcsId) -> Option<usize> {
         self.add_remove_cache.lookup_id(id)
     }
@@ -227,6 +295,8 @@ pub struct InstanceMeta {
 pub struct ComponentMeta {
     pub drop_fn: Option<fn(*mut core::mem::MaybeUninit<u8>)>,
     pub layout: core::alloc::Layout,
+    pub on_add: Option<ComponentHook>,
+    pub on_remove: Option<ComponentHook>,
 }
 
 fn component_meta_drop_fn<T: Component>(ptr: *mut core::mem::MaybeUninit<u8>) {
@@ -238,14 +308,18 @@ impl ComponentMeta {
         Self {
             drop_fn: None,
             layout: core::alloc::Layout::from_size_align(size, align).unwrap(),
+            on_add: None,
+            on_remove: None,
         }
     }
 
-    /// Creates a ComponentMeta with the layout and drop_fn of the generic
+    /// Creates a ComponentMeta with the layout, drop_fn and hooks of the generic
     pub fn from_generic<T: Component>() -> Self {
         Self {
             drop_fn: Some(component_meta_drop_fn::<T>),
             layout: core::alloc::Layout::new::<T>(),
+            on_add: T::ON_ADD,
+            on_remove: T::ON_REMOVE,
         }
     }
 
@@ -254,22 +328,65 @@ impl ComponentMeta {
         Self {
             drop_fn: None,
             layout: core::alloc::Layout::new::<()>(),
+            on_add: None,
+            on_remove: None,
+        }
+    }
+
+    /// Drops a component that may not be aligned, the data is copied to an aligned allocation first if it needs to be
+    ///
+    /// # Safety
+    ///
+    ///    ``ptr`` must point to a valid instance of the type described by this meta that must not be used again
+    pub(crate) unsafe fn drop_unaligned(&self, ptr: *mut MaybeUninit<u8>) {
+        let drop_fn = match self.drop_fn {
+            Some(drop_fn) => drop_fn,
+            None => return,
+        };
+
+        if (ptr as usize).is_multiple_of(self.layout.align()) || self.layout.size() == 0 {
+            drop_fn(ptr);
+            return;
+        }
+
+        unsafe {
+            let aligned = std::alloc::alloc(self.layout) as *mut MaybeUninit<u8>;
+            if aligned.is_null() {
+                std::alloc::handle_alloc_error(self.layout);
+            }
+            std::ptr::copy_nonoverlapping(ptr, aligned, self.layout.size());
+            drop_fn(aligned);
+            std::alloc::dealloc(aligned as *mut u8, self.layout);
         }
     }
 }
 
 pub struct World {
     pub(crate) archetypes: Vec<Archetype>,
+    /// The archetype for each set of sorted component ids
+    pub(crate) archetype_lookup: HashMap<Vec<EcsId>, ArchIndex>,
     pub(crate) archetype_bitset: Bitsetsss,
     pub(crate) entities_bitvec: Bitvec,
+    /// See ``World::set_archetype_compaction_threshold``
+    pub(crate) archetype_compaction_threshold: Option<usize>,
 
-    entities: Entities,
+    pub(crate) entities: Entities,
 
-    ecs_id_meta: Vec<Option<EntityMeta>>,
+    pub(crate) ecs_id_meta: Vec<Option<EntityMeta>>,
     pub(crate) type_id_to_ecs_id: HashMap<TypeId, EcsId, crate::utils::TypeIdHasherBuilder>,
 
     pub(crate) lock_lookup: HashMap<EcsId, usize, crate::utils::TypeIdHasherBuilder>,
+    /// Locks are never removed so that the indices held by ``lock_lookup`` and resources stay valid
     pub(crate) locks: Vec<RwLock<()>>,
+    /// Indices into ``locks`` whose component was despawned, reused before pushing a new lock
+    pub(crate) free_locks: Vec<usize>,
+
+    pub(crate) resources: HashMap<TypeId, Resource, crate::utils::TypeIdHasherBuilder>,
+
+    /// Incremented every time a query is created, components are marked added/changed with the current value
+    pub(crate) change_tick: AtomicU32,
+    /// The change tick when ``World::check_change_ticks`` last clamped old ticks
+    pub(crate) last_check_tick: u32,
 
     /// usize is that cap allocated with the pointer
     pub(crate) entity_builder_reuse: Option<(Vec<EcsId>, core::ptr::NonNull<u8>, usize)>,
@@ -298,8 +415,10 @@ impl World {
     pub fn new() -> Self {
         Self {
             archetypes: Vec::new(),
+            archetype_lookup: HashMap::new(),
             archetype_bitset: Bitsetsss::with_capacity(32),
             entities_bitvec: Bitvec::with_capacity(32),
+            archetype_compaction_threshold: None,
 
             entities: Entities::new(),
 
@@ -311,6 +430,12 @@ impl World {
 
             lock_lookup: HashMap::with_hasher(crate::utils::TypeIdHasherBuilder()),
             locks: Vec::new(),
+            free_locks: Vec::new(),
+
+            resources: HashMap::with_hasher(crate::utils::TypeIdHasherBuilder()),
+
+            change_tick: AtomicU32::new(1),
+            last_check_tick: 1,
 
             entity_builder_reuse: None,
         }
@@ -319,14 +444,25 @@ impl World {
     #[must_use]
     /// Creates an entity builder for creating an entity. See the spawn!() macro for a more concise way to use the EntityBuilder
     pub fn spawn(&mut self) -> crate::entity_builder::EntityBuilder {
-        let entity = self.entities.spawn();
+        let entity = self.spawn_entity_id();
         crate::entity_builder::EntityBuilder::new(self, entity, ComponentMeta::unit())
     }
 
+    /// Same as ``World::spawn`` except returns an error instead of panicking when there are no indices left
+    pub fn try_spawn(&mut self) -> Result<crate::entity_builder::EntityBuilder<'_>, EcsError> {
+        self.flush();
+        let entity = self.entities.try_spawn()?;
+        Ok(crate::entity_builder::EntityBuilder::new(
+            self,
+            entity,
+            ComponentMeta::unit(),
+        ))
+    }
+
     #[must_use]
     /// Same as ``World::spawn`` except takes a capacity to initialise the component storage to
     pub fn spawn_with_capacity(&mut self, capacity: usize) -> crate::entity_builder::EntityBuilder {
-        let entity = self.entities.spawn();
+        let entity = self.spawn_entity_id();
         crate::entity_builder::EntityBuilder::with_capacity(
             self,
             entity,
@@ -335,37 +471,140 @@ impl World {
         )
     }
 
-    /// Despawns an entity, if the entity being despawned is added as a component to any entities it will be automatically removed
+    /// Despawns an entity, if the entity being despawned is added as a component to any entities it will be automatically removed.
+    /// Any children of the entity will be despawned along with it
     pub fn despawn(&mut self, entity: EcsId) -> bool {
+        self.flush();
         if !self.entities.is_alive(entity) {
             return false;
         }
 
-        // TODO: Remove `entity` component from all entities
-        for arch in &self.archetypes {
-            if let Some(_) = arch.comp_lookup.get(&entity) {
-                todo!();
-            }
-        }
+        self.despawn_children(entity);
 
         let InstanceMeta { archetype, index } =
             self.get_entity_meta(entity).unwrap().instance_meta.clone();
+        let comp_ids = &self.archetypes[archetype.0].comp_ids;
+        let hooks = self.component_hooks(comp_ids, |meta| meta.on_remove);
+
+        // Pairs only store indices so we have to get rid of any that use this entity or else they would refer to whatever
+        // entity reuses this index next
+        let wildcards = entity.entity_wildcards();
+        let pairs = self
+            .lock_lookup
+            .keys()
+            .filter(|id| wildcards.iter().any(|&wildcard| id.matches(wildcard)))
+            .copied()
+            .collect::<Vec<_>>();
+        // The component metas of this entity and its pairs are gone once it is despawned so their hooks are collected first
+        let removed_comps = std::iter::once(entity)
+            .chain(pairs)
+            .map(|id| (id, self.component_hooks(&[id], |meta| meta.on_remove)))
+            .collect::<Vec<_>>();
 
         self.archetypes[archetype.0].despawn(entity, index, &mut self.ecs_id_meta);
+
+        let mut removed = Vec::new();
+        for (comp_id, comp_hooks) in removed_comps {
+            for removed_entity in self.remove_component_from_all(comp_id) {
+                removed.push((removed_entity, comp_hooks.clone()));
+            }
+        }
+        for wildcard in wildcards.iter() {
+            self.archetype_bitset.clear_bitvec(*wildcard);
+        }
+
         self.entities.despawn(entity);
+        self.run_hooks(entity, hooks);
+        for (removed_entity, comp_hooks) in removed {
+            self.run_hooks(removed_entity, comp_hooks);
+        }
         true
     }
 
+    /// Same as ``World::despawn`` except returns an error if the entity isn't alive
+    pub fn try_despawn(&mut self, entity: EcsId) -> Result<(), EcsError> {
+        match self.despawn(entity) {
+            true => Ok(()),
+            false => Err(EcsError::DeadEntity(entity)),
+        }
+    }
+
     pub fn is_alive(&self, entity: EcsId) -> bool {
         self.entities.is_alive(entity)
     }
 
+    /// Panics if the query borrows a component mutably more than once or if any of its borrows conflict with a query
+    /// or resource borrow that is still alive, see ``World::try_query_dynamic``
     pub fn query_dynamic<const N: usize>(&self, ids: [FetchType; N]) -> DynQuery<'_, N> {
-        DynQuery::new(self, ids)
+        DynQuery::new(self, ids, 0)
+    }
+
+    /// Same as ``World::query_dynamic`` except returns an error instead of panicking when the query's borrows conflict
+    pub fn try_query_dynamic<const N: usize>(
+        &self,
+        ids: [FetchType; N],
+    ) -> Result<DynQuery<'_, N>, EcsError> {
+        DynQuery::try_new(self, ids, 0)
+    }
+
+    /// Same as ``World::query_dynamic`` except ``FetchType::Added`` and ``FetchType::Changed`` only match components added/changed after ``last_run``
+    pub fn query_dynamic_since<const N: usize>(
+        &self,
+        ids: [FetchType; N],
+        last_run: u32,
+    ) -> DynQuery<'_, N> {
+        DynQuery::new(self, ids, last_run)
     }
 
+    /// Panics if the query borrows a component mutably more than once or if any of its borrows conflict with a query
+    /// or resource borrow that is still alive, see ``World::try_query``
     pub fn query<'a, Q: crate::static_query::QueryTuple>(&'a self) -> StaticQuery<'a, Q> {
-        Q::new(self)
+        Q::new(self, 0)
+    }
+
+    /// Same as ``World::query`` except returns an error instead of panicking when the query's borrows conflict
+    pub fn try_query<'a, Q: crate::static_query::QueryTuple>(
+        &'a self,
+    ) -> Result<StaticQuery<'a, Q>, EcsError> {
+        Q::try_new(self, 0)
+    }
+
+    /// Same as ``World::query`` except ``Added<T>`` and ``Changed<T>`` only match components added/changed after ``last_run``.
+    /// Pass the ``change_tick`` of the previous query to see everything that happened since it was created
+    pub fn query_since<'a, Q: crate::static_query::QueryTuple>(
+        &'a self,
+        last_run: u32,
+    ) -> StaticQuery<'a, Q> {
+        Q::new(self, last_run)
+    }
+
+    /// The tick that components added or changed right now are marked with
+    pub fn change_tick(&self) -> u32 {
+        self.change_tick.load(Ordering::Relaxed)
+    }
+
+    /// Increments the world's change tick, returning the tick before the increment. The tick wraps around on overflow
+    pub fn increment_change_tick(&self) -> u32 {
+        self.change_tick.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Clamps component ticks older than ``MAX_CHANGE_AGE`` so that ``Added`` and ``Changed`` keep working after the
+    /// change tick wraps around. Only does anything once ``CHECK_TICK_THRESHOLD`` ticks have passed since the last
+    /// check, returning true if it did. Called by ``Schedule::run`` and ``World::apply_commands``, worlds that use neither
+    /// should call this regularly
+    pub fn check_change_ticks(&mut self) -> bool {
+        let change_tick = self.change_tick();
+        if change_tick.wrapping_sub(self.last_check_tick) < CHECK_TICK_THRESHOLD {
+            return false;
+        }
+
+        for archetype in self.archetypes.iter_mut() {
+            for (_, _, ticks) in archetype.component_storages.iter_mut() {
+                ticks.get_mut().clamp_ticks(change_tick);
+            }
+        }
+        self.last_check_tick = change_tick;
+        true
     }
 
     pub fn add_component<T: Component>(&mut self, entity: EcsId, component: T) {
@@ -381,12 +620,65 @@ impl World {
         }
     }
 
+    /// Same as ``World::add_component`` except returns an error instead of panicking
+    pub fn try_add_component<T: Component>(
+        &mut self,
+        entity: EcsId,
+        component: T,
+    ) -> Result<(), EcsError> {
+        if !self.entities.is_alive(entity) {
+            return Err(EcsError::DeadEntity(entity));
+        }
+        let comp_id = self.get_or_create_type_id_ecsid::<T>();
+        if self.has_component_dynamic(entity, comp_id) {
+            return Err(EcsError::DuplicateComponent {
+                entity,
+                component: comp_id,
+            });
+        }
+        self.add_component(entity, component);
+        Ok(())
+    }
+
+    /// Adds the component to the entity, if the entity already has the component it is replaced without moving the
+    /// entity between archetypes
+    pub fn insert_component<T: Component>(&mut self, entity: EcsId, component: T) {
+        assert!(self.entities.is_alive(entity));
+        let comp_id = self.get_or_create_type_id_ecsid::<T>();
+        let mut component = core::mem::ManuallyDrop::new(component);
+        unsafe {
+            self.insert_component_dynamic_with_data(
+                entity,
+                comp_id,
+                &mut component as *mut _ as *mut u8,
+            );
+        }
+    }
+
     pub fn remove_component<T: Component>(&mut self, entity: EcsId) {
         assert!(self.entities.is_alive(entity));
         let comp_id = self.get_or_create_type_id_ecsid::<T>();
         self.remove_component_dynamic(entity, comp_id);
     }
 
+    /// Same as ``World::remove_component`` except returns an error if the entity doesn't have the component
+    pub fn try_remove_component<T: Component>(&mut self, entity: EcsId) -> Result<(), EcsError> {
+        let comp_id = self.get_or_create_type_id_ecsid::<T>();
+        self.try_remove_component_dynamic(entity, comp_id)
+    }
+
+    /// Removes the component from the entity and returns it instead of dropping it
+    pub fn take_component<T: Component>(&mut self, entity: EcsId) -> Option<T> {
+        let comp_id = *self.type_id_to_ecs_id.get(&TypeId::of::<T>())?;
+        let mut component = MaybeUninit::<T>::uninit();
+        let dst = component.as_mut_ptr() as *mut u8;
+        // Safe because ``comp_id`` is the id for ``T`` so ``component`` is the right size
+        match unsafe { self.take_component_dynamic(entity, comp_id, dst) } {
+            true => Some(unsafe { component.assume_init() }),
+            false => None,
+        }
+    }
+
     pub fn has_component<T: Component>(&self, entity: EcsId) -> bool {
         let func = || {
             let comp_id = self.type_id_to_ecs_id.get(&TypeId::of::<T>())?;
@@ -397,20 +689,75 @@ impl World {
         func().unwrap_or(false)
     }
 
+    /// Returns true if the entity has the component, ``component_id`` can be a wildcard pair
+    pub fn has_component_dynamic(&self, entity: EcsId, component_id: EcsId) -> bool {
+        let func = || {
+            let ArchIndex(idx) = self.get_entity_meta(entity)?.instance_meta.archetype;
+            self.archetypes[idx].storage_index(component_id)
+        };
+
+        func().is_some()
+    }
+
+    /// Adds the pair ``(R, target)`` to the entity with ``relation`` as its data
+    pub fn add_relation<R: Component>(&mut self, entity: EcsId, target: EcsId, relation: R) {
+        assert!(self.entities.is_alive(entity));
+        assert!(self.entities.is_alive(target));
+        let relation_id = self.get_or_create_type_id_ecsid::<R>();
+        let pair = EcsId::pair(relation_id, target);
+        let mut relation = core::mem::ManuallyDrop::new(relation);
+        unsafe {
+            self.add_component_dynamic_with_data(entity, pair, &mut relation as *mut _ as *mut u8);
+        }
+    }
+
+    pub fn remove_relation<R: Component>(&mut self, entity: EcsId, target: EcsId) {
+        assert!(self.entities.is_alive(entity));
+        let relation_id = self.get_or_create_type_id_ecsid::<R>();
+        self.remove_component_dynamic(entity, EcsId::pair(relation_id, target));
+    }
+
+    pub fn has_relation<R: Component>(&self, entity: EcsId, target: EcsId) -> bool {
+        match self.type_id_to_ecs_id.get(&TypeId::of::<R>()) {
+            Some(&relation_id) => {
+                self.has_component_dynamic(entity, EcsId::pair(relation_id, target))
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the relation and target of a pair if they are both alive
+    pub fn pair_parts(&self, pair: EcsId) -> Option<(EcsId, EcsId)> {
+        let relation = self.entities.id_at(pair.pair_relation()?)?;
+        let target = self.entities.id_at(pair.pair_target()?)?;
+        Some((relation, target))
+    }
+
+    /// Returns true if the EcsId can currently be added as a component, for pairs this means that both the relation and target are alive
+    pub fn is_component_alive(&self, component_id: EcsId) -> bool {
+        match component_id.is_pair() {
+            true => self.pair_parts(component_id).is_some(),
+            false => self.entities.is_alive(component_id),
+        }
+    }
+
+    /// Returns the ComponentMeta used when ``component_id`` is added as a component, pairs use the ComponentMeta of their relation
+    pub fn get_component_meta(&self, component_id: EcsId) -> Option<&ComponentMeta> {
+        let entity = match component_id.is_pair() {
+            true => self.pair_parts(component_id)?.0,
+            false => component_id,
+        };
+
+        Some(&self.get_entity_meta(entity)?.component_meta)
+    }
+
     /// Adds an entity as a dataless component
     ///
     /// This method will panic if a component with the ID of component_id expects data. Entities by default expect no data.
     pub fn add_component_dynamic(&mut self, entity: EcsId, component_id: EcsId) {
         assert!(self.entities.is_alive(entity));
-        assert!(self.entities.is_alive(component_id));
-        assert!(
-            self.get_entity_meta(component_id)
-                .unwrap()
-                .component_meta
-                .layout
-                .size()
-                == 0
-        );
+        assert!(self.is_component_alive(component_id));
+        assert!(self.get_component_meta(component_id).unwrap().layout.size() == 0);
 
         let mut component = core::mem::ManuallyDrop::new(());
         unsafe {
@@ -421,6 +768,31 @@ impl World {
             );
         }
     }
+
+    /// Same as ``World::add_component_dynamic`` except returns an error instead of panicking
+    pub fn try_add_component_dynamic(
+        &mut self,
+        entity: EcsId,
+        component_id: EcsId,
+    ) -> Result<(), EcsError> {
+        if !self.entities.is_alive(entity) {
+            return Err(EcsError::DeadEntity(entity));
+        }
+        if !self.is_component_alive(component_id) {
+            return Err(EcsError::DeadEntity(component_id));
+        }
+        if self.get_component_meta(component_id).unwrap().layout.size() != 0 {
+            return Err(EcsError::LayoutMismatch(component_id));
+        }
+        if self.has_component_dynamic(entity, component_id) {
+            return Err(EcsError::DuplicateComponent {
+                entity,
+                component: component_id,
+            });
+        }
+        self.add_component_dynamic(entity, component_id);
+        Ok(())
+    }
 }
 
 impl World {
@@ -432,13 +804,69 @@ impl World {
         &mut self,
         component_meta: ComponentMeta,
     ) -> crate::entity_builder::EntityBuilder {
-        let entity = self.entities.spawn();
+        let entity = self.spawn_entity_id();
 
         crate::entity_builder::EntityBuilder::new(self, entity, component_meta)
     }
 
+    /// Allocates a new entity id, reserved entities are flushed first so that their indices don't get reused
+    pub(crate) fn spawn_entity_id(&mut self) -> EcsId {
+        self.flush();
+        self.entities.spawn()
+    }
+
+    /// Reserves an id for an entity without needing ``&mut World``, the entity is spawned without any components the
+    /// next time the world is flushed. Spawning entities or applying ``Commands`` flushes the world
+    pub fn reserve_entity(&self) -> EcsId {
+        self.entities.reserve()
+    }
+
+    /// Same as ``World::reserve_entity`` except returns an error instead of panicking when there are no indices left
+    pub fn try_reserve_entity(&self) -> Result<EcsId, EcsError> {
+        Ok(self.entities.try_reserve()?)
+    }
+
+    /// Spawns every entity reserved with ``World::reserve_entity`` without any components
+    pub fn flush(&mut self) {
+        for entity in self.entities.flush_reserved() {
+            crate::entity_builder::EntityBuilder::new(self, entity, ComponentMeta::unit()).build();
+        }
+    }
+
+    /// Sets whether indices are retired or reused once their generation reaches ``MAX_GENERATION``,
+    /// defaults to ``GenerationPolicy::Retire``
+    pub fn set_generation_policy(&mut self, policy: GenerationPolicy) {
+        self.entities.set_generation_policy(policy);
+    }
+
+    /// Limits how many entity indices the world will hand out, defaults to ``MAX_INDICES``
+    pub fn set_max_entity_indices(&mut self, max_indices: usize) {
+        self.entities.set_max_indices(max_indices);
+    }
+
+    /// Once at least ``threshold`` archetypes are empty ``World::compact_archetypes`` is called at the end of
+    /// ``World::apply_commands`` and ``Schedule::run``, defaults to ``None`` which never compacts automatically
+    pub fn set_archetype_compaction_threshold(&mut self, threshold: Option<usize>) {
+        self.archetype_compaction_threshold = threshold;
+    }
+
+    pub fn entity_counters(&self) -> EntityCounters {
+        self.entities.counters()
+    }
+
     pub fn get_or_create_type_id_ecsid<T: Component>(&mut self) -> EcsId {
-        let comp_id = self.type_id_to_ecs_id.get(&TypeId::of::<T>());
+        self.get_or_create_type_id_ecsid_dynamic(TypeId::of::<T>(), || {
+            ComponentMeta::from_generic::<T>()
+        })
+    }
+
+    /// ``component_meta`` must be the ComponentMeta of the type that ``type_id`` is for
+    pub(crate) fn get_or_create_type_id_ecsid_dynamic(
+        &mut self,
+        type_id: TypeId,
+        component_meta: impl FnOnce() -> ComponentMeta,
+    ) -> EcsId {
+        let comp_id = self.type_id_to_ecs_id.get(&type_id);
         if let Some(comp_id) = comp_id {
             return *comp_id;
         }
@@ -447,9 +875,9 @@ impl World {
 
         // Guaranteed valid because we just spawned the entity
         let meta = self.ecs_id_meta[entity.uindex()].as_mut().unwrap();
-        meta.component_meta = ComponentMeta::from_generic::<T>();
+        meta.component_meta = component_meta();
 
-        self.type_id_to_ecs_id.insert(TypeId::of::<T>(), entity);
+        self.type_id_to_ecs_id.insert(type_id, entity);
 
         entity
     }
@@ -486,98 +914,320 @@ impl World {
         }
     }
 
-    pub(crate) fn find_archetype_dynamic(&mut self, comp_ids: &[EcsId]) -> Option<ArchIndex> {
-        if self.archetypes.len() > 0 && comp_ids.len() == 0 {
-            assert!(self.archetypes[0].comp_ids.len() == 0);
-            return Some(ArchIndex(0));
-        }
-
-        let mut bit_length = u32::MAX;
+    /// Gets the archetype bits that ``fetch`` matches and their length for use with ``World::query_archetypes``, ``Without`` is
+    /// matched by negating the component's bits. ``or_bits`` must be the result of ``World::or_archetype_bits`` for ``FetchType::Or``.
+    /// Returns ``None`` if the fetch can't match any archetype
+    pub(crate) fn fetch_archetype_bits<'a>(
+        &'a self,
+        fetch: &FetchType,
+        or_bits: Option<&'a Bitvec>,
+    ) -> Option<((Iter<'a, usize>, fn(usize) -> usize), u32)> {
         let identity: fn(_) -> _ = |x: usize| x;
+        let negate: fn(_) -> _ = |x: usize| !x;
+        let all_archetypes = (
+            (self.entities_bitvec.data.iter(), identity),
+            self.entities_bitvec.len as u32,
+        );
 
-        for id in comp_ids {
-            let bitvec = self.archetype_bitset.get_bitvec(*id)?;
-            if bitvec.len < bit_length as _ {
-                bit_length = bitvec.len as _;
+        Some(match fetch {
+            FetchType::EcsId
+            | FetchType::Optional(_)
+            | FetchType::Resource(_)
+            | FetchType::ResourceMut(_) => all_archetypes,
+            FetchType::Without(id) => match self.archetype_bitset.get_bitvec(*id) {
+                Some(bitvec) => (
+                    (bitvec.data.iter(), negate),
+                    self.entities_bitvec.len as u32,
+                ),
+                None => all_archetypes,
+            },
+            FetchType::Or(_) => {
+                let bitvec = or_bits.unwrap();
+                ((bitvec.data.iter(), identity), bitvec.len as u32)
             }
+            FetchType::Mut(id)
+            | FetchType::Immut(id)
+            | FetchType::MatchedPair(id)
+            | FetchType::Added(id)
+            | FetchType::Changed(id)
+            | FetchType::With(id) => {
+                let bitvec = self.archetype_bitset.get_bitvec(*id)?;
+                ((bitvec.data.iter(), identity), bitvec.len as u32)
+            }
+        })
+    }
+
+    /// Creates the bits of every archetype that matches any of the filters in ``FetchType::Or``
+    pub(crate) fn or_archetype_bits(&self, filters: &[FetchType]) -> Bitvec {
+        let len = self.entities_bitvec.len;
+        let words = len.div_ceil(usize::BITS as usize);
+        Bitvec {
+            data: (0..words)
+                .map(|word| self.or_filter_word(filters, word))
+                .collect(),
+            len,
         }
+    }
 
-        let iters = comp_ids
+    fn or_filter_word(&self, filters: &[FetchType], word: usize) -> usize {
+        filters
             .iter()
-            .map(|&id| self.archetype_bitset.get_bitvec(id).unwrap().data.iter())
-            .map(|bitvec| (bitvec, identity))
-            .collect::<Box<[_]>>();
+            .fold(0, |bits, filter| bits | self.filter_word(filter, word))
+    }
+
+    fn filter_word(&self, fetch: &FetchType, word: usize) -> usize {
+        let component_word = |id| {
+            self.archetype_bitset
+                .get_bitvec(id)
+                .and_then(|bitvec| bitvec.data.get(word).copied())
+                .unwrap_or(0)
+        };
 
-        BitsetIterator::new(iters, bit_length)
-            .filter(|idx| self.archetypes[*idx].comp_ids.len() == comp_ids.len())
-            .next()
-            .map(ArchIndex)
+        match fetch {
+            FetchType::EcsId
+            | FetchType::Optional(_)
+            | FetchType::Resource(_)
+            | FetchType::ResourceMut(_) => self.entities_bitvec.data[word],
+            FetchType::Without(id) => !component_word(*id),
+            FetchType::Or(filters) => self.or_filter_word(filters, word),
+            FetchType::Mut(id)
+            | FetchType::Immut(id)
+            | FetchType::MatchedPair(id)
+            | FetchType::Added(id)
+            | FetchType::Changed(id)
+            | FetchType::With(id) => component_word(*id),
+        }
     }
 
-    pub(crate) fn find_archetype_dynamic_plus_id(
-        &self,
-        comp_ids: &[EcsId],
-        extra_id: EcsId,
-    ) -> Option<usize> {
-        let identity: fn(_) -> _ = |x: usize| x;
+    /// Returns the archetype with exactly the components in ``comp_ids``, ``comp_ids`` doesn't have to be sorted
+    pub(crate) fn find_archetype_dynamic(&self, comp_ids: &[EcsId]) -> Option<ArchIndex> {
+        if comp_ids.windows(2).all(|ids| ids[0] < ids[1]) {
+            return self.archetype_lookup.get(comp_ids).cloned();
+        }
+
+        let mut sorted_ids = comp_ids.to_vec();
+        sorted_ids.sort();
+        self.archetype_lookup.get(&sorted_ids[..]).cloned()
+    }
 
-        let mut bit_length = u32::MAX;
-        for id in comp_ids.iter().chain(std::iter::once(&extra_id)) {
-            let bitvec = self.archetype_bitset.get_bitvec(*id)?;
-            if bitvec.len < bit_length as _ {
-                bit_length = bitvec.len as _;
+    /// Returns the index of an unused lock in ``locks``
+    pub(crate) fn new_lock(&mut self) -> usize {
+        match self.free_locks.pop() {
+            Some(idx) => idx,
+            None => {
+                self.locks.push(RwLock::new(()));
+                self.locks.len() - 1
             }
         }
+    }
 
-        let iters = comp_ids
-            .iter()
-            .map(|&id| self.archetype_bitset.get_bitvec(id).unwrap().data.iter())
-            .map(|iter| (iter, identity))
-            .chain(std::iter::once((
-                self.archetype_bitset
-                    .get_bitvec(extra_id)
-                    .unwrap()
-                    .data
-                    .iter(),
-                identity,
-            )))
-            .collect::<Box<[_]>>();
-
-        BitsetIterator::new(iters, bit_length)
-            .filter(|idx| self.archetypes[*idx].comp_ids.len() == comp_ids.len() + 1)
-            .next()
+    /// Adds the archetype to the world and registers it in ``archetype_lookup``, no other archetype can have the same
+    /// components
+    pub(crate) fn push_archetype(&mut self, archetype: Archetype) -> ArchIndex {
+        let archetype_idx = self.archetypes.len();
+        for &id in archetype.comp_ids.iter() {
+            if !self.lock_lookup.contains_key(&id) {
+                let lock = self.new_lock();
+                self.lock_lookup.insert(id, lock);
+            }
+            self.archetype_bitset.set_bit(id, archetype_idx, true);
+        }
+        self.entities_bitvec.push_bit(true);
+
+        let replaced = self
+            .archetype_lookup
+            .insert(archetype.comp_ids.clone(), ArchIndex(archetype_idx));
+        assert!(replaced.is_none());
+        self.archetypes.push(archetype);
+        ArchIndex(archetype_idx)
     }
 
-    pub(crate) fn find_archetype_dynamic_minus_id(
-        &self,
-        comp_ids: &[EcsId],
-        without_id: EcsId,
-    ) -> Option<usize> {
-        if self.archetypes.len() > 0 && comp_ids.len() == 1 {
-            assert!(self.archetypes[0].comp_ids.len() == 0);
-            return Some(0);
+    /// Returns the archetype with exactly the components in ``comp_ids``, creating it if it doesn't exist yet.
+    /// ``comp_ids`` must be sorted and every id in it must be alive
+    pub(crate) fn find_or_create_archetype(&mut self, comp_ids: &[EcsId]) -> ArchIndex {
+        if let Some(idx) = self.find_archetype_dynamic(comp_ids) {
+            return idx;
         }
 
-        let identity: fn(_) -> _ = |x: usize| x;
+        let mut component_storages = Vec::with_capacity(comp_ids.len());
+        let mut comp_lookup =
+            HashMap::with_capacity_and_hasher(comp_ids.len(), crate::utils::TypeIdHasherBuilder());
+        for (n, &id) in comp_ids.iter().enumerate() {
+            let meta = self.get_component_meta(id).unwrap();
+            let type_info = untyped_vec::TypeInfo::new(meta.layout, meta.drop_fn);
+            component_storages.push((
+                id,
+                UnsafeCell::new(unsafe { UntypedVec::new_from_raw(type_info) }),
+                UnsafeCell::new(ColumnTicks::new()),
+            ));
+            comp_lookup.insert(id, n);
+        }
+
+        self.push_archetype(Archetype {
+            comp_lookup,
+            entities: Vec::new(),
+            component_storages,
+            comp_ids: comp_ids.to_vec(),
+            add_remove_cache: AddRemoveCache::new(),
+        })
+    }
+
+    /// Moves ``entity`` into the archetype with exactly the components in ``components`` with a single move, any
+    /// of its current components that aren't in ``components`` are dropped. ``Some`` is new data for the component
+    /// which replaces the entity's current component if it has one. Entities that aren't in an archetype yet must
+    /// have new data for every component.
+    ///
+    /// # Safety
+    ///
+    ///   Every id in ``components`` must be alive and unique.
+    ///   New data must match the component_meta of its id and must not be used after calling this function.
+    pub(crate) unsafe fn move_entity_dynamic(
+        &mut self,
+        entity: EcsId,
+        components: &mut [(EcsId, Option<*mut MaybeUninit<u8>>)],
+    ) {
+        components.sort_by_key(|(id, _)| *id);
+        let comp_ids = components.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+        let change_tick = self.change_tick();
+
+        let current = self
+            .get_entity_meta(entity)
+            .map(|meta| (meta.instance_meta.archetype.0, meta.instance_meta.index));
+        let removed = match current {
+            Some((current_idx, _)) => self.archetypes[current_idx]
+                .comp_ids
+                .iter()
+                .filter(|id| comp_ids.binary_search(id).is_err())
+                .copied()
+                .collect(),
+            None => Vec::new(),
+        };
+        let added = components
+            .iter()
+            .filter(|(_, new)| new.is_some())
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        let mut hooks = self.component_hooks(&removed, |meta| meta.on_remove);
+        hooks.extend(self.component_hooks(&added, |meta| meta.on_add));
+
+        let ArchIndex(target_idx) = match current {
+            Some((current_idx, _)) if self.archetypes[current_idx].comp_ids == comp_ids => {
+                ArchIndex(current_idx)
+            }
+            _ => self.find_or_create_archetype(&comp_ids),
+        };
 
-        let mut bit_length = u32::MAX;
-        for id in comp_ids.iter().filter(|&&id| id != without_id) {
-            let bitvec = self.archetype_bitset.get_bitvec(*id)?;
-            if bitvec.len < bit_length as _ {
-                bit_length = bitvec.len as _;
+        match current {
+            // Nothing to move, any new components replace the current ones in place
+            Some((current_idx, entity_idx)) if current_idx == target_idx => {
+                let archetype = &mut self.archetypes[current_idx];
+                for (n, &(_, new)) in components.iter().enumerate() {
+                    let new = match new {
+                        Some(new) => new,
+                        None => continue,
+                    };
+                    unsafe { archetype.replace_component(n, entity_idx, new, change_tick) };
+                }
+            }
+            Some((current_idx, entity_idx)) => {
+                let (current_archetype, target_archetype) =
+                    crate::utils::index_twice_mut(current_idx, target_idx, &mut self.archetypes);
+
+                for (id, storage, ticks) in current_archetype.component_storages.iter_mut() {
+                    let (storage, ticks) = (storage.get_mut(), ticks.get_mut());
+                    let kept = components
+                        .binary_search_by_key(id, |(id, _)| *id)
+                        .ok()
+                        .map(|n| &components[n]);
+
+                    match kept {
+                        Some(&(_, None)) => {
+                            let (_, tar_storage, tar_ticks) = &mut target_archetype
+                                .component_storages[target_archetype.comp_lookup[id]];
+                            // Safe because both storages are for the same component id
+                            unsafe {
+                                storage.swap_move_element_to_other_vec(
+                                    tar_storage.get_mut(),
+                                    entity_idx,
+                                )
+                            };
+                            tar_ticks.get_mut().push(ticks.swap_remove(entity_idx));
+                        }
+                        // Removed or replaced
+                        _ => {
+                            storage.swap_remove(entity_idx);
+                            ticks.swap_remove(entity_idx);
+                        }
+                    }
+                }
+
+                for (n, &(_, new)) in components.iter().enumerate() {
+                    if let Some(new) = new {
+                        let (_, storage, ticks) = &mut target_archetype.component_storages[n];
+                        unsafe { storage.get_mut().push_raw(new) };
+                        ticks.get_mut().push(ComponentTicks::new(change_tick));
+                    }
+                }
+
+                target_archetype.entities.push(entity);
+                self.ecs_id_meta[entity.uindex()]
+                    .as_mut()
+                    .unwrap()
+                    .instance_meta = InstanceMeta {
+                    archetype: ArchIndex(target_idx),
+                    index: target_archetype.entities.len() - 1,
+                };
+
+                current_archetype.entities.swap_remove(entity_idx);
+                if let Some(&swapped_entity) = current_archetype.entities.get(entity_idx) {
+                    self.ecs_id_meta[swapped_entity.uindex()]
+                        .as_mut()
+                        .unwrap()
+                        .instance_meta
+                        .index = entity_idx;
+                }
+            }
+            // A newly spawned entity, every component is new
+            None => {
+                let target_archetype = &mut self.archetypes[target_idx];
+                for (n, &(_, new)) in components.iter().enumerate() {
+                    let (_, storage, ticks) = &mut target_archetype.component_storages[n];
+                    unsafe { storage.get_mut().push_raw(new.unwrap()) };
+                    ticks.get_mut().push(ComponentTicks::new(change_tick));
+                }
+                target_archetype.entities.push(entity);
+
+                let entity_meta = EntityMeta {
+                    instance_meta: InstanceMeta {
+                        archetype: ArchIndex(target_idx),
+                        index: target_archetype.entities.len() - 1,
+                    },
+                    component_meta: ComponentMeta::unit(),
+                };
+                self.set_entity_meta(entity, entity_meta);
             }
         }
 
-        let iters = comp_ids
+        self.run_hooks(entity, hooks);
+    }
+
+    /// Returns the ``on_add`` or ``on_remove`` hook of each component in ``comp_ids`` that has one
+    pub(crate) fn component_hooks(
+        &self,
+        comp_ids: &[EcsId],
+        hook: fn(&ComponentMeta) -> Option<ComponentHook>,
+    ) -> Vec<ComponentHook> {
+        comp_ids
             .iter()
-            .filter(|&&id| id != without_id)
-            .map(|&id| self.archetype_bitset.get_bitvec(id).unwrap().data.iter())
-            .map(|iter| (iter, identity))
-            .collect::<Box<[_]>>();
+            .filter_map(|&id| self.get_component_meta(id).and_then(hook))
+            .collect()
+    }
 
-        BitsetIterator::new(iters, bit_length)
-            .filter(|idx| self.archetypes[*idx].comp_ids.len() == comp_ids.len() - 1)
-            .next()
+    /// Hooks are collected before the world is changed and run after so that they can freely change the world
+    pub(crate) fn run_hooks(&mut self, entity: EcsId, hooks: Vec<ComponentHook>) {
+        for hook in hooks {
+            hook(self, entity);
+        }
     }
 
     /// # Safety
@@ -593,7 +1243,7 @@ impl World {
         if !self.entities.is_alive(entity) {
             return;
         }
-        if !self.entities.is_alive(comp_id) {
+        if !self.is_component_alive(comp_id) {
             return;
         }
 
@@ -604,68 +1254,17 @@ impl World {
                 meta.instance_meta.index,
             )
         };
-        let current_archetype = &mut self.archetypes[current_archetype_idx.0];
+        let current_archetype = &self.archetypes[current_archetype_idx.0];
         // Note, this is important, caching will give us *wrong* results if we try and add a component that is in this archetype
-        assert!(!current_archetype.comp_ids.contains(&comp_id));
-
-        let target_archetype_idx = current_archetype
-            .try_find_next_archetype(comp_id)
-            .or_else(|| {
-                // Iterate every archeype to see if one exists
-                // TODO MAYBE: technically we dont need to iterate everything, we can calculate the exact archetype.type_ids the
-                // target archetype will have so we could store a hashmap of that -> archetype_idx in world to avoid this O(n) lookup
-
-                let current_archetype = &self.archetypes[current_archetype_idx.0];
-                let idx = self.find_archetype_dynamic_plus_id(&current_archetype.comp_ids, comp_id);
-
-                if let Some(idx) = idx {
-                    let current_archetype = &mut self.archetypes[current_archetype_idx.0];
-                    current_archetype.insert_archetype_cache(comp_id, idx);
-                }
-
-                idx
-            })
-            .map(ArchIndex)
-            .unwrap_or_else(|| {
-                // Create a new archetype
-                use std::collections::hash_map::Entry;
-                let entry = self.lock_lookup.entry(comp_id);
-                if let Entry::Vacant(entry) = entry {
-                    entry.insert(self.locks.len());
-                    self.locks.push(RwLock::new(()));
-                }
-
-                let (layout, drop_fn) = {
-                    let meta = self
-                        .get_entity_meta(comp_id)
-                        .unwrap()
-                        .component_meta
-                        .clone();
-                    (meta.layout, meta.drop_fn)
-                };
-
-                let archetype = unsafe {
-                    Archetype::from_archetype_with(
-                        &mut self.archetypes[current_archetype_idx.0],
-                        untyped_vec::TypeInfo::new(layout, drop_fn),
-                        comp_id,
-                    )
-                };
-
-                for id in archetype.comp_ids.iter() {
-                    self.archetype_bitset
-                        .set_bit(*id, self.archetypes.len(), true);
-                }
-                self.entities_bitvec.push_bit(true);
-
-                self.archetypes.push(archetype);
+        assert!(
+            !current_archetype.comp_ids.contains(&comp_id),
+            "Attempted to add a component the entity already has, use insert_component to replace it"
+        );
 
-                let archetypes_len = self.archetypes.len();
-                let current_archetype = &mut self.archetypes[current_archetype_idx.0];
-                current_archetype.insert_archetype_cache(comp_id, archetypes_len - 1);
-                ArchIndex(archetypes_len - 1)
-            });
+        let target_archetype_idx =
+            self.find_or_create_archetype_with(current_archetype_idx.clone(), comp_id);
 
+        let change_tick = self.change_tick();
         let (current_archetype, target_archetype) = crate::utils::index_twice_mut(
             current_archetype_idx.0,
             target_archetype_idx.0,
@@ -678,13 +1277,13 @@ impl World {
             current_archetype
                 .component_storages
                 .iter_mut()
-                .map(|(_, storage)| storage.get_mut()),
+                .map(|(_, storage, ticks)| (storage.get_mut(), ticks.get_mut())),
             target_archetype
                 .component_storages
                 .iter_mut()
                 .enumerate()
                 // Skip the extra storage in this archetype
-                .filter(|(n, (tar_id, _))| {
+                .filter(|(n, (tar_id, ..))| {
                     if *tar_id == comp_id {
                         assert!(skipped_idx.is_none());
                         skipped_idx = Some(*n);
@@ -692,24 +1291,28 @@ impl World {
                     }
                     true
                 })
-                .map(|(_, (_, storage))| storage.get_mut()),
+                .map(|(_, (_, storage, ticks))| (storage.get_mut(), ticks.get_mut())),
         )
-        .for_each(|(cur_storage, tar_storage)| unsafe {
-            // Safe because component_storages in archetypes are sorted and we skip the component_storage that isn't the same
-            cur_storage.swap_move_element_to_other_vec(tar_storage, entity_idx)
-        });
+        .for_each(
+            |((cur_storage, cur_ticks), (tar_storage, tar_ticks))| unsafe {
+                // Safe because component_storages in archetypes are sorted and we skip the component_storage that isn't the same
+                cur_storage.swap_move_element_to_other_vec(tar_storage, entity_idx);
+                tar_ticks.push(cur_ticks.swap_remove(entity_idx));
+            },
+        );
 
         if let None = skipped_idx {
             assert!(*target_archetype.comp_ids.last_mut().unwrap() == comp_id);
             skipped_idx = Some(target_archetype.component_storages.len() - 1);
         }
 
+        let (_, storage, ticks) = &mut target_archetype.component_storages[skipped_idx.unwrap()];
         unsafe {
-            target_archetype.component_storages[skipped_idx.unwrap()]
-                .1
+            storage
                 .get_mut()
                 .push_raw(component_ptr as *mut core::mem::MaybeUninit<u8>);
         }
+        ticks.get_mut().push(ComponentTicks::new(change_tick));
 
         target_archetype.entities.push(entity);
         self.ecs_id_meta[entity.uindex()]
@@ -728,66 +1331,513 @@ impl World {
                 .instance_meta
                 .index = entity_idx;
         }
+
+        let hooks = self.component_hooks(&[comp_id], |meta| meta.on_add);
+        self.run_hooks(entity, hooks);
     }
 
-    pub fn remove_component_dynamic(&mut self, entity: EcsId, comp_id: EcsId) {
+    /// Same as ``add_component_dynamic_with_data`` except if the entity already has the component it is dropped and
+    /// replaced in place instead of panicking
+    ///
+    /// # Safety
+    ///
+    ///   ``component_ptr`` must point to data that matches the component_meta of component_id.
+    ///   The data must also not be used after calling this function.
+    pub unsafe fn insert_component_dynamic_with_data(
+        &mut self,
+        entity: EcsId,
+        comp_id: EcsId,
+        component_ptr: *mut u8,
+    ) {
         if !self.entities.is_alive(entity) {
             return;
         }
-        if !self.entities.is_alive(comp_id) {
+        if !self.is_component_alive(comp_id) {
             return;
         }
 
-        let (current_archetype_idx, entity_idx) = {
-            let meta = self.get_entity_meta(entity).unwrap();
-            (
-                meta.instance_meta.archetype.clone(),
-                meta.instance_meta.index,
-            )
+        let change_tick = self.change_tick();
+        let meta = &self.get_entity_meta(entity).unwrap().instance_meta;
+        let (ArchIndex(archetype_idx), entity_idx) = (meta.archetype.clone(), meta.index);
+        let archetype = &mut self.archetypes[archetype_idx];
+        match archetype.comp_lookup.get(&comp_id) {
+            Some(&storage_idx) => {
+                let component_ptr = component_ptr as *mut MaybeUninit<u8>;
+                unsafe {
+                    archetype.replace_component(storage_idx, entity_idx, component_ptr, change_tick)
+                };
+                let hooks = self.component_hooks(&[comp_id], |meta| meta.on_add);
+                self.run_hooks(entity, hooks);
+            }
+            None => unsafe { self.add_component_dynamic_with_data(entity, comp_id, component_ptr) },
+        }
+    }
+
+    /// Returns the archetype that has the same components as ``current_archetype_idx`` plus ``comp_id``, creating it if it doesn't exist yet
+    pub(crate) fn find_or_create_archetype_with(
+        &mut self,
+        current_archetype_idx: ArchIndex,
+        comp_id: EcsId,
+    ) -> ArchIndex {
+        let current_archetype = &mut self.archetypes[current_archetype_idx.0];
+        // Note, this is important, caching will give us *wrong* results if we try and add a component that is in this archetype
+        assert!(!current_archetype.comp_ids.contains(&comp_id));
+
+        if let Some(idx) = current_archetype.try_find_next_archetype(comp_id) {
+            return ArchIndex(idx);
+        }
+        let mut comp_ids = current_archetype.comp_ids.clone();
+        let insert_idx = comp_ids.binary_search(&comp_id).unwrap_err();
+        comp_ids.insert(insert_idx, comp_id);
+
+        let target_archetype_idx = match self.find_archetype_dynamic(&comp_ids) {
+            Some(idx) => idx,
+            None => {
+                let meta = self.get_component_meta(comp_id).unwrap();
+                let type_info = untyped_vec::TypeInfo::new(meta.layout, meta.drop_fn);
+                // Safe because the type info is from the component meta of ``comp_id``
+                let archetype = unsafe {
+                    Archetype::from_archetype_with(
+                        &mut self.archetypes[current_archetype_idx.0],
+                        type_info,
+                        comp_id,
+                    )
+                };
+                self.push_archetype(archetype)
+            }
         };
+        self.link_archetypes(current_archetype_idx, target_archetype_idx.clone(), comp_id);
+        target_archetype_idx
+    }
+
+    /// Returns the archetype that has the same components as ``current_archetype_idx`` minus ``comp_id``, creating it if it doesn't exist yet
+    pub(crate) fn find_or_create_archetype_without(
+        &mut self,
+        current_archetype_idx: ArchIndex,
+        comp_id: EcsId,
+    ) -> ArchIndex {
         let current_archetype = &mut self.archetypes[current_archetype_idx.0];
         // Note, this is important, caching will give us *wrong* results if we try and remove a component that isnt in this archetype
         assert!(current_archetype.comp_ids.contains(&comp_id));
 
-        let target_archetype_idx = current_archetype
-            .try_find_next_archetype(comp_id)
-            .or_else(|| {
-                // Iterate every archeype to see if one exists
-                // TODO MAYBE: technically we dont need to iterate everything, we can calculate the exact archetype.type_ids the
-                // target archetype will have so we could store a hashmap of that -> archetype_idx in world to avoid this O(n) lookup
-
-                let current_archetype = &self.archetypes[current_archetype_idx.0];
-                let idx =
-                    self.find_archetype_dynamic_minus_id(&current_archetype.comp_ids, comp_id);
-
-                if let Some(idx) = idx {
-                    let current_archetype = &mut self.archetypes[current_archetype_idx.0];
-                    current_archetype.insert_archetype_cache(comp_id, idx);
-                }
+        if let Some(idx) = current_archetype.try_find_next_archetype(comp_id) {
+            return ArchIndex(idx);
+        }
+        let mut comp_ids = current_archetype.comp_ids.clone();
+        comp_ids.retain(|&id| id != comp_id);
 
-                idx
-            })
-            .map(ArchIndex)
-            .unwrap_or_else(|| {
-                // Create a new archetype
+        // The storages are copied from the current archetype as ``comp_id`` may be a pair that is being despawned
+        let target_archetype_idx = match self.find_archetype_dynamic(&comp_ids) {
+            Some(idx) => idx,
+            None => {
                 let archetype = Archetype::from_archetype_without(
                     &mut self.archetypes[current_archetype_idx.0],
                     comp_id,
                 );
+                self.push_archetype(archetype)
+            }
+        };
+        self.link_archetypes(current_archetype_idx, target_archetype_idx.clone(), comp_id);
+        target_archetype_idx
+    }
+
+    /// Adds an edge for ``comp_id`` to both archetypes, ``to`` must have the components of ``from`` plus or minus ``comp_id``
+    fn link_archetypes(&mut self, from: ArchIndex, to: ArchIndex, comp_id: EcsId) {
+        self.archetypes[from.0].insert_archetype_cache(comp_id, to.0);
+        self.archetypes[to.0].insert_archetype_cache(comp_id, from.0);
+    }
+
+    /// Moves every entity in ``archetype_idx`` into the archetype without ``comp_id``, dropping their ``comp_id`` component.
+    /// Entities keep their relative order when moved
+    fn move_archetype_without(&mut self, archetype_idx: ArchIndex, comp_id: EcsId) {
+        let target_archetype_idx =
+            self.find_or_create_archetype_without(archetype_idx.clone(), comp_id);
+
+        let (current_archetype, target_archetype) = crate::utils::index_twice_mut(
+            archetype_idx.0,
+            target_archetype_idx.0,
+            &mut self.archetypes,
+        );
+
+        for (id, storage, ticks) in current_archetype.component_storages.iter_mut() {
+            let (storage, ticks) = (storage.get_mut(), ticks.get_mut());
+            if *id == comp_id {
+                storage.clear();
+                ticks.clear();
+                continue;
+            }
+
+            let tar_storage_idx = target_archetype.comp_lookup[id];
+            let (_, tar_storage, tar_ticks) =
+                &mut target_archetype.component_storages[tar_storage_idx];
+            // Safe because both storages are for the same component id
+            unsafe { tar_storage.get_mut().append(storage) };
+            tar_ticks.get_mut().append(ticks);
+        }
+
+        let start_idx = target_archetype.entities.len();
+        target_archetype
+            .entities
+            .append(&mut current_archetype.entities);
+
+        for (n, entity) in target_archetype.entities[start_idx..].iter().enumerate() {
+            self.ecs_id_meta[entity.uindex()]
+                .as_mut()
+                .unwrap()
+                .instance_meta = InstanceMeta {
+                archetype: target_archetype_idx.clone(),
+                index: start_idx + n,
+            };
+        }
+    }
+
+    /// Moves every entity in the archetype to the archetype with ``comp_id`` added, each entity is given a clone of
+    /// ``component``
+    fn move_archetype_with<T: Component + Clone>(
+        &mut self,
+        archetype_idx: ArchIndex,
+        comp_id: EcsId,
+        component: &T,
+    ) {
+        // Clone up front so that a panicking ``clone`` can't leave the archetypes half moved
+        let added = self.archetypes[archetype_idx.0].entities.len();
+        let components: Vec<T> = (0..added).map(|_| component.clone()).collect();
+
+        let change_tick = self.change_tick();
+        let target_archetype_idx =
+            self.find_or_create_archetype_with(archetype_idx.clone(), comp_id);
+
+        let (current_archetype, target_archetype) = crate::utils::index_twice_mut(
+            archetype_idx.0,
+            target_archetype_idx.0,
+            &mut self.archetypes,
+        );
+
+        for (id, storage, ticks) in current_archetype.component_storages.iter_mut() {
+            let tar_storage_idx = target_archetype.comp_lookup[id];
+            let (_, tar_storage, tar_ticks) =
+                &mut target_archetype.component_storages[tar_storage_idx];
+            // Safe because both storages are for the same component id
+            unsafe { tar_storage.get_mut().append(storage.get_mut()) };
+            tar_ticks.get_mut().append(ticks.get_mut());
+        }
+
+        let (_, storage, ticks) =
+            &mut target_archetype.component_storages[target_archetype.comp_lookup[&comp_id]];
+        let (storage, ticks) = (storage.get_mut(), ticks.get_mut());
+        storage.reserve(added);
+        for component in components {
+            let mut component = ManuallyDrop::new(component);
+            // Safe because ``comp_id`` is the id for ``T``
+            unsafe { storage.push_raw(&mut *component as *mut T as *mut MaybeUninit<u8>) };
+            ticks.push(ComponentTicks::new(change_tick));
+        }
+
+        let start_idx = target_archetype.entities.len();
+        target_archetype
+            .entities
+            .append(&mut current_archetype.entities);
+
+        for (n, entity) in target_archetype.entities[start_idx..].iter().enumerate() {
+            self.ecs_id_meta[entity.uindex()]
+                .as_mut()
+                .unwrap()
+                .instance_meta = InstanceMeta {
+                archetype: target_archetype_idx.clone(),
+                index: start_idx + n,
+            };
+        }
+    }
 
-                for id in archetype.comp_ids.iter() {
-                    self.archetype_bitset
-                        .set_bit(*id, self.archetypes.len(), true);
+    /// The index of every archetype that ``fetches`` can match, row filters like ``Added`` and ``Changed`` are ignored
+    pub(crate) fn query_archetype_idxs(&self, fetches: &[FetchType]) -> Vec<usize> {
+        let or_bits = FetchType::or_archetype_bits(self, fetches);
+        let mut or_bits = or_bits.iter();
+        let mut bit_length = self.entities_bitvec.len as u32;
+        let iters = fetches
+            .iter()
+            .map(|fetch| {
+                let or_bits = match fetch {
+                    FetchType::Or(_) => or_bits.next(),
+                    _ => None,
+                };
+                let (iter, len) = self.fetch_archetype_bits(fetch, or_bits)?;
+                bit_length = u32::min(bit_length, len);
+                Some(iter)
+            })
+            .collect::<Option<Box<[_]>>>();
+
+        match iters {
+            Some(iters) => BitsetIterator::new(iters, bit_length).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Archetypes with entities that match ``Q``, panics if ``Q`` filters individual entities
+    fn matched_archetypes<Q: crate::static_query::QueryTuple>(&self) -> Vec<usize> {
+        let fetches = match Q::fetch_types(self) {
+            Some(fetches) => fetches,
+            None => return Vec::new(),
+        };
+        assert!(
+            !fetches
+                .as_ref()
+                .iter()
+                .any(|fetch| matches!(fetch, FetchType::Added(_) | FetchType::Changed(_))),
+            "Queries used to change components in bulk cannot contain Added or Changed"
+        );
+
+        self.query_archetype_idxs(fetches.as_ref())
+            .into_iter()
+            .filter(|&idx| !self.archetypes[idx].entities.is_empty())
+            .collect()
+    }
+
+    /// Adds a clone of ``component`` to every entity that matches ``Q``, entities that already have the component get
+    /// it replaced. Each matched archetype is moved all at once with a single copy per component storage
+    pub fn add_component_to_query<Q: crate::static_query::QueryTuple, T: Component + Clone>(
+        &mut self,
+        component: T,
+    ) {
+        let comp_id = self.get_or_create_type_id_ecsid::<T>();
+        let change_tick = self.change_tick();
+        // Archetypes that already have the component come first so that entities moved into them aren't replaced again
+        let (with, without): (Vec<_>, Vec<_>) = self
+            .matched_archetypes::<Q>()
+            .into_iter()
+            .partition(|&idx| self.archetypes[idx].comp_lookup.contains_key(&comp_id));
+
+        let hooks = self.component_hooks(&[comp_id], |meta| meta.on_add);
+        let mut added = Vec::new();
+        for idx in with {
+            let archetype = &mut self.archetypes[idx];
+            let storage_idx = archetype.comp_lookup[&comp_id];
+            for entity_idx in 0..archetype.entities.len() {
+                let mut new = ManuallyDrop::new(component.clone());
+                let new = &mut *new as *mut T as *mut MaybeUninit<u8>;
+                // Safe because ``comp_id`` is the id for ``T``
+                unsafe { archetype.replace_component(storage_idx, entity_idx, new, change_tick) };
+            }
+            if !hooks.is_empty() {
+                added.extend_from_slice(&archetype.entities);
+            }
+        }
+
+        for idx in without {
+            if !hooks.is_empty() {
+                added.extend_from_slice(&self.archetypes[idx].entities);
+            }
+            self.move_archetype_with(ArchIndex(idx), comp_id, &component);
+        }
+        for entity in added {
+            self.run_hooks(entity, hooks.clone());
+        }
+    }
+
+    /// Removes ``T`` from every entity that matches ``Q``, each matched archetype is moved all at once with a single
+    /// copy per component storage
+    pub fn remove_component_from_query<Q: crate::static_query::QueryTuple, T: Component>(
+        &mut self,
+    ) {
+        let comp_id = match self.type_id_to_ecs_id.get(&TypeId::of::<T>()) {
+            Some(&comp_id) => comp_id,
+            None => return,
+        };
+
+        let hooks = self.component_hooks(&[comp_id], |meta| meta.on_remove);
+        let mut removed = Vec::new();
+        for idx in self.matched_archetypes::<Q>() {
+            if !self.archetypes[idx].comp_lookup.contains_key(&comp_id) {
+                continue;
+            }
+            if !hooks.is_empty() {
+                removed.extend_from_slice(&self.archetypes[idx].entities);
+            }
+            self.move_archetype_without(ArchIndex(idx), comp_id);
+        }
+        for entity in removed {
+            self.run_hooks(entity, hooks.clone());
+        }
+    }
+
+    /// Removes ``comp_id`` from every entity that has it and forgets about ``comp_id`` being used as a component.
+    /// Called when ``comp_id`` is despawned, returns the entities that had the component
+    fn remove_component_from_all(&mut self, comp_id: EcsId) -> Vec<EcsId> {
+        let archetype_idxs = match self.archetype_bitset.get_bitvec(comp_id) {
+            Some(bitvec) => {
+                let identity: fn(_) -> _ = |x: usize| x;
+                BitsetIterator::new([(bitvec.data.iter(), identity)], bitvec.len as u32)
+                    .collect::<Vec<_>>()
+            }
+            None => Vec::new(),
+        };
+
+        // Archetypes left empty by an earlier despawned component would create an archetype with that component again
+        let mut removed = Vec::new();
+        for &idx in archetype_idxs.iter() {
+            if !self.archetypes[idx].entities.is_empty() {
+                removed.extend_from_slice(&self.archetypes[idx].entities);
+                self.move_archetype_without(ArchIndex(idx), comp_id);
+            }
+        }
+
+        // Archetypes with comp_id are left empty, clearing their bits, caches and lookup means they will never get used again
+        self.archetype_bitset.clear_bitvec(comp_id);
+        for &idx in archetype_idxs.iter() {
+            self.archetypes[idx].add_remove_cache.clear();
+            self.archetype_lookup.remove(&self.archetypes[idx].comp_ids);
+        }
+        for archetype in self.archetypes.iter_mut() {
+            archetype.add_remove_cache.remove_id(comp_id);
+        }
+
+        if let Some(lock_idx) = self.lock_lookup.remove(&comp_id) {
+            self.free_locks.push(lock_idx);
+        }
+
+        self.type_id_to_ecs_id.retain(|_, id| *id != comp_id);
+        removed
+    }
+
+    /// Removes every archetype that has no entities, freeing their storages and shrinking the archetype bitsets so that
+    /// queries don't have to skip over them. The archetype with no components is always kept
+    pub fn compact_archetypes(&mut self) {
+        let mut kept = 0;
+        let new_idxs = self
+            .archetypes
+            .iter()
+            .map(|archetype| match is_removable(archetype) {
+                true => None,
+                false => {
+                    kept += 1;
+                    Some(kept - 1)
                 }
-                self.entities_bitvec.push_bit(true);
+            })
+            .collect::<Vec<_>>();
+        if kept == self.archetypes.len() {
+            return;
+        }
+
+        let archetypes = std::mem::take(&mut self.archetypes);
+        self.archetypes = archetypes
+            .into_iter()
+            .zip(new_idxs.iter())
+            .filter(|(_, new_idx)| new_idx.is_some())
+            .map(|(archetype, _)| archetype)
+            .collect();
+
+        self.archetype_lookup.clear();
+        self.archetype_bitset = Bitsetsss::with_capacity(32);
+        self.entities_bitvec = Bitvec::with_capacity(self.archetypes.len());
+        for (idx, archetype) in self.archetypes.iter_mut().enumerate() {
+            archetype
+                .add_remove_cache
+                .remap(|old_idx| new_idxs[old_idx]);
+
+            for &id in archetype.comp_ids.iter() {
+                self.archetype_bitset.set_bit(id, idx, true);
+            }
+            self.entities_bitvec.push_bit(true);
+            self.archetype_lookup
+                .insert(archetype.comp_ids.clone(), ArchIndex(idx));
 
-                self.archetypes.push(archetype);
+            for entity in archetype.entities.iter() {
+                self.ecs_id_meta[entity.uindex()]
+                    .as_mut()
+                    .unwrap()
+                    .instance_meta
+                    .archetype = ArchIndex(idx);
+            }
+        }
+    }
 
-                let archetypes_len = self.archetypes.len();
-                let current_archetype = &mut self.archetypes[current_archetype_idx.0];
-                current_archetype.insert_archetype_cache(comp_id, archetypes_len - 1);
-                ArchIndex(archetypes_len - 1)
+    /// Calls ``World::compact_archetypes`` if there are enough empty archetypes, see ``World::set_archetype_compaction_threshold``
+    pub(crate) fn auto_compact_archetypes(&mut self) {
+        if let Some(threshold) = self.archetype_compaction_threshold {
+            let empty = self
+                .archetypes
+                .iter()
+                .filter(|archetype| is_removable(archetype))
+                .count();
+            if empty > 0 && empty >= threshold {
+                self.compact_archetypes();
+            }
+        }
+    }
+
+    pub fn remove_component_dynamic(&mut self, entity: EcsId, comp_id: EcsId) {
+        if !self.entities.is_alive(entity) {
+            return;
+        }
+        if !self.is_component_alive(comp_id) {
+            return;
+        }
+        // Safe because the component gets dropped instead of copied out
+        unsafe { self.remove_component_to(entity, comp_id, None) };
+    }
+
+    /// Same as ``World::remove_component_dynamic`` except returns an error if the entity doesn't have the component
+    pub fn try_remove_component_dynamic(
+        &mut self,
+        entity: EcsId,
+        comp_id: EcsId,
+    ) -> Result<(), EcsError> {
+        if !self.entities.is_alive(entity) {
+            return Err(EcsError::DeadEntity(entity));
+        }
+        if !self.is_component_alive(comp_id) {
+            return Err(EcsError::DeadEntity(comp_id));
+        }
+        if !self.has_component_dynamic(entity, comp_id) {
+            return Err(EcsError::MissingComponent {
+                entity,
+                component: comp_id,
             });
+        }
+        self.remove_component_dynamic(entity, comp_id);
+        Ok(())
+    }
+
+    /// Removes the component from the entity without dropping it, its data is copied to ``dst`` instead. Returns false
+    /// and leaves ``dst`` untouched if the entity doesn't have the component
+    ///
+    /// # Safety
+    ///
+    ///    ``dst`` must be valid for writes of the component's size, it does not need to be aligned. The caller takes
+    ///    ownership of the component
+    pub unsafe fn take_component_dynamic(
+        &mut self,
+        entity: EcsId,
+        comp_id: EcsId,
+        dst: *mut u8,
+    ) -> bool {
+        if !self.entities.is_alive(entity) || !self.is_component_alive(comp_id) {
+            return false;
+        }
+        if !self.has_component_dynamic(entity, comp_id) {
+            return false;
+        }
+        unsafe { self.remove_component_to(entity, comp_id, Some(dst as *mut MaybeUninit<u8>)) };
+        true
+    }
+
+    /// Moves the entity to the archetype without ``comp_id``, the component is copied to ``dst`` if there is one
+    /// otherwise it is dropped
+    unsafe fn remove_component_to(
+        &mut self,
+        entity: EcsId,
+        comp_id: EcsId,
+        dst: Option<*mut MaybeUninit<u8>>,
+    ) {
+        let (current_archetype_idx, entity_idx) = {
+            let meta = self.get_entity_meta(entity).unwrap();
+            (
+                meta.instance_meta.archetype.clone(),
+                meta.instance_meta.index,
+            )
+        };
+        let target_archetype_idx =
+            self.find_or_create_archetype_without(current_archetype_idx.clone(), comp_id);
 
         let (current_archetype, target_archetype) = crate::utils::index_twice_mut(
             current_archetype_idx.0,
@@ -801,7 +1851,7 @@ impl World {
                 .component_storages
                 .iter_mut()
                 .enumerate()
-                .filter(|(n, (id, _))| {
+                .filter(|(n, (id, ..))| {
                     if *id == comp_id {
                         assert!(skipped_storage.is_none());
                         skipped_storage = Some(*n);
@@ -809,26 +1859,33 @@ impl World {
                     }
                     true
                 })
-                .map(|(_, (_, storage))| storage.get_mut()),
+                .map(|(_, (_, storage, ticks))| (storage.get_mut(), ticks.get_mut())),
             target_archetype
                 .component_storages
                 .iter_mut()
-                .map(|(_, storage)| storage.get_mut()),
+                .map(|(_, storage, ticks)| (storage.get_mut(), ticks.get_mut())),
         )
-        .for_each(|(cur_storage, tar_storage)| unsafe {
-            // Safe because component_storages in archetypes are sorted and we skip the component_storage that isn't the same
-            cur_storage.swap_move_element_to_other_vec(tar_storage, entity_idx)
-        });
+        .for_each(
+            |((cur_storage, cur_ticks), (tar_storage, tar_ticks))| unsafe {
+                // Safe because component_storages in archetypes are sorted and we skip the component_storage that isn't the same
+                cur_storage.swap_move_element_to_other_vec(tar_storage, entity_idx);
+                tar_ticks.push(cur_ticks.swap_remove(entity_idx));
+            },
+        );
 
         if skipped_storage.is_none() {
             assert!(*current_archetype.comp_ids.last_mut().unwrap() == comp_id);
             skipped_storage = Some(current_archetype.component_storages.len() - 1);
         }
 
-        current_archetype.component_storages[skipped_storage.unwrap()]
-            .1
-            .get_mut()
-            .swap_remove(entity_idx);
+        let (_, storage, ticks) =
+            &mut current_archetype.component_storages[skipped_storage.unwrap()];
+        match dst {
+            // Safe because the caller guarantees ``dst`` is large enough for the component
+            Some(dst) => unsafe { storage.get_mut().swap_remove_to(entity_idx, dst) },
+            None => storage.get_mut().swap_remove(entity_idx),
+        }
+        ticks.get_mut().swap_remove(entity_idx);
 
         target_archetype.entities.push(entity);
         self.ecs_id_meta[entity.uindex()]
@@ -847,13 +1904,16 @@ impl World {
                 .instance_meta
                 .index = entity_idx;
         }
+
+        let hooks = self.component_hooks(&[comp_id], |meta| meta.on_remove);
+        self.run_hooks(entity, hooks);
     }
 
     pub fn get_component_mut_dynamic(&mut self, entity: EcsId, comp_id: EcsId) -> Option<*mut u8> {
         if !self.entities.is_alive(entity) {
             return None;
         }
-        if !self.entities.is_alive(comp_id) {
+        if !self.is_component_alive(comp_id) {
             return None;
         }
 
@@ -864,16 +1924,19 @@ impl World {
                 meta.instance_meta.index,
             )
         };
+        let change_tick = self.change_tick();
         let archetype = &mut self.archetypes[archetype_idx.0];
 
-        let component_storage_idx = archetype.comp_lookup[&comp_id];
+        let component_storage_idx = *archetype.comp_lookup.get(&comp_id)?;
 
-        Some(
-            archetype.component_storages[component_storage_idx]
-                .1
-                .get_mut()
-                .get_mut_raw(entity_idx)
-                .unwrap(),
-        )
+        let (_, storage, ticks) = &mut archetype.component_storages[component_storage_idx];
+        ticks.get_mut().mark_changed(entity_idx, change_tick);
+        Some(storage.get_mut().get_mut_raw(entity_idx).unwrap())
     }
 }
+
+/// Empty archetypes can be removed by ``World::compact_archetypes``, except for the archetype with no components which
+/// snapshots expect to be first
+fn is_removable(archetype: &Archetype) -> bool {
+    archetype.entities.is_empty() && !archetype.comp_ids.is_empty()
+}
@@ -0,0 +1,150 @@
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{
+    parse_macro_input, parse_quote, Data, DeriveInput, Fields, Generics, Index, LitStr, Path,
+};
+
+/// Implements ``Component``, options go in a ``#[component(...)]`` attribute:
+///
+///   ``name = "..."`` the name used by ``ComponentRegistry::register_component``, defaults to the name of the type
+///   ``snapshot`` ``text`` or ``raw`` registers the component's ``SnapshotComponent`` or ``TextComponent`` impl, or
+///   registers it as plain old data. ``raw`` calls the unsafe ``ComponentRegistry::register_raw`` so using it asserts
+///   that every bit pattern is a valid value of the type and that the type has no references or pointers
+///   ``on_add = path`` and ``on_remove = path`` set the component's hooks to a ``fn(&mut World, EcsId)``
+#[proc_macro_derive(Component, attributes(component))]
+pub fn derive_component(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match component_impl(input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Implements ``Bundle`` for a struct, each field is added as a separate component
+#[proc_macro_derive(Bundle)]
+pub fn derive_bundle(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match bundle_impl(input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Every type parameter has to be ``'static`` for the type to be ``'static``
+fn add_static_bounds(generics: &mut Generics) {
+    for param in generics.type_params_mut() {
+        param.bounds.push(parse_quote!('static));
+    }
+}
+
+fn component_impl(mut input: DeriveInput) -> syn::Result<TokenStream2> {
+    let ident = &input.ident;
+    let mut name = LitStr::new(&ident.to_string(), ident.span());
+    let mut register = Vec::new();
+    let mut on_add: Option<Path> = None;
+    let mut on_remove: Option<Path> = None;
+
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|a| a.path().is_ident("component"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                name = meta.value()?.parse()?;
+            } else if meta.path.is_ident("snapshot") {
+                register.push(format_ident!("register"));
+            } else if meta.path.is_ident("text") {
+                register.push(format_ident!("register_text"));
+            } else if meta.path.is_ident("raw") {
+                register.push(format_ident!("register_raw"));
+            } else if meta.path.is_ident("on_add") {
+                on_add = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("on_remove") {
+                on_remove = Some(meta.value()?.parse()?);
+            } else {
+                return Err(meta.error("unknown component option"));
+            }
+            Ok(())
+        })?;
+    }
+
+    let register = register
+        .iter()
+        .map(|register| match register == "register_raw" {
+            // Safe because using ``raw`` is the user asserting that the type is plain old data
+            true => quote!(unsafe { registry.register_raw::<Self>(#name) }),
+            false => quote!(registry.#register::<Self>(#name)),
+        });
+    let hook = |hook: Option<Path>| match hook {
+        Some(path) => quote!(::core::option::Option::Some(#path)),
+        None => quote!(::core::option::Option::None),
+    };
+    let (on_add, on_remove) = (hook(on_add), hook(on_remove));
+
+    add_static_bounds(&mut input.generics);
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::arche_tape::Component for #ident #ty_generics #where_clause {
+            const ON_ADD: ::core::option::Option<::arche_tape::ComponentHook> = #on_add;
+            const ON_REMOVE: ::core::option::Option<::arche_tape::ComponentHook> = #on_remove;
+
+            fn name() -> &'static str {
+                #name
+            }
+
+            fn register(registry: &mut ::arche_tape::ComponentRegistry) {
+                #(#register;)*
+            }
+        }
+    })
+}
+
+fn bundle_impl(mut input: DeriveInput) -> syn::Result<TokenStream2> {
+    let ident = &input.ident;
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                "Bundle can only be derived for structs",
+            ))
+        }
+    };
+
+    let types = fields.iter().map(|field| &field.ty).collect::<Vec<_>>();
+    let vars = (0..types.len())
+        .map(|n| format_ident!("field_{}", n))
+        .collect::<Vec<_>>();
+    let destructure = match fields {
+        Fields::Named(fields) => {
+            let names = fields.named.iter().map(|field| &field.ident);
+            quote!(#ident { #(#names: #vars),* })
+        }
+        Fields::Unnamed(_) => {
+            let indices = (0..types.len()).map(Index::from);
+            quote!(#ident { #(#indices: #vars),* })
+        }
+        Fields::Unit => quote!(#ident),
+    };
+
+    add_static_bounds(&mut input.generics);
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        unsafe impl #impl_generics ::arche_tape::Bundle for #ident #ty_generics #where_clause {
+            fn component_ids(world: &mut ::arche_tape::World) -> ::std::vec::Vec<::arche_tape::EcsId> {
+                ::std::vec![#(world.get_or_create_type_id_ecsid::<#types>(),)*]
+            }
+
+            #[allow(unused_variables)]
+            fn take_components(self, func: impl ::core::ops::FnOnce(&[*mut u8])) {
+                let #destructure = self;
+                #(
+                    let mut #vars = ::core::mem::ManuallyDrop::new(#vars);
+                )*
+                func(&[#(&mut *#vars as *mut #types as *mut u8,)*]);
+            }
+        }
+    })
+}
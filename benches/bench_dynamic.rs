@@ -1,10 +1,12 @@
 use arche_tape::world::ComponentMeta;
+use arche_tape::Component;
 use arche_tape::EcsId;
 use arche_tape::FetchType;
 use arche_tape::World;
 
 pub mod frag_iter_20_padding_20 {
     use super::*;
+    #[derive(Component)]
     pub struct Data(f32);
     pub struct Benchmark(World, EcsId);
 
@@ -70,6 +72,7 @@ pub mod frag_iter_20_padding_20 {
 
 pub mod frag_iter_20 {
     use super::*;
+    #[derive(Component)]
     pub struct Data(f32);
     pub struct Benchmark(World, EcsId);
 
@@ -120,6 +123,7 @@ pub mod frag_iter_20 {
 
 pub mod frag_iter_200 {
     use super::*;
+    #[derive(Component)]
     pub struct Data(f32);
     pub struct Benchmark(World, EcsId);
 
@@ -170,6 +174,7 @@ pub mod frag_iter_200 {
 
 pub mod frag_iter_2000 {
     use super::*;
+    #[derive(Component)]
     pub struct Data(f32);
     pub struct Benchmark(World, EcsId);
 
@@ -222,13 +227,13 @@ pub mod simple_iter {
     use super::*;
     use cgmath::*;
 
-    #[derive(Copy, Clone)]
+    #[derive(Copy, Clone, Component)]
     struct Transform(Matrix4<f32>);
-    #[derive(Copy, Clone)]
+    #[derive(Copy, Clone, Component)]
     struct Position(Vector3<f32>);
-    #[derive(Copy, Clone)]
+    #[derive(Copy, Clone, Component)]
     struct Rotation(Vector3<f32>);
-    #[derive(Copy, Clone)]
+    #[derive(Copy, Clone, Component)]
     struct Velocity(Vector3<f32>);
 
     pub struct Benchmark(World, EcsId, EcsId);
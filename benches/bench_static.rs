@@ -3,26 +3,30 @@ use arche_tape::spawn;
 pub mod frag_iter_20_padding_20 {
     use super::spawn;
     use arche_tape::world::World;
+    use arche_tape::Component;
 
+    #[derive(Component)]
     pub struct Data(f32);
 
     macro_rules! setup {
         ($world:ident, (bloat: ($($y:ident,)*)), ($($x:ident),*)) => {
             $(
+                #[derive(Component)]
                 pub struct $x(f32);
             )*
 
             $(
+                #[derive(Component)]
                 pub struct $y(f32);
             )*
 
             $(
                 for _ in 0..20 {
-                    spawn_entity(&mut $world, $x);
+                    spawn_entity(&mut $world, $x(0.));
                 }
             )*
 
-            fn spawn_entity<T: 'static>(world: &mut World, data: T) {
+            fn spawn_entity<T: Component>(world: &mut World, data: T) {
                 spawn!(&mut world, data, $($y(2.),)* Data(1.));
             }
         };
@@ -77,12 +81,15 @@ pub mod frag_iter_20_padding_20 {
 pub mod frag_iter_2000 {
     use arche_tape::spawn;
     use arche_tape::world::World;
+    use arche_tape::Component;
 
+    #[derive(Component)]
     pub struct Data(f32);
 
     macro_rules! setup {
         ($world:ident, $($x:ident),*) => {
             $(
+                #[derive(Component)]
                 pub struct $x(f32);
             )*
 
@@ -116,12 +123,15 @@ pub mod frag_iter_2000 {
 pub mod frag_iter_200 {
     use arche_tape::spawn;
     use arche_tape::world::World;
+    use arche_tape::Component;
 
+    #[derive(Component)]
     pub struct Data(f32);
 
     macro_rules! setup {
         ($world:ident, $($x:ident),*) => {
             $(
+                #[derive(Component)]
                 pub struct $x(f32);
             )*
 
@@ -169,12 +179,15 @@ pub mod frag_iter_200 {
 pub mod frag_iter_20 {
     use arche_tape::spawn;
     use arche_tape::world::World;
+    use arche_tape::Component;
 
+    #[derive(Component)]
     pub struct Data(f32);
 
     macro_rules! setup {
         ($world:ident, $($x:ident),*) => {
             $(
+                #[derive(Component)]
                 pub struct $x(f32);
             )*
 
@@ -208,15 +221,16 @@ pub mod frag_iter_20 {
 pub mod simple_iter {
     use arche_tape::spawn;
     use arche_tape::world::World;
+    use arche_tape::Component;
     use cgmath::*;
 
-    #[derive(Copy, Clone)]
+    #[derive(Copy, Clone, Component)]
     struct Transform(Matrix4<f32>);
-    #[derive(Copy, Clone)]
+    #[derive(Copy, Clone, Component)]
     struct Position(Vector3<f32>);
-    #[derive(Copy, Clone)]
+    #[derive(Copy, Clone, Component)]
     struct Rotation(Vector3<f32>);
-    #[derive(Copy, Clone)]
+    #[derive(Copy, Clone, Component)]
     struct Velocity(Vector3<f32>);
 
     pub struct Benchmark(World);
@@ -252,15 +266,16 @@ pub mod simple_iter {
 pub mod simple_insert {
     use arche_tape::spawn;
     use arche_tape::world::World;
+    use arche_tape::Component;
     use cgmath::*;
 
-    #[derive(Copy, Clone)]
+    #[derive(Copy, Clone, Component)]
     struct Transform(Matrix4<f32>);
-    #[derive(Copy, Clone)]
+    #[derive(Copy, Clone, Component)]
     struct Position(Vector3<f32>);
-    #[derive(Copy, Clone)]
+    #[derive(Copy, Clone, Component)]
     struct Rotation(Vector3<f32>);
-    #[derive(Copy, Clone)]
+    #[derive(Copy, Clone, Component)]
     struct Velocity(Vector3<f32>);
 
     pub struct Benchmark();
@@ -289,11 +304,13 @@ pub mod simple_insert {
 pub mod frag_insert {
     use arche_tape::spawn;
     use arche_tape::world::World;
+    use arche_tape::Component;
     use cgmath::*;
 
     macro_rules! setup {
         ($world:ident, $($x:ident),*) => {
             $(
+                #[derive(Component)]
                 pub struct $x(());
             )*
 
@@ -311,13 +328,13 @@ pub mod frag_insert {
         }
     }
 
-    #[derive(Copy, Clone)]
+    #[derive(Copy, Clone, Component)]
     struct Transform(Matrix4<f32>);
-    #[derive(Copy, Clone)]
+    #[derive(Copy, Clone, Component)]
     struct Position(Vector3<f32>);
-    #[derive(Copy, Clone)]
+    #[derive(Copy, Clone, Component)]
     struct Rotation(Vector3<f32>);
-    #[derive(Copy, Clone)]
+    #[derive(Copy, Clone, Component)]
     struct Velocity(Vector3<f32>);
 
     pub struct Benchmark();
@@ -339,14 +356,23 @@ pub mod frag_insert {
 pub mod simple_large_iter {
     use arche_tape::spawn;
     use arche_tape::world::World;
+    use arche_tape::Component;
 
+    #[derive(Component)]
     pub struct A(f32);
+    #[derive(Component)]
     pub struct B(f32);
+    #[derive(Component)]
     pub struct C(f32);
+    #[derive(Component)]
     pub struct D(f32);
+    #[derive(Component)]
     pub struct E(f32);
+    #[derive(Component)]
     pub struct F(f32);
+    #[derive(Component)]
     pub struct G(f32);
+    #[derive(Component)]
     pub struct H(f32);
 
     pub struct Benchmark(World);
@@ -388,10 +414,11 @@ pub mod add_remove {
     use arche_tape::entities::EcsId;
     use arche_tape::spawn;
     use arche_tape::world::World;
+    use arche_tape::Component;
 
-    #[derive(Copy, Clone)]
+    #[derive(Copy, Clone, Component)]
     struct A(f32);
-    #[derive(Copy, Clone)]
+    #[derive(Copy, Clone, Component)]
     struct B(f32);
 
     pub struct Benchmark(World, Box<[EcsId]>);
@@ -423,9 +450,13 @@ pub mod padded_add_remove {
     use arche_tape::entities::EcsId;
     use arche_tape::spawn;
     use arche_tape::world::World;
+    use arche_tape::Component;
 
+    #[derive(Component)]
     struct Padding([u8; 1024]);
+    #[derive(Component)]
     struct A(f32);
+    #[derive(Component)]
     struct B(f32);
 
     pub struct Benchmark(World, Box<[EcsId]>);
@@ -457,15 +488,25 @@ pub mod wide_add_remove {
     use arche_tape::entities::EcsId;
     use arche_tape::spawn;
     use arche_tape::world::World;
+    use arche_tape::Component;
 
+    #[derive(Component)]
     struct P1([u8; 128]);
+    #[derive(Component)]
     struct P2([u8; 128]);
+    #[derive(Component)]
     struct P3([u8; 128]);
+    #[derive(Component)]
     struct P4([u8; 128]);
+    #[derive(Component)]
     struct P5([u8; 128]);
+    #[derive(Component)]
     struct P6([u8; 128]);
+    #[derive(Component)]
     struct P7([u8; 128]);
+    #[derive(Component)]
     struct P8([u8; 128]);
+    #[derive(Component)]
     struct B(f32);
 
     pub struct Benchmark(World, Box<[EcsId]>);
@@ -507,7 +548,9 @@ pub mod get {
     use arche_tape::entities::EcsId;
     use arche_tape::spawn;
     use arche_tape::world::World;
+    use arche_tape::Component;
 
+    #[derive(Component)]
     pub struct A(f32);
 
     pub struct Benchmark(World, Box<[EcsId]>);
@@ -539,12 +582,14 @@ pub mod padded_get {
     use arche_tape::entities::EcsId;
     use arche_tape::spawn;
     use arche_tape::world::World;
+    use arche_tape::Component;
 
+    #[derive(Component)]
     pub struct Data(f32);
 
     macro_rules! create_entities {
         ($world:ident; $entities:ident; $($dummy:ident),*) => {
-            $(pub struct $dummy(f32);)*
+            $(#[derive(Component)] pub struct $dummy(f32);)*
 
             for _ in 0..10_000 {
                 let entity = spawn!(&mut $world, $($dummy(1.0)),*);
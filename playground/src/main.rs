@@ -1,7 +1,9 @@
 use arche_tape::entities::EcsId;
 use arche_tape::spawn;
 use arche_tape::world::World;
+use arche_tape::Component;
 
+#[derive(Component)]
 pub struct A(f32);
 
 pub struct Benchmark(World, Box<[EcsId]>);
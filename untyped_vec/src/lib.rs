@@ -207,7 +207,7 @@ impl UntypedVec {
                 drop_fn(ptr);
             }
             true
-        } else if self.len >= self.type_info.layout.size() {
+        } else if self.type_info.layout.size() > 0 && self.len >= self.type_info.layout.size() {
             self.len -= self.type_info.layout.size();
             let ptr = self.data.as_ptr();
             // Safe because we're offsetting inside of the allocation
@@ -272,6 +272,56 @@ impl UntypedVec {
         }
     }
 
+    /// Reallocates until there is room for at least ``additional`` more elements
+    pub fn reserve(&mut self, additional: usize) {
+        let size = self.type_info.layout.size();
+        if size == 0 {
+            return;
+        }
+
+        let required = self.len + additional * size;
+        while required > self.cap {
+            self.realloc();
+        }
+    }
+
+    /// Moves every element of ``other`` onto the end of this vec, leaving ``other`` empty.
+    /// The elements keep their order
+    ///
+    /// # Safety
+    ///
+    ///  The other UntypedVec must be of the same type
+    pub unsafe fn append(&mut self, other: &mut UntypedVec) {
+        assert!(self.type_info == other.type_info);
+
+        if self.type_info.layout.size() == 0 {
+            self.len += other.len;
+            other.len = 0;
+            return;
+        }
+
+        if other.len == 0 {
+            return;
+        }
+
+        self.reserve(other.len());
+        unsafe {
+            // Safe because we reserved enough room for other's elements and are offsetting within the allocation
+            let dst = self.data.as_ptr().add(self.len);
+            // The pointers are guaranteed to be nonoverlapping as we are writing to uninitialised memory in this vec
+            std::ptr::copy_nonoverlapping(other.data.as_ptr(), dst, other.len);
+        }
+
+        self.len += other.len;
+        // Setting the length of other to zero is effectively mem::forget on the moved elements
+        other.len = 0;
+    }
+
+    /// Drops every element in the vec, keeping the allocation
+    pub fn clear(&mut self) {
+        while self.pop() {}
+    }
+
     pub fn swap_remove(&mut self, element: usize) {
         assert!(self.len > 0);
 
@@ -303,19 +353,45 @@ impl UntypedVec {
         }
     }
 
+    /// Same as ``swap_remove`` except the element is copied to ``dst`` instead of being dropped
+    ///
+    /// # Safety
+    ///
+    ///   ``dst`` must be valid for writes of ``type_info.layout.size()`` bytes, it does not need to be aligned
+    pub unsafe fn swap_remove_to(&mut self, element: usize, dst: *mut MaybeUninit<u8>) {
+        assert!(self.len > 0);
+
+        let size = self.type_info.layout.size();
+        if size == 0 {
+            self.len -= 1;
+            return;
+        }
+        assert!(element < self.len / size);
+
+        let data = self.data.as_ptr() as *mut MaybeUninit<u8>;
+        unsafe {
+            // Safe because we're offsetting inside the allocation and len is never >= isize::MAX
+            let to_remove = data.add(element * size);
+            let last = data.add(self.len - size);
+            std::ptr::copy_nonoverlapping(to_remove, dst, size);
+            if to_remove != last {
+                // Safe because the removed element was copied out so its slot can be overwritten
+                std::ptr::copy_nonoverlapping(last, to_remove, size);
+            }
+        }
+
+        // Reducing the length without dropping is effectively mem::forget on the copied element
+        self.len -= size;
+    }
+
     /// # Safety
     ///
     ///   The generic used must be the same as the type used for push_raw and must correspond to the data for the EcsId in TypeInfo
     #[allow(unused_unsafe)]
     pub unsafe fn as_slice<'a, T: 'static>(&'a self) -> &'a [T] {
         assert!(self.type_info.layout == core::alloc::Layout::new::<T>());
-        assert!(self.len % self.type_info.layout.size() == 0);
-
-        let slice_len = if self.type_info.layout.size() == 0 {
-            self.len
-        } else {
-            self.len / self.type_info.layout.size()
-        };
+        // ``len`` handles zero sized types which would otherwise divide by zero
+        let slice_len = self.len();
 
         unsafe {
             // Safe because we've really failed our job as an untyped vec if the data isnt aligned to T and size of T
@@ -330,13 +406,8 @@ impl UntypedVec {
     #[allow(unused_unsafe)]
     pub unsafe fn as_slice_mut<'a, T: 'static>(&'a mut self) -> &'a mut [T] {
         assert!(self.type_info.layout == core::alloc::Layout::new::<T>());
-        assert!(self.len % self.type_info.layout.size() == 0);
-
-        let slice_len = if self.type_info.layout.size() == 0 {
-            self.len
-        } else {
-            self.len / self.type_info.layout.size()
-        };
+        // ``len`` handles zero sized types which would otherwise divide by zero
+        let slice_len = self.len();
 
         unsafe {
             // Safe because we've really failed our job as an untyped vec if the data isnt aligned to T and size of T
@@ -478,6 +549,21 @@ mod untyped_vec {
         assert!(slice[0] == 10);
     }
 
+    #[test]
+    pub fn as_slice_zero_sized() {
+        let mut untyped_vec = untyped_vec_new::<()>();
+
+        for _ in 0..3 {
+            let mut data = ManuallyDrop::new(());
+            unsafe {
+                untyped_vec.push_raw(&mut data as *mut _ as *mut MaybeUninit<u8>);
+            }
+        }
+
+        assert!(unsafe { untyped_vec.as_slice::<()>() }.len() == 3);
+        assert!(unsafe { untyped_vec.as_slice_mut::<()>() }.len() == 3);
+    }
+
     #[test]
     pub fn pop() {
         let mut untyped_vec = untyped_vec_new::<u32>();
@@ -644,4 +730,88 @@ mod untyped_vec {
         assert!(dropped == true);
         assert!(untyped_vec.len == 0);
     }
+
+    #[test]
+    pub fn remove_to() {
+        let mut dropped = false;
+        pub struct Wrap(u32, *mut bool);
+        impl Drop for Wrap {
+            fn drop(&mut self) {
+                unsafe { *self.1 = true };
+            }
+        }
+
+        let mut untyped_vec = untyped_vec_new::<Wrap>();
+        for n in 0..3 {
+            let data = Wrap(n, &mut dropped as *mut bool);
+            let mut data = ManuallyDrop::new(data);
+            unsafe {
+                untyped_vec.push_raw(&mut data as *mut _ as *mut MaybeUninit<u8>);
+            }
+        }
+
+        let mut removed = MaybeUninit::<Wrap>::uninit();
+        unsafe {
+            untyped_vec.swap_remove_to(0, removed.as_mut_ptr() as *mut MaybeUninit<u8>);
+        }
+
+        assert!(dropped == false);
+        assert!(untyped_vec.len == std::mem::size_of::<Wrap>() * 2);
+        let removed = unsafe { removed.assume_init() };
+        assert!(removed.0 == 0);
+        assert!(unsafe { untyped_vec.as_slice::<Wrap>()[0].0 } == 2);
+        assert!(unsafe { untyped_vec.as_slice::<Wrap>()[1].0 } == 1);
+        std::mem::forget(removed);
+    }
+
+    #[test]
+    pub fn append() {
+        let mut untyped_vec_1 = untyped_vec_new::<u32>();
+        let mut untyped_vec_2 = untyped_vec_new::<u32>();
+
+        for n in 0..3_u32 {
+            let mut data = ManuallyDrop::new(n);
+            unsafe {
+                untyped_vec_1.push_raw(&mut data as *mut _ as *mut MaybeUninit<u8>);
+            }
+        }
+        for n in 3..8_u32 {
+            let mut data = ManuallyDrop::new(n);
+            unsafe {
+                untyped_vec_2.push_raw(&mut data as *mut _ as *mut MaybeUninit<u8>);
+            }
+        }
+
+        unsafe {
+            untyped_vec_1.append(&mut untyped_vec_2);
+        }
+
+        assert!(untyped_vec_2.len == 0);
+        assert!(untyped_vec_1.len() == 8);
+        assert!(unsafe { untyped_vec_1.as_slice::<u32>() } == [0, 1, 2, 3, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    pub fn clear() {
+        let mut dropped = false;
+        pub struct Wrap(u32, *mut bool);
+        impl Drop for Wrap {
+            fn drop(&mut self) {
+                unsafe { *self.1 = true };
+            }
+        }
+
+        let mut untyped_vec = untyped_vec_new::<Wrap>();
+        let data = Wrap(10, &mut dropped as *mut bool);
+        let mut data = ManuallyDrop::new(data);
+        unsafe {
+            untyped_vec.push_raw(&mut data as *mut _ as *mut MaybeUninit<u8>);
+        }
+
+        untyped_vec.clear();
+
+        assert!(dropped == true);
+        assert!(untyped_vec.len == 0);
+        assert!(untyped_vec.cap == std::mem::size_of::<Wrap>() * 4);
+    }
 }